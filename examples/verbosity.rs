@@ -0,0 +1,29 @@
+//! # CCB Logger - Verbosity Flags Example
+//!
+//! This example shows how to wire CCB's `Verbosity` flags into a clap CLI.
+//! Run it with `-v`, `-vv`, `-q`, or `-qq` to see the level change:
+//!
+//! ```bash
+//! cargo run --example verbosity --features clap -- -vv
+//! ```
+
+use ccb::{debug, error, info, set_global_logger, trace, warn, Logger, Verbosity};
+use clap::Parser;
+
+#[derive(Parser)]
+struct Cli {
+    #[command(flatten)]
+    verbosity: Verbosity,
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    set_global_logger(Logger::new().with_level_from_verbosity_flag(&cli.verbosity));
+
+    trace!("Shown with -vv");
+    debug!("Shown with -v");
+    info!("Shown by default");
+    warn!("Hidden with -qq");
+    error!("Always shown");
+}
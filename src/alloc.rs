@@ -0,0 +1,184 @@
+//! Allocation counting for measuring memory use around blocks of code.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    /// Bytes and allocations made by this thread so far.
+    ///
+    /// Const-initialized and free of destructors, so accessing it never
+    /// allocates and is safe from inside the allocator.
+    static ALLOCATED: Cell<AllocStats> = const { Cell::new(AllocStats { bytes: 0, count: 0 }) };
+}
+
+/// A global allocator wrapper that counts allocations per thread.
+///
+/// Install it with `#[global_allocator]` to make [`AllocStats::current`] and
+/// the [`measure_alloc!`](crate::measure_alloc) macro report real numbers;
+/// without it, every measurement is zero. Requires the `alloc-counter` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::CountingAllocator;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator::system();
+/// ```
+#[derive(Debug, Default)]
+pub struct CountingAllocator<A = System> {
+    /// The allocator that performs the actual allocations.
+    inner: A,
+}
+
+impl CountingAllocator<System> {
+    /// Creates a counting wrapper around the system allocator.
+    pub const fn system() -> Self {
+        Self { inner: System }
+    }
+}
+
+impl<A> CountingAllocator<A> {
+    /// Creates a counting wrapper around another allocator.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The allocator that performs the actual allocations
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+/// Adds an allocation of `bytes` to the current thread's counters.
+fn record(bytes: usize) {
+    let _ = ALLOCATED.try_with(|allocated| {
+        let stats = allocated.get();
+        allocated.set(AllocStats {
+            bytes: stats.bytes + bytes as u64,
+            count: stats.count + 1,
+        });
+    });
+}
+
+// SAFETY: Every call is forwarded unchanged to the wrapped allocator, which
+// upholds the `GlobalAlloc` contract; counting only touches a thread-local
+// `Cell` and never allocates.
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        if !ptr.is_null() {
+            record(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = self.inner.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record(new_size.saturating_sub(layout.size()));
+        }
+        new_ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+    }
+}
+
+/// Allocation counters for the current thread.
+///
+/// Reallocations count as one allocation of the bytes they grow by. Frees are
+/// not subtracted, so the counters only ever increase.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    /// Total bytes allocated.
+    pub bytes: u64,
+    /// Number of allocations.
+    pub count: u64,
+}
+
+impl AllocStats {
+    /// Returns the current thread's counters.
+    ///
+    /// These are only non-zero when [`CountingAllocator`] is the global
+    /// allocator.
+    pub fn current() -> Self {
+        ALLOCATED.try_with(Cell::get).unwrap_or_default()
+    }
+
+    /// Returns the allocations made between `earlier` and `self`.
+    pub fn since(&self, earlier: &AllocStats) -> AllocStats {
+        AllocStats {
+            bytes: self.bytes.saturating_sub(earlier.bytes),
+            count: self.count.saturating_sub(earlier.count),
+        }
+    }
+}
+
+/// Runs a block and logs the memory it allocated on the current thread.
+///
+/// The entry uses the given message and carries `alloc_bytes` and
+/// `alloc_count` fields. The block's value is returned. Measurements require
+/// [`CountingAllocator`] to be installed as the global allocator, and the
+/// `alloc-counter` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{measure_alloc, CountingAllocator, Level};
+///
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator::system();
+///
+/// let words = measure_alloc!(Level::Debug, "parse", {
+///     "a b c".split(' ').map(String::from).collect::<Vec<_>>()
+/// });
+/// // Logs e.g. "DEBG parse alloc_bytes=99 alloc_count=4"
+/// assert_eq!(words.len(), 3);
+/// ```
+#[macro_export]
+macro_rules! measure_alloc {
+    ($level:expr, $msg:expr, $body:block) => {{
+        let before = $crate::AllocStats::current();
+        let result = $body;
+        let delta = $crate::AllocStats::current().since(&before);
+        $crate::with_current_logger(|logger| {
+            let bytes = delta.bytes.to_string();
+            let count = delta.count.to_string();
+            logger.log(
+                $level,
+                $msg,
+                &[("alloc_bytes", &bytes), ("alloc_count", &count)],
+            );
+        });
+        result
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator::system();
+
+    #[test]
+    /// Verifies that allocations in the measured block are counted.
+    fn test_counts_allocations() {
+        let before = AllocStats::current();
+        let buffer: Vec<u8> = Vec::with_capacity(1000);
+        let delta = AllocStats::current().since(&before);
+        drop(buffer);
+
+        assert_eq!(delta.count, 1);
+        assert!(delta.bytes >= 1000);
+    }
+}
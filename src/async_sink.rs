@@ -0,0 +1,182 @@
+//! Writing entries from a background thread.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+
+use crate::{Level, LogEntry, Logger};
+
+/// What a logger with [`Logger::with_async_channel`] does when its queue is
+/// full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Wait until the background thread has made room. No entry is lost, but
+    /// logging can stall when the output can't keep up.
+    #[default]
+    Block,
+    /// Discard the oldest queued entry to make room. Logging never waits; the
+    /// number of discarded entries is reported in a warning.
+    DropOldest,
+}
+
+/// Entries waiting for the background thread, and its progress.
+#[derive(Debug, Default)]
+struct Queue {
+    /// Entries not yet picked up by the background thread.
+    entries: VecDeque<LogEntry>,
+    /// Whether the background thread is writing an entry right now.
+    busy: bool,
+    /// Entries discarded since the last overflow warning.
+    dropped: usize,
+    /// Set when the sink is shutting down.
+    closed: bool,
+}
+
+/// The queue and its wakeup signal, shared with the background thread.
+#[derive(Debug)]
+struct Shared {
+    /// The maximum number of queued entries.
+    capacity: usize,
+    /// What to do when the queue is full.
+    policy: OverflowPolicy,
+    /// Queued entries and progress.
+    queue: Mutex<Queue>,
+    /// Signalled whenever the queue or progress changes.
+    changed: Condvar,
+}
+
+impl Shared {
+    /// Locks the queue. The queue stays consistent even if a holder panicked.
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        self.queue.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Waits for the queue or progress to change.
+    fn wait<'a>(&self, queue: MutexGuard<'a, Queue>) -> MutexGuard<'a, Queue> {
+        self.changed
+            .wait(queue)
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A bounded queue of entries written by a background thread.
+///
+/// Shared between clones of a logger. When the last clone is dropped, the
+/// queue is drained and the thread stopped.
+pub(crate) struct AsyncSink {
+    /// State shared with the background thread.
+    shared: Arc<Shared>,
+    /// The background thread, taken when shutting down.
+    worker: Option<JoinHandle<()>>,
+}
+
+impl fmt::Debug for AsyncSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncSink")
+            .field("capacity", &self.shared.capacity)
+            .field("policy", &self.shared.policy)
+            .finish()
+    }
+}
+
+impl AsyncSink {
+    /// Starts a background thread that writes queued entries with `logger`.
+    pub(crate) fn spawn(logger: Logger, capacity: usize, policy: OverflowPolicy) -> Self {
+        let shared = Arc::new(Shared {
+            capacity: capacity.max(1),
+            policy,
+            queue: Mutex::new(Queue::default()),
+            changed: Condvar::new(),
+        });
+        let worker = {
+            let shared = shared.clone();
+            thread::Builder::new()
+                .name("ccb-async".to_string())
+                .spawn(move || run(&shared, &logger))
+                .expect("failed to spawn the async logging thread")
+        };
+        Self {
+            shared,
+            worker: Some(worker),
+        }
+    }
+
+    /// Returns the maximum number of queued entries.
+    pub(crate) fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+
+    /// Returns what happens when the queue is full.
+    pub(crate) fn policy(&self) -> OverflowPolicy {
+        self.shared.policy
+    }
+
+    /// Queues an entry, applying the overflow policy when the queue is full.
+    pub(crate) fn send(&self, entry: LogEntry) {
+        let mut queue = self.shared.lock();
+        while queue.entries.len() >= self.shared.capacity {
+            match self.shared.policy {
+                OverflowPolicy::Block => queue = self.shared.wait(queue),
+                OverflowPolicy::DropOldest => {
+                    queue.entries.pop_front();
+                    queue.dropped += 1;
+                }
+            }
+        }
+        queue.entries.push_back(entry);
+        self.shared.changed.notify_all();
+    }
+
+    /// Waits until every queued entry has been written.
+    pub(crate) fn drain(&self) {
+        let mut queue = self.shared.lock();
+        while !queue.entries.is_empty() || queue.busy {
+            queue = self.shared.wait(queue);
+        }
+    }
+}
+
+impl Drop for AsyncSink {
+    fn drop(&mut self) {
+        self.shared.lock().closed = true;
+        self.shared.changed.notify_all();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Writes queued entries until the sink is closed and the queue is empty.
+fn run(shared: &Shared, logger: &Logger) {
+    let mut queue = shared.lock();
+    loop {
+        let Some(entry) = queue.entries.pop_front() else {
+            if queue.closed {
+                return;
+            }
+            queue = shared.wait(queue);
+            continue;
+        };
+        let dropped = std::mem::take(&mut queue.dropped);
+        queue.busy = true;
+        drop(queue);
+        // Wake senders blocked on a full queue
+        shared.changed.notify_all();
+
+        if dropped > 0 {
+            let dropped = dropped.to_string();
+            let warning = logger.build_entry(
+                Level::Warn,
+                "Async log queue full, dropped entries",
+                &[("dropped", &dropped)],
+            );
+            logger.write_entry(&warning);
+        }
+        logger.write_entry(&entry);
+
+        queue = shared.lock();
+        queue.busy = false;
+        shared.changed.notify_all();
+    }
+}
@@ -0,0 +1,373 @@
+//! A validating builder for [`Logger`].
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::{
+    format, ColorSupport, Config, ConfigError, Format, Level, Logger, SharedWriter, StyleConfig,
+    Theme, TimestampMode, TimestampPrecision,
+};
+
+/// Builds a [`Logger`], validating the configuration before use.
+///
+/// The `Logger::new().with_*` chain never fails: invalid settings are ignored
+/// with a warning or silently have no effect. The builder offers the same
+/// setters but reports such problems from [`LoggerBuilder::build`] instead.
+/// Settings without a dedicated setter can be given as a whole [`Config`]
+/// with [`LoggerBuilder::with_config`].
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{Format, Level, Logger};
+///
+/// let logger = Logger::builder()
+///     .with_level(Level::Debug)
+///     .with_format(Format::Json)
+///     .with("service", "auth")
+///     .build()
+///     .expect("valid logger configuration");
+///
+/// let err = Logger::builder()
+///     .with_format(Format::Json)
+///     .with_colors(true)
+///     .build();
+/// assert!(err.is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LoggerBuilder {
+    /// The configuration being built.
+    config: Config,
+    /// Context fields, in the order they were added.
+    fields: Vec<(String, String)>,
+    /// Where entries are written; stderr when `None`.
+    writer: Option<SharedWriter>,
+    /// Colors chosen explicitly, overriding detection.
+    colors: Option<bool>,
+    /// The output buffer capacity; 0 disables buffering.
+    buffering: usize,
+}
+
+impl LoggerBuilder {
+    /// Creates a builder with the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the whole configuration, keeping fields, the writer, and
+    /// buffering.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration to start from
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the minimum log level. See [`Logger::with_level`].
+    pub fn with_level(mut self, level: Level) -> Self {
+        self.config.level = level;
+        self
+    }
+
+    /// Sets the minimum level for a module. See [`Logger::with_module_level`].
+    pub fn with_module_level<M: Into<String>>(mut self, module: M, level: Level) -> Self {
+        let module = module.into();
+        self.config.module_levels.retain(|(m, _)| *m != module);
+        self.config.module_levels.push((module, level));
+        self
+    }
+
+    /// Enables or disables colors, overriding detection. See
+    /// [`Logger::with_colors`].
+    ///
+    /// Enabling colors for a format that never renders them is an error.
+    pub fn with_colors(mut self, use_colors: bool) -> Self {
+        self.colors = Some(use_colors);
+        self
+    }
+
+    /// Overrides the detected color capability. See
+    /// [`Logger::with_color_support`].
+    pub fn with_color_support(mut self, support: ColorSupport) -> Self {
+        self.config.color_support = support;
+        self
+    }
+
+    /// Sets the level label colors. See [`Logger::with_theme`].
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.config.theme = theme;
+        self
+    }
+
+    /// Sets bold labels and key and message colors. See
+    /// [`Logger::with_style`].
+    pub fn with_style(mut self, style: StyleConfig) -> Self {
+        self.config.style = style;
+        self
+    }
+
+    /// Shows or hides timestamps. See [`Logger::with_timestamp`].
+    pub fn with_timestamp(mut self, show_timestamp: bool) -> Self {
+        self.config.show_timestamp = show_timestamp;
+        self.config.level_timestamps.clear();
+        self
+    }
+
+    /// Sets the timestamp format. See [`Logger::with_timestamp_format`].
+    ///
+    /// An invalid format is an error.
+    pub fn with_timestamp_format(mut self, format: &str) -> Self {
+        self.config.timestamp_format = format.to_string();
+        self
+    }
+
+    /// Sets the fractional-second digits of timestamps. See
+    /// [`Logger::with_timestamp_precision`].
+    pub fn with_timestamp_precision(mut self, precision: TimestampPrecision) -> Self {
+        self.config.timestamp_precision = precision;
+        self
+    }
+
+    /// Shortens timestamps repeated within a second. See
+    /// [`Logger::with_timestamp_mode`].
+    pub fn with_timestamp_mode(mut self, mode: TimestampMode) -> Self {
+        self.config.timestamp_mode = mode;
+        self
+    }
+
+    /// Displays timestamps in UTC. See [`Logger::with_utc`].
+    pub fn with_utc(mut self, utc: bool) -> Self {
+        self.config.utc = utc;
+        #[cfg(feature = "chrono-tz")]
+        if utc {
+            self.config.timezone = None;
+        }
+        self
+    }
+
+    /// Sends entries below `Warn` to stdout. See
+    /// [`Logger::with_split_streams`].
+    pub fn with_split_streams(mut self, enabled: bool) -> Self {
+        self.config.split_streams = enabled;
+        self
+    }
+
+    /// Sets the output format. See [`Logger::with_format`].
+    pub fn with_format(mut self, format: Format) -> Self {
+        self.config.format = format;
+        self
+    }
+
+    /// Nests dotted field keys in JSON output. See
+    /// [`Logger::with_nested_json`].
+    pub fn with_nested_json(mut self, enabled: bool) -> Self {
+        self.config.nested_json = enabled;
+        self
+    }
+
+    /// Sets the string written after each entry. See
+    /// [`Logger::with_record_delimiter`].
+    ///
+    /// An empty delimiter is an error.
+    pub fn with_record_delimiter(mut self, delimiter: impl Into<String>) -> Self {
+        self.config.record_delimiter = delimiter.into();
+        self
+    }
+
+    /// Sets the line terminator written after each entry. See
+    /// [`Logger::with_line_terminator`].
+    ///
+    /// An empty terminator is an error.
+    pub fn with_line_terminator(self, terminator: &str) -> Self {
+        self.with_record_delimiter(terminator)
+    }
+
+    /// Writes a schema descriptor before the first JSON record. See
+    /// [`Logger::with_json_schema_header`].
+    pub fn with_json_schema_header(mut self, enabled: bool) -> Self {
+        self.config.json_schema_header = enabled;
+        self
+    }
+
+    /// Limits the distinct field keys written as top-level JSON keys. See
+    /// [`Logger::with_json_max_keys`].
+    pub fn with_json_max_keys(mut self, max: usize) -> Self {
+        self.config.json_max_keys = Some(max);
+        self
+    }
+
+    /// Writes buffered output at least every `interval`. See
+    /// [`Logger::with_flush_interval`].
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.config.flush_interval = Some(interval);
+        self
+    }
+
+    /// Writes entries to `writer` instead of stderr. See
+    /// [`Logger::with_writer`].
+    pub fn with_writer(mut self, writer: Arc<Mutex<dyn Write + Send>>) -> Self {
+        self.writer = Some(SharedWriter(writer));
+        self
+    }
+
+    /// Batches output. See [`Logger::with_buffering`].
+    pub fn with_buffering(mut self, capacity: usize) -> Self {
+        self.buffering = capacity;
+        self
+    }
+
+    /// Adds a context field. See [`Logger::with`].
+    pub fn with<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.fields.push((key.into(), value.into()));
+        self
+    }
+
+    /// Adds several context fields. See [`Logger::with_fields`].
+    pub fn with_fields(mut self, fields: &[(&str, &str)]) -> Self {
+        self.fields
+            .extend(fields.iter().map(|(k, v)| (k.to_string(), v.to_string())));
+        self
+    }
+
+    /// Validates the configuration and builds the logger.
+    ///
+    /// # Returns
+    ///
+    /// The logger, or the first problem found with the configuration.
+    pub fn build(self) -> Result<Logger, ConfigError> {
+        self.validate()?;
+
+        let mut logger = Logger::with_config(self.config);
+        if let Some(SharedWriter(writer)) = self.writer {
+            logger = logger.with_writer(writer);
+        }
+        if let Some(use_colors) = self.colors {
+            logger = logger.with_colors(use_colors);
+        }
+        logger = logger.with_buffering(self.buffering);
+        for (key, value) in self.fields {
+            logger = logger.with(key, value);
+        }
+        Ok(logger)
+    }
+
+    /// Checks the configuration for invalid or contradictory settings.
+    fn validate(&self) -> Result<(), ConfigError> {
+        if !format::is_valid_timestamp_format(&self.config.timestamp_format) {
+            return Err(ConfigError::InvalidTimestampFormat(
+                self.config.timestamp_format.clone(),
+            ));
+        }
+        if self.colors == Some(true) && !self.config.format.supports_colors() {
+            return Err(ConfigError::ColorsUnsupported(
+                self.config.format.describe(),
+            ));
+        }
+        if let (Some(from), Some(until)) = (self.config.active_from, self.config.active_until) {
+            if until <= from {
+                return Err(ConfigError::EmptyActiveWindow);
+            }
+        }
+        if self.config.record_delimiter.is_empty() {
+            return Err(ConfigError::EmptyRecordDelimiter);
+        }
+        Ok(())
+    }
+}
+
+impl Logger {
+    /// Creates a [`LoggerBuilder`] for building a validated logger.
+    pub fn builder() -> LoggerBuilder {
+        LoggerBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CompositeFormat;
+    use chrono::{Duration, Local};
+
+    #[test]
+    /// Verifies that invalid and contradictory settings are rejected.
+    fn test_build_validation() {
+        assert_eq!(
+            Logger::builder()
+                .with_timestamp_format("")
+                .build()
+                .unwrap_err(),
+            ConfigError::InvalidTimestampFormat(String::new())
+        );
+        assert_eq!(
+            Logger::builder()
+                .with_colors(true)
+                .with_format(Format::Logfmt)
+                .build()
+                .unwrap_err(),
+            ConfigError::ColorsUnsupported("logfmt".to_string())
+        );
+        let now = Local::now();
+        let window = Config {
+            active_from: Some(now),
+            active_until: Some(now - Duration::hours(1)),
+            ..Config::default()
+        };
+        assert_eq!(
+            Logger::builder().with_config(window).build().unwrap_err(),
+            ConfigError::EmptyActiveWindow
+        );
+        assert_eq!(
+            Logger::builder()
+                .with_record_delimiter("")
+                .build()
+                .unwrap_err(),
+            ConfigError::EmptyRecordDelimiter
+        );
+        assert_eq!(
+            Logger::builder()
+                .with_line_terminator("")
+                .build()
+                .unwrap_err(),
+            ConfigError::EmptyRecordDelimiter
+        );
+        assert!(Logger::builder()
+            .with_line_terminator("\r\n")
+            .build()
+            .is_ok());
+
+        let composite = Format::Composite(CompositeFormat::new(vec![Format::Pretty, Format::Json]));
+        assert!(Logger::builder()
+            .with_colors(true)
+            .with_format(composite)
+            .build()
+            .is_ok());
+        assert!(Logger::builder()
+            .with_colors(false)
+            .with_format(Format::Json)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    /// Verifies that the built logger has the configured settings and fields.
+    fn test_build() {
+        let output: Arc<Mutex<Vec<u8>>> = Arc::new(Mutex::new(Vec::new()));
+        let logger = Logger::builder()
+            .with_level(Level::Debug)
+            .with_timestamp(false)
+            .with_fields(&[("service", "auth")])
+            .with("region", "eu")
+            .with_writer(output.clone())
+            .build()
+            .unwrap();
+        logger.debug("Ready", &[]);
+
+        let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        assert!(output.starts_with("DEBG Ready "));
+        assert!(output.contains("service=auth") && output.contains("region=eu"));
+        assert!(!logger.config.use_colors);
+    }
+}
@@ -0,0 +1,140 @@
+//! In-memory capture of a logger's output for tests.
+
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::Logger;
+
+/// Gives access to the output of a logger created with [`Logger::capturing`].
+///
+/// Clones share the same captured output.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::Logger;
+///
+/// let (logger, capture) = Logger::capturing();
+/// let logger = logger.with_timestamp(false);
+/// logger.info("Started", &[("port", "8080")]);
+/// logger.debug("Hidden", &[]);
+///
+/// assert_eq!(capture.lines(), ["INFO Started port=8080"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CaptureHandle {
+    /// The bytes written by the capturing logger.
+    output: Arc<Mutex<Vec<u8>>>,
+}
+
+impl CaptureHandle {
+    /// Returns the captured output as lines, with terminal escape sequences
+    /// such as colors and hyperlinks stripped.
+    pub fn lines(&self) -> Vec<String> {
+        let output = self.output.lock().unwrap_or_else(PoisonError::into_inner);
+        strip_escapes(&String::from_utf8_lossy(&output))
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    /// Discards everything captured so far.
+    pub fn clear(&self) {
+        self.output
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+impl Logger {
+    /// Creates a logger that writes into memory, for asserting on log output in
+    /// tests.
+    ///
+    /// The logger starts with the default configuration and colors disabled;
+    /// further builder methods can be chained onto it as usual.
+    ///
+    /// # Returns
+    ///
+    /// The logger and a [`CaptureHandle`] for reading what it wrote.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Level, Logger};
+    ///
+    /// let (logger, capture) = Logger::capturing();
+    /// let logger = logger.with_timestamp(false).with_level(Level::Warn);
+    /// logger.info("Ignored", &[]);
+    /// logger.warn("Disk almost full", &[]);
+    ///
+    /// assert_eq!(capture.lines(), ["WARN Disk almost full"]);
+    /// ```
+    pub fn capturing() -> (Logger, CaptureHandle) {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let logger = Logger::new().with_writer(output.clone());
+        (logger, CaptureHandle { output })
+    }
+}
+
+/// Removes ANSI CSI sequences, such as colors, and OSC sequences, such as
+/// hyperlinks, from `text`.
+fn strip_escapes(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            stripped.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters up to a final byte in '@'..='~'
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: terminated by BEL or ESC '\'
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ColorSupport;
+
+    #[test]
+    /// Verifies that colors and hyperlinks are stripped from captured lines.
+    fn test_capture_strips_escapes() {
+        let (logger, capture) = Logger::capturing();
+        let logger = logger
+            .with_timestamp(false)
+            .with_colors(true)
+            .with_color_support(ColorSupport::Basic)
+            .with_hyperlink_keys(["url"]);
+        logger.warn("Slow", &[("url", "https://example.com")]);
+        logger.error("Failed", &[]);
+
+        assert_eq!(
+            capture.lines(),
+            ["WARN Slow url=https://example.com", "ERRO Failed"]
+        );
+        capture.clear();
+        assert!(capture.lines().is_empty());
+    }
+}
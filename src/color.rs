@@ -0,0 +1,324 @@
+//! Terminal color capability detection and color downgrading.
+
+use termcolor::{Color, ColorSpec};
+
+/// The range of colors a terminal can display.
+///
+/// Colors are downgraded to the closest color the terminal supports before
+/// they are written, so an `Rgb` level color still renders sensibly on a
+/// 16-color terminal. Variants are ordered from least to most capable.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{ColorSupport, Logger};
+///
+/// let detected = ColorSupport::detect();
+/// let logger = Logger::new().with_color_support(ColorSupport::Ansi256);
+/// assert_eq!(logger.color_support(), ColorSupport::Ansi256);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum ColorSupport {
+    /// No colors; only text attributes such as bold are used.
+    None,
+    /// The 16 standard ANSI colors.
+    Basic,
+    /// The 256-color xterm palette.
+    Ansi256,
+    /// 24-bit RGB colors.
+    TrueColor,
+}
+
+/// Decides whether colors are used by default.
+///
+/// Follows the <https://no-color.org> and `CLICOLOR_FORCE` conventions:
+/// `$NO_COLOR` set to any value turns colors off, and otherwise
+/// `$CLICOLOR_FORCE` set to anything but `0` turns them on even when stderr
+/// isn't a terminal. Without either, colors are used when stderr is a
+/// terminal.
+pub(crate) fn default_use_colors() -> bool {
+    stream_use_colors(atty::Stream::Stderr)
+}
+
+/// Decides whether colors are used by default on `stream`, following the
+/// same conventions as [`default_use_colors`].
+pub(crate) fn stream_use_colors(stream: atty::Stream) -> bool {
+    use_colors_from_env(
+        std::env::var_os("NO_COLOR").is_some(),
+        std::env::var("CLICOLOR_FORCE").ok().as_deref(),
+        atty::is(stream),
+    )
+}
+
+/// Decides whether a file sink, such as a tee, is colored: only when the file
+/// is itself a terminal, like `/dev/tty`, and `$NO_COLOR` isn't set.
+/// `$CLICOLOR_FORCE` is ignored so that log files stay plain.
+pub(crate) fn file_use_colors(file: &std::fs::File) -> bool {
+    use std::io::IsTerminal;
+    use_colors_from_env(
+        std::env::var_os("NO_COLOR").is_some(),
+        None,
+        file.is_terminal(),
+    )
+}
+
+/// Decides whether colors are used from the environment and terminal state.
+fn use_colors_from_env(no_color: bool, clicolor_force: Option<&str>, is_tty: bool) -> bool {
+    if no_color {
+        return false;
+    }
+    matches!(clicolor_force, Some(value) if value != "0") || is_tty
+}
+
+/// RGB values of the 16 standard colors, as rendered by xterm. The first
+/// eight are the normal variants and the last eight the intense ones.
+const BASIC_PALETTE: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::White, (229, 229, 229)),
+    (Color::Black, (127, 127, 127)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (92, 92, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Channel values of the 6x6x6 color cube in the 256-color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorSupport {
+    /// Detects the color capability of the current terminal.
+    ///
+    /// `$COLORTERM` set to `truecolor` or `24bit` selects `TrueColor`. Otherwise
+    /// `$TERM` decides: `dumb` means `None`, a name containing `256color` means
+    /// `Ansi256`, and any other value means `Basic`. Without `$TERM`, Windows
+    /// consoles are assumed to support `Basic` colors and other platforms none.
+    ///
+    /// This only inspects the environment; whether colors are used at all is
+    /// still controlled by [`Logger::with_colors`](crate::Logger::with_colors).
+    pub fn detect() -> Self {
+        Self::from_env(
+            std::env::var("COLORTERM").ok().as_deref(),
+            std::env::var("TERM").ok().as_deref(),
+        )
+    }
+
+    /// Determines the capability from `$COLORTERM` and `$TERM` values.
+    fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            return ColorSupport::TrueColor;
+        }
+
+        match term {
+            Some("dumb") => ColorSupport::None,
+            Some(term) if term.contains("256color") => ColorSupport::Ansi256,
+            Some(term) if !term.is_empty() => ColorSupport::Basic,
+            _ if cfg!(windows) => ColorSupport::Basic,
+            _ => ColorSupport::None,
+        }
+    }
+
+    /// Returns a spec with `color` as the foreground, downgraded to fit.
+    ///
+    /// Colors reduced to the 16-color palette may set the intense attribute to
+    /// select a bright variant. With `None` no foreground color is set.
+    pub(crate) fn foreground(self, color: Color) -> ColorSpec {
+        let mut spec = ColorSpec::new();
+        match (self, color) {
+            (ColorSupport::None, _) => {}
+            (ColorSupport::TrueColor, color) => {
+                spec.set_fg(Some(color));
+            }
+            (ColorSupport::Ansi256, Color::Rgb(r, g, b)) => {
+                spec.set_fg(Some(Color::Ansi256(nearest_ansi256((r, g, b)))));
+            }
+            (ColorSupport::Basic, Color::Rgb(r, g, b)) => {
+                set_basic(&mut spec, nearest_basic((r, g, b)));
+            }
+            (ColorSupport::Basic, Color::Ansi256(index)) if index < 16 => {
+                set_basic(&mut spec, index as usize);
+            }
+            (ColorSupport::Basic, Color::Ansi256(index)) => {
+                set_basic(&mut spec, nearest_basic(ansi256_to_rgb(index)));
+            }
+            (_, color) => {
+                spec.set_fg(Some(color));
+            }
+        }
+        spec
+    }
+}
+
+/// Sets the foreground to the basic palette entry at `index`.
+fn set_basic(spec: &mut ColorSpec, index: usize) {
+    spec.set_fg(Some(BASIC_PALETTE[index].0))
+        .set_intense(index >= 8);
+}
+
+/// Returns the squared distance between two RGB colors.
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (i32::from(x) - i32::from(y)).unsigned_abs().pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// Returns the index of the basic palette color closest to `rgb`.
+fn nearest_basic(rgb: (u8, u8, u8)) -> usize {
+    (0..BASIC_PALETTE.len())
+        .min_by_key(|&index| distance(BASIC_PALETTE[index].1, rgb))
+        .unwrap_or(0)
+}
+
+/// Returns the 256-color palette index closest to `rgb`, picking between the
+/// color cube and the grayscale ramp.
+fn nearest_ansi256((r, g, b): (u8, u8, u8)) -> u8 {
+    let cube_index = |v: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| CUBE_LEVELS[i].abs_diff(v))
+            .unwrap_or(0) as u8
+    };
+    let (ri, gi, bi) = (cube_index(r), cube_index(g), cube_index(b));
+    let cube = 16 + 36 * ri + 6 * gi + bi;
+
+    let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+    if distance(ansi256_to_rgb(gray), (r, g, b)) < distance(ansi256_to_rgb(cube), (r, g, b)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// Returns the RGB value of a 256-color palette index.
+fn ansi256_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => BASIC_PALETTE[index as usize].1,
+        16..=231 => {
+            let index = index - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Verifies capability detection from `$COLORTERM` and `$TERM`.
+    fn test_detect_from_env() {
+        let detect = ColorSupport::from_env;
+        assert_eq!(
+            detect(Some("truecolor"), Some("xterm")),
+            ColorSupport::TrueColor
+        );
+        assert_eq!(detect(None, Some("xterm-256color")), ColorSupport::Ansi256);
+        assert_eq!(detect(None, Some("xterm")), ColorSupport::Basic);
+        assert_eq!(detect(Some("yes"), Some("dumb")), ColorSupport::None);
+    }
+
+    #[test]
+    /// Verifies that `NO_COLOR` wins over `CLICOLOR_FORCE`, which wins over
+    /// terminal detection.
+    fn test_use_colors_from_env() {
+        assert!(use_colors_from_env(false, None, true));
+        assert!(!use_colors_from_env(false, None, false));
+        assert!(!use_colors_from_env(true, None, true));
+        assert!(!use_colors_from_env(true, Some("1"), true));
+        assert!(use_colors_from_env(false, Some("1"), false));
+        assert!(!use_colors_from_env(false, Some("0"), false));
+    }
+
+    #[test]
+    /// Verifies that a regular file is never colored.
+    fn test_file_use_colors() {
+        let path = std::env::temp_dir().join(format!("ccb-color-{}", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        assert!(!file_use_colors(&file));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    /// Verifies that colors are downgraded to the closest supported color.
+    fn test_downgrade_colors() {
+        let gray = Color::Rgb(128, 128, 128);
+
+        assert_eq!(ColorSupport::TrueColor.foreground(gray).fg(), Some(&gray));
+        assert_eq!(
+            ColorSupport::Ansi256.foreground(gray).fg(),
+            Some(&Color::Ansi256(244))
+        );
+        assert_eq!(
+            ColorSupport::Ansi256
+                .foreground(Color::Rgb(255, 135, 0))
+                .fg(),
+            Some(&Color::Ansi256(208))
+        );
+
+        let basic = ColorSupport::Basic.foreground(gray);
+        assert_eq!(basic.fg(), Some(&Color::Black));
+        assert!(basic.intense());
+        let basic = ColorSupport::Basic.foreground(Color::Ansi256(160));
+        assert_eq!(basic.fg(), Some(&Color::Red));
+        assert!(!basic.intense());
+
+        assert_eq!(ColorSupport::None.foreground(Color::Red).fg(), None);
+    }
+}
+
+/// Serializes optional colors as the strings `termcolor` parses: a name such
+/// as `"red"`, an ANSI 256 index such as `"208"`, or `"r,g,b"`.
+#[cfg(feature = "serde")]
+pub(crate) mod serde_option_color {
+    use serde::{de, ser, Deserialize, Deserializer, Serializer};
+    use termcolor::Color;
+
+    pub(crate) fn serialize<S: Serializer>(
+        color: &Option<Color>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let Some(color) = color else {
+            return serializer.serialize_none();
+        };
+        let name = match color {
+            Color::Black => "black".to_string(),
+            Color::Blue => "blue".to_string(),
+            Color::Green => "green".to_string(),
+            Color::Red => "red".to_string(),
+            Color::Cyan => "cyan".to_string(),
+            Color::Magenta => "magenta".to_string(),
+            Color::Yellow => "yellow".to_string(),
+            Color::White => "white".to_string(),
+            Color::Ansi256(index) => index.to_string(),
+            Color::Rgb(r, g, b) => format!("{},{},{}", r, g, b),
+            _ => return Err(ser::Error::custom("unsupported color")),
+        };
+        serializer.serialize_some(&name)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Color>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|name| name.parse().map_err(de::Error::custom))
+            .transpose()
+    }
+}
@@ -0,0 +1,269 @@
+//! A one-line `key=value` form of [`Config`], for environment variables and
+//! command-line flags.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Config, Format, ParseConfigError, TimestampMode, TimestampPrecision};
+
+/// The settings the one-line form covers, in the order they are written.
+const KEYS: [&str; 10] = [
+    "level",
+    "format",
+    "colors",
+    "timestamp",
+    "timestamp_precision",
+    "timestamp_mode",
+    "location",
+    "utc",
+    "sort_fields",
+    "nested_json",
+];
+
+/// Writes a switch as `on` or `off`.
+fn on_off(enabled: bool) -> &'static str {
+    if enabled {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// Parses `on`/`off`, also accepting `true`/`false` and `1`/`0`.
+fn parse_switch(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "on" | "true" | "1" => Some(true),
+        "off" | "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Returns the one-line name of a format, if it has one.
+fn format_name(format: &Format) -> Option<&'static str> {
+    match format {
+        Format::Pretty => Some("pretty"),
+        Format::Json => Some("json"),
+        Format::Logfmt => Some("logfmt"),
+        #[cfg(feature = "msgpack")]
+        Format::MessagePack => Some("messagepack"),
+        Format::Csv(_) | Format::Composite(_) => None,
+    }
+}
+
+/// Parses a format name written by [`format_name`].
+fn parse_format(value: &str) -> Option<Format> {
+    match value.to_ascii_lowercase().as_str() {
+        "pretty" => Some(Format::Pretty),
+        "json" => Some(Format::Json),
+        "logfmt" => Some(Format::Logfmt),
+        #[cfg(feature = "msgpack")]
+        "messagepack" => Some(Format::MessagePack),
+        _ => None,
+    }
+}
+
+/// Returns the one-line name of a timestamp precision.
+fn precision_name(precision: TimestampPrecision) -> &'static str {
+    match precision {
+        TimestampPrecision::Seconds => "seconds",
+        TimestampPrecision::Millis => "millis",
+        TimestampPrecision::Micros => "micros",
+        TimestampPrecision::Nanos => "nanos",
+    }
+}
+
+/// Parses a timestamp precision name written by [`precision_name`].
+fn parse_precision(value: &str) -> Option<TimestampPrecision> {
+    match value.to_ascii_lowercase().as_str() {
+        "seconds" => Some(TimestampPrecision::Seconds),
+        "millis" => Some(TimestampPrecision::Millis),
+        "micros" => Some(TimestampPrecision::Micros),
+        "nanos" => Some(TimestampPrecision::Nanos),
+        _ => None,
+    }
+}
+
+/// Parses a timestamp mode name.
+fn parse_mode(value: &str) -> Option<TimestampMode> {
+    match value.to_ascii_lowercase().as_str() {
+        "full" => Some(TimestampMode::Full),
+        "compact" => Some(TimestampMode::Compact),
+        _ => None,
+    }
+}
+
+impl Config {
+    /// Applies one `key=value` setting.
+    fn apply_setting(&mut self, key: &str, value: &str) -> Result<(), ParseConfigError> {
+        let invalid = || ParseConfigError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        let switch = || parse_switch(value).ok_or_else(invalid);
+        match key {
+            "level" => self.level = value.parse().map_err(|_| invalid())?,
+            "format" => self.format = parse_format(value).ok_or_else(invalid)?,
+            "colors" => self.use_colors = switch()?,
+            "timestamp" => {
+                self.show_timestamp = switch()?;
+                self.level_timestamps.clear();
+            }
+            "timestamp_precision" => {
+                self.timestamp_precision = parse_precision(value).ok_or_else(invalid)?
+            }
+            "timestamp_mode" => self.timestamp_mode = parse_mode(value).ok_or_else(invalid)?,
+            "location" => self.show_location = switch()?,
+            "utc" => self.utc = switch()?,
+            "sort_fields" => self.sort_fields = switch()?,
+            "nested_json" => self.nested_json = switch()?,
+            _ => return Err(ParseConfigError::UnknownKey(key.to_string())),
+        }
+        Ok(())
+    }
+}
+
+/// Parses a configuration from comma-separated `key=value` settings, such as
+/// `level=debug,colors=off,timestamp=on`.
+///
+/// Settings that are left out keep their [`Config::default`] values, and a
+/// later setting overrides an earlier one with the same key. Keys and values
+/// ignore surrounding whitespace and the case of values.
+///
+/// | Key | Values |
+/// |-----|--------|
+/// | `level` | `trace`, `debug`, `info`, `warn`, `error`, `off` |
+/// | `format` | `pretty`, `json`, `logfmt` (`messagepack` with the `msgpack` feature) |
+/// | `colors`, `timestamp`, `location`, `utc`, `sort_fields`, `nested_json` | `on`, `off` (or `true`, `false`, `1`, `0`) |
+/// | `timestamp_precision` | `seconds`, `millis`, `micros`, `nanos` |
+/// | `timestamp_mode` | `full`, `compact` |
+///
+/// # Errors
+///
+/// Returns a [`ParseConfigError`] naming the first unknown key, invalid
+/// value, or setting without `=`.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{Config, Level, Logger};
+///
+/// let config: Config = "level=debug, colors=off, timestamp=on".parse().unwrap();
+/// assert_eq!(config.level, Level::Debug);
+/// assert!(!config.use_colors);
+///
+/// assert!("levle=debug".parse::<Config>().is_err());
+///
+/// // Driven by a single environment variable
+/// let config = std::env::var("CCB_CONFIG")
+///     .ok()
+///     .and_then(|line| line.parse::<Config>().ok())
+///     .unwrap_or_default();
+/// let logger = Logger::with_config(config);
+/// ```
+impl FromStr for Config {
+    type Err = ParseConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = Config::default();
+        for setting in s.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (key, value) = setting
+                .split_once('=')
+                .ok_or_else(|| ParseConfigError::MissingValue(setting.to_string()))?;
+            config.apply_setting(key.trim(), value.trim())?;
+        }
+        Ok(config)
+    }
+}
+
+/// Writes the settings covered by [`Config::from_str`] in the same
+/// `key=value` form, so that parsing the result gives them back.
+///
+/// Other settings are left out, as is a format that can't be named in this
+/// form, such as CSV.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::Config;
+///
+/// let config: Config = "level=warn,colors=off".parse().unwrap();
+/// let line = config.to_string();
+/// assert!(line.starts_with("level=warn,format=pretty,colors=off,"));
+/// assert_eq!(line.parse::<Config>().unwrap().to_string(), line);
+/// ```
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut settings = Vec::with_capacity(KEYS.len());
+        settings.push(format!("level={}", self.level.name()));
+        if let Some(format) = format_name(&self.format) {
+            settings.push(format!("format={}", format));
+        }
+        settings.push(format!("colors={}", on_off(self.use_colors)));
+        settings.push(format!("timestamp={}", on_off(self.show_timestamp)));
+        settings.push(format!(
+            "timestamp_precision={}",
+            precision_name(self.timestamp_precision)
+        ));
+        settings.push(format!(
+            "timestamp_mode={}",
+            match self.timestamp_mode {
+                TimestampMode::Full => "full",
+                TimestampMode::Compact => "compact",
+            }
+        ));
+        settings.push(format!("location={}", on_off(self.show_location)));
+        settings.push(format!("utc={}", on_off(self.utc)));
+        settings.push(format!("sort_fields={}", on_off(self.sort_fields)));
+        settings.push(format!("nested_json={}", on_off(self.nested_json)));
+        write!(f, "{}", settings.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Level;
+
+    #[test]
+    /// Verifies that every key round-trips through `Display` and `FromStr`.
+    fn test_round_trip() {
+        let line = "level=trace,format=logfmt,colors=on,timestamp=off,\
+                    timestamp_precision=micros,timestamp_mode=compact,location=on,\
+                    utc=on,sort_fields=on,nested_json=on";
+        let config: Config = line.parse().unwrap();
+        assert_eq!(config.level, Level::Trace);
+        assert!(matches!(config.format, Format::Logfmt));
+        assert_eq!(config.timestamp_precision, TimestampPrecision::Micros);
+        assert_eq!(config.to_string(), line);
+
+        let keys: Vec<_> = line
+            .split(',')
+            .map(|setting| setting.split_once('=').unwrap().0)
+            .collect();
+        assert_eq!(keys, KEYS);
+    }
+
+    #[test]
+    /// Verifies the errors for unknown keys, invalid values, and missing `=`.
+    fn test_parse_errors() {
+        assert_eq!(
+            "level=debug,verbose=on".parse::<Config>().unwrap_err(),
+            ParseConfigError::UnknownKey("verbose".to_string())
+        );
+        assert_eq!(
+            "colors=maybe".parse::<Config>().unwrap_err(),
+            ParseConfigError::InvalidValue {
+                key: "colors".to_string(),
+                value: "maybe".to_string(),
+            }
+        );
+        assert_eq!(
+            "level".parse::<Config>().unwrap_err(),
+            ParseConfigError::MissingValue("level".to_string())
+        );
+
+        let config: Config = " , level = WARN ,utc=1,".parse().unwrap();
+        assert_eq!(config.level, Level::Warn);
+        assert!(config.utc);
+    }
+}
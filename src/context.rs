@@ -0,0 +1,253 @@
+//! Scoped context fields and carrying a thread's logging context across
+//! thread spawns.
+
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::thread::{self, JoinHandle};
+
+use crate::trace::{current_trace_context, set_current_trace_context};
+use crate::{clear_thread_logger, set_thread_logger, Logger, TraceContext, THREAD_LOGGER};
+
+thread_local! {
+    /// Fields pushed with [`push_context`], outermost scope first.
+    static SCOPED_FIELDS: RefCell<Vec<(String, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Adds fields to every entry logged on the current thread until the returned
+/// guard is dropped.
+///
+/// The fields apply to all loggers, so a `request_id` can be attached for the
+/// duration of a request handler without passing a [`Logger`] around. Scopes
+/// nest: an inner scope's fields override outer ones with the same key, and
+/// fields passed to the logging call override both. A logger's own
+/// [`with`](Logger::with) context has the lowest precedence.
+///
+/// # Arguments
+///
+/// * `fields` - The key-value pairs to attach
+///
+/// # Returns
+///
+/// A guard that removes the fields when dropped. Dropping an outer guard also
+/// removes the fields of any scopes nested inside it.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{info, push_context};
+///
+/// fn handle_request() {
+///     let _scope = push_context(&[("request_id", "req-42")]);
+///     info!("Handling request"); // includes request_id=req-42
+/// }
+///
+/// handle_request();
+/// info!("Idle"); // no request_id
+/// ```
+#[must_use = "the fields are removed as soon as the guard is dropped"]
+pub fn push_context(fields: &[(&str, &str)]) -> ContextGuard {
+    let depth = SCOPED_FIELDS.with(|scoped| {
+        let mut scoped = scoped.borrow_mut();
+        let depth = scoped.len();
+        scoped.extend(fields.iter().map(|(k, v)| (k.to_string(), v.to_string())));
+        depth
+    });
+    ContextGuard {
+        depth,
+        _not_send: PhantomData,
+    }
+}
+
+/// Removes the fields of a [`push_context`] scope when dropped.
+///
+/// The guard belongs to the thread that created it and can't be sent to
+/// another thread.
+#[derive(Debug)]
+pub struct ContextGuard {
+    /// Length of the scoped field stack before this scope was pushed.
+    depth: usize,
+    /// Keeps the guard on the thread whose stack it truncates.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        let _ = SCOPED_FIELDS.try_with(|scoped| scoped.borrow_mut().truncate(self.depth));
+    }
+}
+
+/// Calls `f` with the current thread's scoped fields, outermost first.
+pub(crate) fn with_scoped_fields<R>(f: impl FnOnce(&[(String, String)]) -> R) -> R {
+    SCOPED_FIELDS.with(|scoped| f(&scoped.borrow()))
+}
+
+/// A snapshot of a thread's logging context, taken with [`capture_context`].
+///
+/// The snapshot holds the thread logger installed with
+/// [`set_thread_logger`](crate::set_thread_logger), along with its context
+/// fields such as a `trace_id`, the fields of active [`push_context`]
+/// scopes, and the trace context entered with
+/// [`enter_trace_context`](crate::enter_trace_context). Restoring it on
+/// another thread makes that thread's macro calls produce correlated entries.
+/// A snapshot of a thread without a thread logger restores the fallback to
+/// the global logger.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{capture_context, info, set_thread_logger, Logger};
+///
+/// set_thread_logger(Logger::new().with("trace_id", "abc123"));
+/// let snapshot = capture_context();
+///
+/// std::thread::spawn(move || {
+///     snapshot.restore();
+///     info!("Working"); // includes trace_id=abc123
+/// })
+/// .join()
+/// .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ContextSnapshot {
+    /// The captured thread logger, if one was installed.
+    logger: Option<Logger>,
+    /// The captured scoped fields, outermost first.
+    scoped: Vec<(String, String)>,
+    /// The captured thread trace context, if one was entered.
+    trace: Option<TraceContext>,
+}
+
+impl ContextSnapshot {
+    /// Installs the captured context as the current thread's context.
+    ///
+    /// Any scoped fields and trace context of the current thread are replaced
+    /// by the captured ones.
+    pub fn restore(&self) {
+        match &self.logger {
+            Some(logger) => set_thread_logger(logger.clone()),
+            None => clear_thread_logger(),
+        }
+        SCOPED_FIELDS.with(|scoped| scoped.borrow_mut().clone_from(&self.scoped));
+        set_current_trace_context(self.trace.clone());
+    }
+
+    /// Returns the captured thread logger, if one was installed.
+    pub fn logger(&self) -> Option<&Logger> {
+        self.logger.as_ref()
+    }
+
+    /// Returns the captured thread trace context, if one was entered.
+    pub fn trace(&self) -> Option<&TraceContext> {
+        self.trace.as_ref()
+    }
+}
+
+/// Captures the current thread's logging context.
+///
+/// # Returns
+///
+/// A snapshot that can be moved to another thread and restored there with
+/// [`ContextSnapshot::restore`] or [`spawn_with_context`].
+pub fn capture_context() -> ContextSnapshot {
+    ContextSnapshot {
+        logger: THREAD_LOGGER.with(|slot| slot.borrow().clone()),
+        scoped: with_scoped_fields(<[_]>::to_vec),
+        trace: current_trace_context(),
+    }
+}
+
+/// Spawns a thread that runs with a previously captured logging context.
+///
+/// This is [`std::thread::spawn`] with the snapshot restored before `f`
+/// runs, so request context such as a `trace_id` carries over into worker
+/// threads.
+///
+/// # Arguments
+///
+/// * `snapshot` - The context to restore in the new thread
+/// * `f` - The closure to run in the new thread
+///
+/// # Returns
+///
+/// The spawned thread's join handle.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{capture_context, info, set_thread_logger, spawn_with_context, Logger};
+///
+/// set_thread_logger(Logger::new().with("request_id", "req-7"));
+/// let worker = spawn_with_context(capture_context(), || {
+///     info!("Resizing image"); // includes request_id=req-7
+/// });
+/// worker.join().unwrap();
+/// ```
+pub fn spawn_with_context<F, T>(snapshot: ContextSnapshot, f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    thread::spawn(move || {
+        snapshot.restore();
+        f()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Verifies that a spawned thread sees the captured thread logger, scoped
+    /// fields, and trace context.
+    fn test_spawn_with_context() {
+        set_thread_logger(Logger::new().with("trace_id", "abc123"));
+        let snapshot = capture_context();
+        clear_thread_logger();
+
+        let trace_id = spawn_with_context(snapshot, || {
+            capture_context()
+                .logger()
+                .and_then(|logger| logger.context.get("trace_id").cloned())
+        })
+        .join()
+        .unwrap();
+        assert_eq!(trace_id.as_deref(), Some("abc123"));
+
+        let scope = push_context(&[("request_id", "req-7")]);
+        let scoped = spawn_with_context(capture_context(), || with_scoped_fields(<[_]>::to_vec));
+        drop(scope);
+        assert_eq!(
+            scoped.join().unwrap(),
+            [("request_id".to_string(), "req-7".to_string())]
+        );
+
+        let entered = crate::enter_trace_context("4bf92f35", "00f067aa");
+        let traced = spawn_with_context(capture_context(), current_trace_context);
+        drop(entered);
+        assert_eq!(
+            traced.join().unwrap(),
+            Some(TraceContext::new("4bf92f35", "00f067aa"))
+        );
+
+        let empty = spawn_with_context(capture_context(), || {
+            let snapshot = capture_context();
+            snapshot.logger().is_none() && snapshot.trace().is_none()
+        });
+        assert!(empty.join().unwrap());
+    }
+
+    #[test]
+    /// Verifies that nested scopes compose and are removed by their guards.
+    fn test_push_context() {
+        let keys = || with_scoped_fields(|fields| fields.len());
+        let outer = push_context(&[("request_id", "req-1"), ("user", "alice")]);
+        {
+            let _inner = push_context(&[("user", "bob")]);
+            assert_eq!(keys(), 3);
+        }
+        assert_eq!(keys(), 2);
+        drop(outer);
+        assert_eq!(keys(), 0);
+    }
+}
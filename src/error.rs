@@ -0,0 +1,197 @@
+//! Error types for failures inside the logger itself, for parsing levels and
+//! configurations, and for invalid configurations.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// An error that occurred while emitting a log entry.
+///
+/// Logging calls never return these errors to the caller; instead they are
+/// passed to the handler installed with [`set_error_handler`](crate::set_error_handler),
+/// so the policy for logging failures lives in one place.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{set_error_handler, CcbError};
+///
+/// set_error_handler(|err: &CcbError| {
+///     if let CcbError::Io(io) = err {
+///         eprintln!("logging failed: {}", io);
+///     }
+/// });
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CcbError {
+    /// Writing the entry to its output failed, for example because of a
+    /// broken pipe or a full disk.
+    Io(io::Error),
+    /// Rendering or writing the entry panicked.
+    Panicked,
+    /// A hook added with [`Logger::with_hook`](crate::Logger::with_hook)
+    /// panicked. The entry is still written.
+    HookPanicked,
+}
+
+impl fmt::Display for CcbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CcbError::Io(err) => write!(f, "failed to write log entry: {}", err),
+            CcbError::Panicked => write!(f, "writing a log entry panicked"),
+            CcbError::HookPanicked => write!(f, "a log entry hook panicked"),
+        }
+    }
+}
+
+impl Error for CcbError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CcbError::Io(err) => Some(err),
+            CcbError::Panicked | CcbError::HookPanicked => None,
+        }
+    }
+}
+
+impl From<io::Error> for CcbError {
+    fn from(err: io::Error) -> Self {
+        CcbError::Io(err)
+    }
+}
+
+/// The error returned when parsing a [`Level`](crate::Level) from a string
+/// fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::Level;
+///
+/// let err = "loud".parse::<Level>().unwrap_err();
+/// assert_eq!(err.input(), "loud");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLevelError {
+    /// The string that didn't name a level.
+    input: String,
+}
+
+impl ParseLevelError {
+    pub(crate) fn new(input: &str) -> Self {
+        Self {
+            input: input.to_string(),
+        }
+    }
+
+    /// Returns the string that failed to parse.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+}
+
+impl fmt::Display for ParseLevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown log level `{}` (expected trace, debug, info, warn, error or off)",
+            self.input
+        )
+    }
+}
+
+impl Error for ParseLevelError {}
+
+/// The error returned by [`LoggerBuilder::build`](crate::LoggerBuilder::build)
+/// for an invalid or contradictory configuration.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{ConfigError, Logger};
+///
+/// let err = Logger::builder().with_timestamp_format("%Q").build().unwrap_err();
+/// assert!(matches!(err, ConfigError::InvalidTimestampFormat(_)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConfigError {
+    /// The timestamp format is empty or has an unknown specifier.
+    InvalidTimestampFormat(String),
+    /// Colors were explicitly enabled for a format that never renders them,
+    /// such as JSON. Holds the format's description.
+    ColorsUnsupported(String),
+    /// The active window ends before it starts, so nothing would be logged.
+    EmptyActiveWindow,
+    /// The record delimiter is empty, so entries would run together.
+    EmptyRecordDelimiter,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::InvalidTimestampFormat(format) => {
+                write!(f, "invalid timestamp format `{}`", format)
+            }
+            ConfigError::ColorsUnsupported(format) => {
+                write!(
+                    f,
+                    "colors were enabled, but the {} format never renders them",
+                    format
+                )
+            }
+            ConfigError::EmptyActiveWindow => {
+                write!(f, "the active window ends before it starts")
+            }
+            ConfigError::EmptyRecordDelimiter => {
+                write!(f, "the record delimiter is empty")
+            }
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// The error returned when parsing a [`Config`](crate::Config) from its
+/// one-line `key=value` form fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{Config, ParseConfigError};
+///
+/// let err = "level=loud".parse::<Config>().unwrap_err();
+/// assert!(matches!(err, ParseConfigError::InvalidValue { .. }));
+/// assert_eq!(err.to_string(), "invalid value `loud` for `level`");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseConfigError {
+    /// A setting named a key the one-line form doesn't know.
+    UnknownKey(String),
+    /// A known key was given a value it doesn't accept.
+    InvalidValue {
+        /// The setting's key.
+        key: String,
+        /// The rejected value.
+        value: String,
+    },
+    /// A setting had no `=`. Holds the setting as written.
+    MissingValue(String),
+}
+
+impl fmt::Display for ParseConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseConfigError::UnknownKey(key) => write!(f, "unknown config key `{}`", key),
+            ParseConfigError::InvalidValue { key, value } => {
+                write!(f, "invalid value `{}` for `{}`", value, key)
+            }
+            ParseConfigError::MissingValue(setting) => {
+                write!(f, "expected `key=value`, found `{}`", setting)
+            }
+        }
+    }
+}
+
+impl Error for ParseConfigError {}
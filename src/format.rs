@@ -0,0 +1,840 @@
+//! Output formats and the renderers for non-pretty formats.
+
+use std::fmt::{self, Write as _};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, TimeZone};
+
+use crate::LogEntry;
+
+/// Default timestamp layout of the pretty and CSV formats, with milliseconds.
+pub(crate) const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+/// ISO-8601 timestamp layout, with milliseconds and UTC offset.
+pub(crate) const ISO_TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3f%:z";
+
+/// How many fractional-second digits timestamps show.
+///
+/// See [`Logger::with_timestamp_precision`](crate::Logger::with_timestamp_precision).
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{Logger, TimestampPrecision};
+///
+/// let logger = Logger::new().with_timestamp_precision(TimestampPrecision::Micros);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum TimestampPrecision {
+    /// Whole seconds: `15:00:00`.
+    Seconds,
+    /// Milliseconds: `15:00:00.123`. This is the default.
+    #[default]
+    Millis,
+    /// Microseconds: `15:00:00.123456`.
+    Micros,
+    /// Nanoseconds: `15:00:00.123456789`.
+    Nanos,
+}
+
+impl TimestampPrecision {
+    /// Returns the pretty and CSV timestamp layout with this precision.
+    pub(crate) fn timestamp_format(self) -> &'static str {
+        match self {
+            TimestampPrecision::Seconds => "%Y-%m-%d %H:%M:%S",
+            TimestampPrecision::Millis => TIMESTAMP_FORMAT,
+            TimestampPrecision::Micros => "%Y-%m-%d %H:%M:%S%.6f",
+            TimestampPrecision::Nanos => "%Y-%m-%d %H:%M:%S%.9f",
+        }
+    }
+
+    /// Returns the layout of the sub-second part shown by
+    /// [`TimestampMode::Compact`], at least milliseconds.
+    pub(crate) fn fraction_format(self) -> &'static str {
+        match self {
+            TimestampPrecision::Seconds | TimestampPrecision::Millis => "%.3f",
+            TimestampPrecision::Micros => "%.6f",
+            TimestampPrecision::Nanos => "%.9f",
+        }
+    }
+
+    /// Returns the ISO-8601 timestamp layout with this precision.
+    pub(crate) fn iso_format(self) -> &'static str {
+        match self {
+            TimestampPrecision::Seconds => "%Y-%m-%dT%H:%M:%S%:z",
+            TimestampPrecision::Millis => ISO_TIMESTAMP_FORMAT,
+            TimestampPrecision::Micros => "%Y-%m-%dT%H:%M:%S%.6f%:z",
+            TimestampPrecision::Nanos => "%Y-%m-%dT%H:%M:%S%.9f%:z",
+        }
+    }
+}
+
+/// How pretty timestamps are shown for consecutive entries.
+///
+/// See [`Logger::with_timestamp_mode`](crate::Logger::with_timestamp_mode).
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{Logger, TimestampMode};
+///
+/// let logger = Logger::new().with_timestamp_mode(TimestampMode::Compact);
+/// // 2009-11-10 15:00:00.123 INFO Started
+/// // +.125 DEBG Tick
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum TimestampMode {
+    /// Every entry shows its full timestamp. This is the default.
+    #[default]
+    Full,
+    /// An entry in the same second as the previous timestamped entry only
+    /// shows its sub-second part, such as `+.125`.
+    Compact,
+}
+
+/// Returns whether `pattern` is a usable chrono format string.
+///
+/// Empty patterns and patterns with unknown or incomplete specifiers, such as
+/// `%Q` or a trailing `%`, are rejected.
+pub(crate) fn is_valid_timestamp_format(pattern: &str) -> bool {
+    !pattern.is_empty() && StrftimeItems::new(pattern).all(|item| item != Item::Error)
+}
+
+/// Formats `timestamp` with `pattern`, falling back to [`TIMESTAMP_FORMAT`]
+/// when the pattern can't be rendered.
+pub(crate) fn format_datetime<Tz>(timestamp: &DateTime<Tz>, pattern: &str) -> String
+where
+    Tz: TimeZone,
+    Tz::Offset: fmt::Display,
+{
+    let mut formatted = String::new();
+    if write!(formatted, "{}", timestamp.format(pattern)).is_err() {
+        return timestamp.format(TIMESTAMP_FORMAT).to_string();
+    }
+    formatted
+}
+
+/// The version of the JSON record layout announced by the schema header.
+pub(crate) const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// The version byte that starts every MessagePack frame.
+#[cfg(feature = "msgpack")]
+pub(crate) const MSGPACK_FRAME_VERSION: u8 = 1;
+
+/// Keys the JSON format reserves for an entry's own properties.
+const JSON_RESERVED_KEYS: [&str; 5] = ["timestamp", "level", "target", "message", "location"];
+
+/// Keys the logfmt format reserves for an entry's own properties.
+const LOGFMT_RESERVED_KEYS: [&str; 5] = ["level", "target", "ts", "msg", "location"];
+
+/// Keys the JSON and logfmt formats reserve for an entry's trace context,
+/// when it has one.
+const TRACE_KEYS: [&str; 2] = ["trace_id", "span_id"];
+
+/// Returns whether a field key collides with one of the entry's own keys.
+fn is_reserved(reserved: &[&str], entry: &LogEntry, key: &str) -> bool {
+    reserved.contains(&key) || (entry.trace.is_some() && TRACE_KEYS.contains(&key))
+}
+
+/// Selects how log entries are rendered.
+///
+/// The default `Pretty` format is the colored, human-oriented terminal layout.
+/// Other formats trade readability for easier machine processing. Formats may
+/// be added, including ones behind features such as `msgpack`, so matches on
+/// this enum need a wildcard arm.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{CsvFormat, Format, Logger};
+///
+/// let logger = Logger::new().with_format(Format::Csv(CsvFormat::new(["user_id", "ip"])));
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[non_exhaustive]
+pub enum Format {
+    /// Human-readable terminal output with optional colors.
+    #[default]
+    Pretty,
+    /// Comma-separated values with a fixed set of field columns.
+    Csv(CsvFormat),
+    /// One JSON object per entry, with fields as top-level keys.
+    ///
+    /// Each object has `timestamp` (ISO-8601), `level`, and `message` keys,
+    /// plus `target` for entries with a target, `location` when locations
+    /// are recorded, and `trace_id` and `span_id` for entries with a
+    /// [`TraceContext`](crate::TraceContext). Fields follow, sorted by
+    /// key; a field whose key collides with one of those is renamed with a
+    /// `fields.` prefix. Colors are never applied.
+    Json,
+    /// `key=value` pairs separated by spaces, one line per entry.
+    ///
+    /// Each line starts with `level`, `target` for entries with a target,
+    /// `ts` (ISO-8601), and `msg`, plus `location` when locations are
+    /// recorded and `trace_id` and `span_id` for entries with a trace
+    /// context, followed by the fields sorted
+    /// by key. Values that are empty or contain spaces, `=`, quotes, or
+    /// control characters are double-quoted with escapes. Field keys that
+    /// collide with the leading keys get a `fields.` prefix. Colors are never
+    /// applied.
+    Logfmt,
+    /// Versioned, length-prefixed MessagePack records, for compact binary
+    /// sinks.
+    ///
+    /// Each entry is the [`LogEntry`] serialized with `rmp-serde` as a map
+    /// with named keys. Its frame starts with a framing version byte,
+    /// currently `1`, followed by the record length as a 4-byte big-endian
+    /// integer, so a reader can frame records and reject layouts it doesn't
+    /// know. Timestamps are kept as recorded, in RFC 3339 with their offset,
+    /// and the record delimiter is not written. Requires the `msgpack`
+    /// feature.
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+    /// Several formats rendered back to back for every entry.
+    Composite(CompositeFormat),
+}
+
+impl Format {
+    /// Returns a short human-readable summary of the format and its options.
+    /// Returns whether any part of this format renders colors.
+    pub(crate) fn supports_colors(&self) -> bool {
+        match self {
+            Format::Pretty => true,
+            Format::Csv(_) | Format::Json | Format::Logfmt => false,
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => false,
+            Format::Composite(composite) => composite.formats.iter().any(Format::supports_colors),
+        }
+    }
+
+    /// Returns whether this format is purely machine-readable, with no
+    /// human-oriented pretty part.
+    pub(crate) fn is_structured(&self) -> bool {
+        !self.supports_colors()
+    }
+
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            Format::Pretty => "pretty".to_string(),
+            Format::Json => "json".to_string(),
+            Format::Logfmt => "logfmt".to_string(),
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => "messagepack".to_string(),
+            Format::Csv(csv) => format!(
+                "csv (columns: {}; extra column: {})",
+                csv.columns.join(", "),
+                if csv.overflow_column { "on" } else { "off" }
+            ),
+            Format::Composite(composite) => format!(
+                "composite ({})",
+                composite
+                    .formats
+                    .iter()
+                    .map(Format::describe)
+                    .collect::<Vec<_>>()
+                    .join(" + ")
+            ),
+        }
+    }
+}
+
+/// Configuration for CSV output.
+///
+/// Every record has the columns `timestamp`, `level`, `message`, followed by one
+/// column per configured field key, in order. Fields whose keys are not listed
+/// are collected into a trailing `extra` column as space-separated `key=value`
+/// pairs, or dropped entirely when the overflow column is disabled.
+///
+/// A header row naming the columns is written before the first record. Loggers
+/// cloned from one another share the same header state, so the header is only
+/// written once per output.
+///
+/// Values are quoted according to RFC 4180: any value containing a comma,
+/// double quote, or line break is wrapped in double quotes with embedded quotes
+/// doubled.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::CsvFormat;
+///
+/// let csv = CsvFormat::new(["user_id", "ip"]).with_overflow_column(false);
+/// assert_eq!(csv.header(), "timestamp,level,message,user_id,ip");
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CsvFormat {
+    /// Field keys rendered as dedicated columns, in order.
+    columns: Vec<String>,
+    /// Whether unlisted fields are kept in a trailing `extra` column.
+    overflow_column: bool,
+    /// Set once the header row has been written.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    header_written: Arc<AtomicBool>,
+}
+
+impl CsvFormat {
+    /// Creates a CSV format with the given field keys as columns.
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - The field keys to render as dedicated columns, in order
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::CsvFormat;
+    ///
+    /// let csv = CsvFormat::new(["method", "path", "status"]);
+    /// ```
+    pub fn new<I, S>(columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            columns: columns.into_iter().map(Into::into).collect(),
+            overflow_column: true,
+            header_written: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Controls whether fields without a dedicated column are kept.
+    ///
+    /// When enabled (the default), such fields are written to a trailing
+    /// `extra` column. When disabled, they are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to emit the overflow column
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_overflow_column(mut self, enabled: bool) -> Self {
+        self.overflow_column = enabled;
+        self
+    }
+
+    /// Returns the header row for this format, without a trailing newline.
+    pub fn header(&self) -> String {
+        let mut columns = vec!["timestamp", "level", "message"];
+        columns.extend(self.columns.iter().map(String::as_str));
+        if self.overflow_column {
+            columns.push("extra");
+        }
+        columns
+            .into_iter()
+            .map(escape_csv)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Renders a single entry as one CSV record, preceded by the header row
+    /// if it hasn't been written yet.
+    ///
+    /// The timestamp is passed in already formatted so that it honors the
+    /// logger's timezone settings, and both rows are terminated by the
+    /// logger's record delimiter. A `preview` render includes the header
+    /// when it is still due but leaves it due for the real output.
+    pub(crate) fn render(
+        &self,
+        entry: &LogEntry,
+        timestamp: &str,
+        delimiter: &str,
+        preview: bool,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        let header_due = if preview {
+            !self.header_written.load(Ordering::Relaxed)
+        } else {
+            !self.header_written.swap(true, Ordering::Relaxed)
+        };
+        if header_due {
+            write!(out, "{}{}", self.header(), delimiter)?;
+        }
+
+        let mut record = vec![
+            timestamp.to_string(),
+            entry.level.as_str().to_string(),
+            entry.message.clone(),
+        ];
+        for column in &self.columns {
+            record.push(entry.fields.get(column).cloned().unwrap_or_default());
+        }
+        if self.overflow_column {
+            let mut extra: Vec<_> = entry
+                .fields
+                .iter()
+                .filter(|(key, _)| !self.columns.contains(key))
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect();
+            extra.sort();
+            record.push(extra.join(" "));
+        }
+
+        let record: Vec<_> = record.iter().map(|value| escape_csv(value)).collect();
+        write!(out, "{}{}", record.join(","), delimiter)
+    }
+}
+
+/// Renders each entry through several formats in sequence.
+///
+/// This produces hybrid human and machine logs in a single output, such as a
+/// pretty line followed by a comment-prefixed machine-readable record that
+/// tooling which skips comments can still parse. Each format terminates its own
+/// output with the logger's record delimiter; the separator is written between
+/// consecutive formats and defaults to nothing.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{CompositeFormat, CsvFormat, Format, Logger};
+///
+/// let hybrid = CompositeFormat::new([Format::Pretty, Format::Csv(CsvFormat::new(["user_id"]))])
+///     .with_separator("# ");
+/// let logger = Logger::new().with_format(Format::Composite(hybrid));
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompositeFormat {
+    /// The formats each entry is rendered in, in order.
+    formats: Vec<Format>,
+    /// Written between the output of consecutive formats.
+    separator: String,
+}
+
+impl CompositeFormat {
+    /// Creates a composite that renders entries in each of `formats` in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `formats` - The formats to render each entry in
+    pub fn new<I>(formats: I) -> Self
+    where
+        I: IntoIterator<Item = Format>,
+    {
+        Self {
+            formats: formats.into_iter().collect(),
+            separator: String::new(),
+        }
+    }
+
+    /// Sets the string written between the output of consecutive formats.
+    ///
+    /// # Arguments
+    ///
+    /// * `separator` - The separator, e.g. `"# "` to comment out later formats
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Returns the formats each entry is rendered in.
+    pub(crate) fn formats(&self) -> &[Format] {
+        &self.formats
+    }
+
+    /// Returns the separator written between formats.
+    pub(crate) fn separator(&self) -> &str {
+        &self.separator
+    }
+}
+
+/// Renders a single entry as one JSON object.
+///
+/// The timestamp is passed in already formatted so that it honors the
+/// logger's timezone settings.
+///
+/// With `nested`, field keys are split on `.` into nested objects, so
+/// `http.status` is written as `{"http":{"status":...}}`. Fields are applied
+/// in key order and a later one wins a conflict: `http.status` replaces a
+/// plain `http` value with an object.
+///
+/// Fields whose keys are in `overflow` are left out of the top level and
+/// written together as a stringified object in a trailing `extra` key. A
+/// field named `extra` is then moved aside like a reserved key.
+pub(crate) fn render_json(
+    entry: &LogEntry,
+    timestamp: &str,
+    nested: bool,
+    overflow: &[String],
+    delimiter: &str,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let mut members = vec![
+        ("timestamp".to_string(), JsonNode::leaf(timestamp)),
+        ("level".to_string(), JsonNode::leaf(entry.level.name())),
+    ];
+    if let Some(target) = &entry.target {
+        members.push(("target".to_string(), JsonNode::leaf(target)));
+    }
+    members.push(("message".to_string(), JsonNode::leaf(&entry.message)));
+    if let Some(location) = entry.location {
+        let location = format!("{}:{}", location.file(), location.line());
+        members.push(("location".to_string(), JsonNode::Leaf(location)));
+    }
+    if let Some(trace) = &entry.trace {
+        members.push(("trace_id".to_string(), JsonNode::leaf(&trace.trace_id)));
+        members.push(("span_id".to_string(), JsonNode::leaf(&trace.span_id)));
+    }
+
+    let mut fields: Vec<_> = entry.fields.iter().collect();
+    fields.sort_unstable_by_key(|(key, _)| *key);
+    let mut field_members = Vec::new();
+    let mut extra = Vec::new();
+    for (key, value) in fields {
+        if overflow.contains(key) {
+            extra.push((key.clone(), JsonNode::leaf(value)));
+            continue;
+        }
+        let top = if nested {
+            key.split('.').next().unwrap_or_default()
+        } else {
+            key
+        };
+        let moved = !overflow.is_empty() && top == "extra";
+        let key = if moved || is_reserved(&JSON_RESERVED_KEYS, entry, top) {
+            format!("fields.{}", key)
+        } else {
+            key.clone()
+        };
+        if nested {
+            let path: Vec<_> = key.split('.').collect();
+            JsonNode::insert(&mut field_members, &path, value);
+        } else {
+            field_members.push((key, JsonNode::leaf(value)));
+        }
+    }
+    members.extend(field_members);
+    if !extra.is_empty() {
+        let extra = JsonNode::Object(extra).to_string();
+        members.push(("extra".to_string(), JsonNode::Leaf(extra)));
+    }
+
+    write!(out, "{}{}", JsonNode::Object(members), delimiter)
+}
+
+/// Returns the schema descriptor written before the first JSON record of a
+/// sink, without a delimiter.
+///
+/// The descriptor is a single `_schema` object holding the layout version and
+/// the keys records may carry, mapped to their JSON types. Besides the
+/// entry's own keys, it lists `field_keys`, the logger's context fields,
+/// which every record carries; with `nested`, dotted keys are listed as
+/// nested objects as in the records. All values are strings.
+pub(crate) fn json_schema(field_keys: &[&str], nested: bool) -> String {
+    let mut fields: Vec<_> = JSON_RESERVED_KEYS
+        .iter()
+        .chain(&TRACE_KEYS)
+        .map(|key| (key.to_string(), JsonNode::leaf("string")))
+        .collect();
+    for key in field_keys {
+        let top = if nested {
+            key.split('.').next().unwrap_or_default()
+        } else {
+            key
+        };
+        let key = if JSON_RESERVED_KEYS.contains(&top) || TRACE_KEYS.contains(&top) {
+            format!("fields.{}", key)
+        } else {
+            key.to_string()
+        };
+        if nested {
+            let path: Vec<_> = key.split('.').collect();
+            JsonNode::insert(&mut fields, &path, "string");
+        } else {
+            fields.push((key, JsonNode::leaf("string")));
+        }
+    }
+    format!(
+        "{{\"_schema\":{{\"version\":{},\"fields\":{}}}}}",
+        JSON_SCHEMA_VERSION,
+        JsonNode::Object(fields)
+    )
+}
+
+/// A JSON value of the JSON format: a string or an object.
+enum JsonNode {
+    /// A string value.
+    Leaf(String),
+    /// An object, with members in insertion order.
+    Object(Vec<(String, JsonNode)>),
+}
+
+impl JsonNode {
+    /// Creates a string value.
+    fn leaf(value: &str) -> Self {
+        JsonNode::Leaf(value.to_string())
+    }
+
+    /// Sets the value at `path` within `members`, creating objects along the
+    /// way and replacing any value that is in the way.
+    fn insert(members: &mut Vec<(String, JsonNode)>, path: &[&str], value: &str) {
+        let Some((&key, rest)) = path.split_first() else {
+            return;
+        };
+        let index = match members.iter().position(|(k, _)| k == key) {
+            Some(index) => index,
+            None => {
+                members.push((key.to_string(), JsonNode::Object(Vec::new())));
+                members.len() - 1
+            }
+        };
+        let node = &mut members[index].1;
+        if rest.is_empty() {
+            *node = JsonNode::leaf(value);
+            return;
+        }
+        if !matches!(node, JsonNode::Object(_)) {
+            *node = JsonNode::Object(Vec::new());
+        }
+        if let JsonNode::Object(children) = node {
+            JsonNode::insert(children, rest, value);
+        }
+    }
+}
+
+impl fmt::Display for JsonNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonNode::Leaf(value) => write!(f, "\"{}\"", escape_json(value)),
+            JsonNode::Object(members) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "\"{}\":{}", escape_json(key), value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Writes a single entry as a MessagePack record preceded by the framing
+/// version byte and its length as a big-endian `u32`.
+#[cfg(feature = "msgpack")]
+pub(crate) fn render_msgpack(entry: &LogEntry, out: &mut dyn Write) -> io::Result<()> {
+    let record = rmp_serde::to_vec_named(entry).map_err(io::Error::other)?;
+    let length = u32::try_from(record.len()).map_err(io::Error::other)?;
+    out.write_all(&[MSGPACK_FRAME_VERSION])?;
+    out.write_all(&length.to_be_bytes())?;
+    out.write_all(&record)
+}
+
+/// Renders a single entry as one logfmt line.
+///
+/// The timestamp is passed in already formatted so that it honors the
+/// logger's timezone settings.
+pub(crate) fn render_logfmt(
+    entry: &LogEntry,
+    timestamp: &str,
+    delimiter: &str,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let mut pairs = vec![format!("level={}", escape_logfmt(entry.level.name()))];
+    if let Some(target) = &entry.target {
+        pairs.push(format!("target={}", escape_logfmt(target)));
+    }
+    pairs.push(format!("ts={}", escape_logfmt(timestamp)));
+    pairs.push(format!("msg={}", escape_logfmt(&entry.message)));
+    if let Some(location) = entry.location {
+        pairs.push(format!("location={}:{}", location.file(), location.line()));
+    }
+    if let Some(trace) = &entry.trace {
+        pairs.push(format!("trace_id={}", escape_logfmt(&trace.trace_id)));
+        pairs.push(format!("span_id={}", escape_logfmt(&trace.span_id)));
+    }
+
+    let mut fields: Vec<_> = entry.fields.iter().collect();
+    fields.sort_unstable_by_key(|(key, _)| *key);
+    for (key, value) in fields {
+        let prefix = if is_reserved(&LOGFMT_RESERVED_KEYS, entry, key) {
+            "fields."
+        } else {
+            ""
+        };
+        pairs.push(format!(
+            "{}{}={}",
+            prefix,
+            escape_logfmt_key(key),
+            escape_logfmt(value)
+        ));
+    }
+
+    write!(out, "{}{}", pairs.join(" "), delimiter)
+}
+
+/// Quotes a logfmt value if it is empty or contains characters that would
+/// break `key=value` parsing.
+pub(crate) fn escape_logfmt(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c == ' ' || c == '=' || c == '"' || c.is_control());
+    if needs_quotes {
+        format!("\"{}\"", escape_json(value))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Replaces characters that can't appear in a logfmt key with underscores.
+fn escape_logfmt_key(key: &str) -> String {
+    key.chars()
+        .map(|c| {
+            if c == ' ' || c == '=' || c == '"' || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Escapes a string for use inside a JSON string literal.
+fn escape_json(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if u32::from(c) < 0x20 => escaped.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Quotes a CSV value if it contains a delimiter, quote, or line break.
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Level;
+    use chrono::{Local, TimeZone};
+    use std::collections::HashMap;
+
+    fn entry(fields: &[(&str, &str)]) -> LogEntry {
+        LogEntry {
+            level: Level::Warn,
+            message: "disk \"almost\" full, act now".to_string(),
+            fields: fields
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>(),
+            timestamp: Local.with_ymd_and_hms(2024, 1, 15, 14, 30, 25).unwrap(),
+            location: None,
+            target: None,
+            trace: None,
+            inherited_keys: Default::default(),
+        }
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    /// Verifies that MessagePack records are versioned, length-prefixed, and
+    /// decode back into the entry.
+    fn test_render_msgpack() {
+        let entry = entry(&[("user", "ada")]);
+        let mut out = Vec::new();
+        render_msgpack(&entry, &mut out).unwrap();
+        render_msgpack(&entry, &mut out).unwrap();
+
+        assert_eq!(out[0], MSGPACK_FRAME_VERSION);
+        let (prefix, rest) = out[1..].split_at(4);
+        let length = u32::from_be_bytes(prefix.try_into().unwrap()) as usize;
+        assert_eq!(rest.len(), 2 * (5 + length) - 5);
+        assert_eq!(rest[length], MSGPACK_FRAME_VERSION);
+
+        let decoded: LogEntry = rmp_serde::from_slice(&rest[..length]).unwrap();
+        assert_eq!(decoded.level, Level::Warn);
+        assert_eq!(decoded.message, entry.message);
+        assert_eq!(decoded.fields, entry.fields);
+        assert_eq!(decoded.timestamp, entry.timestamp);
+    }
+
+    fn render(csv: &CsvFormat, entry: &LogEntry, out: &mut Vec<u8>) {
+        let timestamp = entry.timestamp.format(TIMESTAMP_FORMAT).to_string();
+        csv.render(entry, &timestamp, "\n", false, out).unwrap();
+    }
+
+    #[test]
+    /// Verifies header emission, column mapping, overflow, and quoting.
+    fn test_csv_render() {
+        let csv = CsvFormat::new(["disk", "used"]);
+        let mut out = Vec::new();
+
+        render(
+            &csv,
+            &entry(&[("disk", "/dev/sda1"), ("host", "a,b")]),
+            &mut out,
+        );
+        render(&csv, &entry(&[]), &mut out);
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "timestamp,level,message,disk,used,extra\n\
+             2024-01-15 14:30:25.000,WARN,\"disk \"\"almost\"\" full, act now\",/dev/sda1,,\"host=a,b\"\n\
+             2024-01-15 14:30:25.000,WARN,\"disk \"\"almost\"\" full, act now\",,,\n"
+        );
+    }
+
+    #[test]
+    /// Verifies logfmt quoting of values and sanitizing of keys.
+    fn test_logfmt_render() {
+        let mut out = Vec::new();
+        let entry = entry(&[
+            ("msg", "dup"),
+            ("path", "/tmp/a b"),
+            ("bad key", "x=1"),
+            ("empty", ""),
+        ]);
+
+        render_logfmt(&entry, "2024-01-15T14:30:25.000+00:00", "\n", &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "level=warn ts=2024-01-15T14:30:25.000+00:00 msg=\"disk \\\"almost\\\" full, act now\" \
+             bad_key=\"x=1\" empty=\"\" fields.msg=dup path=\"/tmp/a b\"\n"
+        );
+    }
+
+    #[test]
+    /// Tests that unlisted fields are dropped when the overflow column is disabled.
+    fn test_csv_without_overflow() {
+        let csv = CsvFormat::new(["disk"]).with_overflow_column(false);
+        let mut out = Vec::new();
+
+        render(&csv, &entry(&[("disk", "sda"), ("host", "db1")]), &mut out);
+
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.lines().nth(1).unwrap().ends_with(",sda"));
+        assert!(!output.contains("db1"));
+    }
+}
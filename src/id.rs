@@ -0,0 +1,123 @@
+//! Generation of the ids CCB attaches to entries, such as span ids.
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Characters used by the default random ids.
+const BASE62: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Length of the default random ids; 12 base62 characters hold about 71 bits.
+const RANDOM_ID_LEN: usize = 12;
+
+/// Produces the ids CCB generates, such as span ids.
+///
+/// The default generator returns short random base62 strings. Use
+/// [`IdGenerator::new`] with [`Logger::with_id_generator`](crate::Logger::with_id_generator)
+/// to match the id format of another tracing system, for example UUIDs.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::IdGenerator;
+///
+/// let ids = IdGenerator::counter();
+/// assert_eq!(ids.generate(), "1");
+/// assert_eq!(ids.generate(), "2");
+/// assert_eq!(IdGenerator::default().generate().len(), 12);
+/// ```
+#[derive(Clone)]
+pub struct IdGenerator(Arc<dyn Fn() -> String + Send + Sync>);
+
+impl IdGenerator {
+    /// Creates a generator that calls `generate` for each id.
+    ///
+    /// # Arguments
+    ///
+    /// * `generate` - A closure returning a new id on each call
+    pub fn new<F>(generate: F) -> Self
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        Self(Arc::new(generate))
+    }
+
+    /// Creates a generator of short random base62 ids. This is the default.
+    pub fn random() -> Self {
+        Self::new(random_base62)
+    }
+
+    /// Creates a generator of increasing decimal ids starting at 1.
+    ///
+    /// Clones of the generator share the same counter.
+    pub fn counter() -> Self {
+        let next = Arc::new(AtomicU64::new(1));
+        Self::new(move || next.fetch_add(1, Ordering::Relaxed).to_string())
+    }
+
+    /// Returns a new id.
+    pub fn generate(&self) -> String {
+        (self.0)()
+    }
+}
+
+impl Default for IdGenerator {
+    fn default() -> Self {
+        Self::random()
+    }
+}
+
+impl fmt::Debug for IdGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("IdGenerator")
+    }
+}
+
+/// Returns a random base62 id.
+///
+/// The randomness comes from the standard library's randomly keyed hasher,
+/// mixed with a process-wide counter and the current time, so no extra
+/// dependency is needed. The ids are unpredictable enough for correlation but
+/// are not suitable for security purposes.
+fn random_base62() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    hasher.write_u128(nanos);
+    let mut bits = hasher.finish();
+
+    let mut id = String::with_capacity(RANDOM_ID_LEN);
+    for index in 0..RANDOM_ID_LEN {
+        if index == 10 {
+            // 64 bits only cover ten and a half digits; draw fresh ones
+            hasher.write_u8(0);
+            bits = hasher.finish();
+        }
+        id.push(BASE62[(bits % 62) as usize] as char);
+        bits /= 62;
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    /// Verifies that random ids are well-formed and distinct.
+    fn test_random_ids() {
+        let ids: HashSet<_> = (0..1000).map(|_| random_base62()).collect();
+        assert_eq!(ids.len(), 1000);
+        assert!(ids
+            .iter()
+            .all(|id| id.len() == RANDOM_ID_LEN && id.bytes().all(|b| b.is_ascii_alphanumeric())));
+    }
+}
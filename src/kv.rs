@@ -0,0 +1,160 @@
+//! Parsing of `key=value` strings into structured fields.
+
+use crate::{Level, Logger};
+
+/// Key under which tokens that aren't `key=value` pairs are collected.
+const UNPARSED_KEY: &str = "unparsed";
+
+/// Parses a space-separated `key=value` string into fields.
+///
+/// Pairs are separated by whitespace and split at the first `=`. A value may be
+/// wrapped in double quotes to include spaces, with `\"` and `\\` escapes
+/// inside the quotes; an unterminated quote runs to the end of the input.
+///
+/// Parsing is lenient: tokens without an `=` or with an empty key are not
+/// dropped but collected, space-separated, into a single `unparsed` field.
+///
+/// # Arguments
+///
+/// * `input` - The string to parse, e.g. `user=alice msg="hello world"`
+///
+/// # Returns
+///
+/// The parsed fields in input order.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::parse_fields;
+///
+/// let fields = parse_fields(r#"user=alice note="two words" oops"#);
+/// assert_eq!(
+///     fields,
+///     [
+///         ("user".to_string(), "alice".to_string()),
+///         ("note".to_string(), "two words".to_string()),
+///         ("unparsed".to_string(), "oops".to_string()),
+///     ]
+/// );
+/// ```
+pub fn parse_fields(input: &str) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    let mut unparsed = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|&c| c != '=' && !c.is_whitespace()) {
+            key.push(c);
+        }
+        let is_pair = !key.is_empty() && chars.next_if_eq(&'=').is_some();
+        if !is_pair {
+            // Not a pair; keep the rest of the token for the unparsed field
+            let mut token = key;
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                token.push(c);
+            }
+            unparsed.push(token);
+            continue;
+        }
+
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => value.extend(chars.next()),
+                    c => value.push(c),
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                value.push(c);
+            }
+        }
+        fields.push((key, value));
+    }
+
+    if !unparsed.is_empty() {
+        fields.push((UNPARSED_KEY.to_string(), unparsed.join(" ")));
+    }
+    fields
+}
+
+impl Logger {
+    /// Logs a message with fields parsed from a `key=value` string.
+    ///
+    /// This eases migrating `println!`-style logging that already formats its
+    /// data as `key=value` pairs. See [`parse_fields`] for the accepted syntax;
+    /// malformed pairs never cause the entry to be dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The severity level for the entry
+    /// * `message` - The log message
+    /// * `pairs` - Space-separated `key=value` pairs
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Level, Logger};
+    ///
+    /// let logger = Logger::new();
+    /// let user = "alice";
+    /// logger.log_parsed(Level::Info, "Login", &format!("user={} attempts=2", user));
+    /// ```
+    #[track_caller]
+    pub fn log_parsed(&self, level: Level, message: &str, pairs: &str) {
+        let fields = parse_fields(pairs);
+        let fields: Vec<_> = fields
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        self.log(level, message, &fields);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    /// Verifies plain pairs, quoted values with escapes, and empty values.
+    fn test_parse_fields() {
+        assert_eq!(
+            parse_fields(r#"  a=1  path="/tmp/my file" quote="say \"hi\"" empty= "#),
+            pairs(&[
+                ("a", "1"),
+                ("path", "/tmp/my file"),
+                ("quote", "say \"hi\""),
+                ("empty", ""),
+            ])
+        );
+        assert!(parse_fields("").is_empty());
+    }
+
+    #[test]
+    /// Tests that malformed tokens are collected instead of failing the parse.
+    fn test_parse_fields_lenient() {
+        assert_eq!(
+            parse_fields(r#"ok=1 stray =nokey tail="unterminated quote"#),
+            pairs(&[
+                ("ok", "1"),
+                ("tail", "unterminated quote"),
+                ("unparsed", "stray =nokey"),
+            ])
+        );
+    }
+}
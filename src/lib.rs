@@ -22,7 +22,7 @@
 //!
 //! - **Five semantic log levels**: Trace, Debug, Info, Warn, Error
 //! - **Automatic color detection**: Beautiful colored output with terminal compatibility
-//! - **High-precision timestamps**: Microsecond precision with clean formatting
+//! - **Precise timestamps**: Milliseconds by default, configurable from seconds to nanoseconds
 //! - **Structured logging**: Chain context with `with(key, value)` method
 //! - **Convenient macros**: Easy-to-use macros with variadic key-value pairs
 //! - **Global logger support**: Set and use application-wide logger configuration
@@ -42,53 +42,216 @@
 //! set_global_logger(logger);
 //! ```
 
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::io::Write;
-use std::sync::{Arc, Mutex};
+use std::panic::{AssertUnwindSafe, Location};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU64, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, Utc};
 use once_cell::sync::Lazy;
-use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+use termcolor::{ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+#[cfg(feature = "alloc-counter")]
+mod alloc;
+mod async_sink;
+mod builder;
+mod capture;
+mod color;
+mod config_dsl;
+mod context;
+mod error;
+mod format;
+mod id;
+mod kv;
+mod line_writer;
+mod panic_hook;
+mod rotating_file;
+mod span;
+#[cfg(feature = "syslog")]
+mod syslog_sink;
+mod theme;
+mod trace;
+#[cfg(feature = "clap")]
+mod verbosity;
+
+#[cfg(feature = "alloc-counter")]
+pub use alloc::{AllocStats, CountingAllocator};
+pub use async_sink::OverflowPolicy;
+pub use builder::LoggerBuilder;
+pub use capture::CaptureHandle;
+pub use color::ColorSupport;
+pub use context::{
+    capture_context, push_context, spawn_with_context, ContextGuard, ContextSnapshot,
+};
+pub use error::{CcbError, ConfigError, ParseConfigError, ParseLevelError};
+pub use format::{CompositeFormat, CsvFormat, Format, TimestampMode, TimestampPrecision};
+pub use id::IdGenerator;
+pub use kv::parse_fields;
+pub use line_writer::LogWriter;
+pub use panic_hook::capture_panics;
+pub use span::{Span, SpanId};
+#[cfg(feature = "syslog")]
+pub use syslog::Facility as SyslogFacility;
+pub use termcolor::Color;
+pub use theme::{StyleConfig, Theme};
+pub use trace::{enter_trace_context, TraceContext, TraceContextGuard};
+#[cfg(feature = "clap")]
+pub use verbosity::Verbosity;
 
 /// Represents the severity level of a log message.
 ///
 /// Log levels are ordered by severity, with `Trace` being the lowest and `Error` being the highest.
 /// Each level has a distinct color and four-character representation for consistent alignment.
 ///
+/// Teams whose severity taxonomy doesn't match the five built-ins can define their own
+/// levels with [`Level::Custom`]. Custom levels are ordered against the built-ins by
+/// their numeric [`severity`](Level::severity) and participate in filtering and
+/// formatting like any other level.
+///
 /// # Examples
 ///
 /// ```rust
-/// use ccb::Level;
+/// use ccb::{Color, Level};
 ///
 /// assert!(Level::Trace < Level::Debug);
 /// assert!(Level::Info < Level::Error);
 /// assert_eq!(Level::Info.as_str(), "INFO");
+///
+/// const NOTICE: Level = Level::Custom(25, "NOTE", Color::Magenta);
+/// assert!(Level::Info < NOTICE && NOTICE < Level::Warn);
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy)]
 pub enum Level {
     /// The lowest level, used for fine-grained tracing information.
     /// Displayed as "TRCE" in cyan color.
-    Trace = 0,
+    Trace,
     /// Development and diagnostic information.
     /// Displayed as "DEBG" in blue color.
-    Debug = 1,
+    Debug,
     /// General informational messages about application flow.
     /// Displayed as "INFO" in green color.
-    Info = 2,
+    Info,
     /// Warning messages for potentially harmful situations.
     /// Displayed as "WARN" in yellow color.
-    Warn = 3,
+    Warn,
     /// Error messages for failure conditions.
     /// Displayed as "ERRO" in red color.
-    Error = 4,
+    Error,
+    /// A user-defined level with a numeric severity, a label, and a color.
+    ///
+    /// The severity places the level relative to the built-ins (see
+    /// [`Level::severity`]). Labels should be four characters long to keep
+    /// output aligned.
+    Custom(u8, &'static str, Color),
+    /// A threshold that sorts above every other level, including custom ones.
+    ///
+    /// `with_level(Level::Off)` disables logging entirely. Entries logged at
+    /// `Off` itself are always dropped, so its "OFF" label never appears in
+    /// output.
+    Off,
 }
 
 impl Level {
+    /// The level of entries created with [`Logger::event`].
+    ///
+    /// Its severity of 0 sorts below [`Level::Trace`]. Events bypass the
+    /// level filter and are only delivered to hooks, so hooks can tell them
+    /// apart from log lines by comparing against this level.
+    pub const EVENT: Level = Level::Custom(0, "EVNT", Color::Magenta);
+
+    /// Returns the five built-in levels that entries can be logged at, from
+    /// `Trace` to `Error`.
+    ///
+    /// [`Level::Off`] and custom levels are not included. Each level's
+    /// [`Level::name`] parses back into it, which makes the list suitable for
+    /// help text and for validating a `--log-level` flag.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Level;
+    ///
+    /// let names: Vec<_> = Level::all().iter().map(Level::name).collect();
+    /// assert_eq!(names, ["trace", "debug", "info", "warn", "error"]);
+    /// ```
+    pub fn all() -> &'static [Level] {
+        &[
+            Level::Trace,
+            Level::Debug,
+            Level::Info,
+            Level::Warn,
+            Level::Error,
+        ]
+    }
+
+    /// Iterates over the built-in levels returned by [`Level::all`], in order
+    /// of increasing severity.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Level;
+    ///
+    /// let help = Level::iter().map(|level| level.name()).collect::<Vec<_>>().join("|");
+    /// assert_eq!(help, "trace|debug|info|warn|error");
+    ///
+    /// for level in Level::iter() {
+    ///     assert_eq!(level.name().parse::<Level>(), Ok(level));
+    /// }
+    /// ```
+    pub fn iter() -> impl Iterator<Item = Level> {
+        Level::all().iter().copied()
+    }
+
+    /// Returns the numeric severity used to order levels.
+    ///
+    /// The built-in levels use the following values, leaving room for custom
+    /// levels in between:
+    ///
+    /// | Level | Severity |
+    /// |-------|----------|
+    /// | Trace | 5        |
+    /// | Debug | 10       |
+    /// | Info  | 20       |
+    /// | Warn  | 30       |
+    /// | Error | 40       |
+    /// | Off   | 255      |
+    ///
+    /// `Off` still sorts above a custom level with severity 255.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Color, Level};
+    ///
+    /// assert_eq!(Level::Info.severity(), 20);
+    /// assert_eq!(Level::Custom(25, "NOTE", Color::Magenta).severity(), 25);
+    /// ```
+    pub fn severity(&self) -> u8 {
+        match self {
+            Level::Trace => 5,
+            Level::Debug => 10,
+            Level::Info => 20,
+            Level::Warn => 30,
+            Level::Error => 40,
+            Level::Custom(severity, _, _) => *severity,
+            Level::Off => u8::MAX,
+        }
+    }
+
     /// Returns the four-character string representation of the log level.
     ///
-    /// All levels are formatted to exactly four characters for consistent alignment
-    /// in log output.
+    /// All built-in levels are formatted to exactly four characters for consistent
+    /// alignment in log output. Custom levels return their configured label.
     ///
     /// # Examples
     ///
@@ -108,6 +271,33 @@ impl Level {
             Level::Info => "INFO",
             Level::Warn => "WARN",
             Level::Error => "ERRO",
+            Level::Custom(_, name, _) => name,
+            Level::Off => "OFF",
+        }
+    }
+
+    /// Returns the lowercase name of this level, as used by machine-oriented
+    /// formats such as JSON.
+    ///
+    /// Custom levels return their label unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Level;
+    ///
+    /// assert_eq!(Level::Info.name(), "info");
+    /// assert_eq!(Level::Error.name(), "error");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Trace => "trace",
+            Level::Debug => "debug",
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+            Level::Custom(_, name, _) => name,
+            Level::Off => "off",
         }
     }
 
@@ -119,6 +309,8 @@ impl Level {
     /// - Info: Green
     /// - Warn: Yellow
     /// - Error: Red
+    /// - Custom: The configured color
+    /// - Off: White (never displayed)
     ///
     /// # Examples
     ///
@@ -136,7 +328,92 @@ impl Level {
             Level::Info => Color::Green,
             Level::Warn => Color::Yellow,
             Level::Error => Color::Red,
+            Level::Custom(_, _, color) => *color,
+            Level::Off => Color::White,
+        }
+    }
+
+    /// Reads the level from an environment variable, such as `CCB_LOG=debug`.
+    ///
+    /// The value is parsed as described in the [`FromStr`] implementation.
+    /// When the variable is unset or doesn't name a level, `Info` is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `var_name` - The name of the environment variable to read
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Level, Logger};
+    ///
+    /// let logger = Logger::new().with_level(Level::from_env("CCB_LOG"));
+    /// ```
+    pub fn from_env(var_name: &str) -> Level {
+        std::env::var(var_name)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(Level::Info)
+    }
+}
+
+/// Levels are equal when they have the same severity and label; the color of a
+/// custom level does not affect equality.
+impl PartialEq for Level {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Level {}
+
+impl PartialOrd for Level {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Levels are ordered by severity, with the label breaking ties between
+/// distinct levels of the same severity. [`Level::Off`] sorts above all others.
+impl Ord for Level {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let is_off = |level: &Level| matches!(level, Level::Off);
+        is_off(self)
+            .cmp(&is_off(other))
+            .then_with(|| self.severity().cmp(&other.severity()))
+            .then_with(|| self.as_str().cmp(other.as_str()))
+    }
+}
+
+/// Serializes a level as its lowercase [`Level::name`].
+#[cfg(feature = "serde")]
+impl serde::Serialize for Level {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// Deserializes a built-in level with [`Level::from_str`]; custom levels
+/// can't be deserialized.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Level {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Serializes a source location as `"file:line"`.
+#[cfg(feature = "serde")]
+fn serialize_location<S: serde::Serializer>(
+    location: &Option<&'static Location<'static>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match location {
+        Some(location) => {
+            serializer.serialize_some(&format!("{}:{}", location.file(), location.line()))
         }
+        None => serializer.serialize_none(),
     }
 }
 
@@ -146,27 +423,57 @@ impl fmt::Display for Level {
     }
 }
 
+/// Parses a built-in level from its name or its four-character code, ignoring
+/// case and surrounding whitespace. `warning` is accepted for `Warn`, and
+/// `off` for [`Level::Off`]. Custom levels can't be parsed.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::Level;
+///
+/// assert_eq!("debug".parse::<Level>(), Ok(Level::Debug));
+/// assert_eq!("WARNING".parse::<Level>(), Ok(Level::Warn));
+/// assert_eq!("ERRO".parse::<Level>(), Ok(Level::Error));
+/// assert!("verbose".parse::<Level>().is_err());
+/// ```
+impl FromStr for Level {
+    type Err = ParseLevelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "trace" | "trce" => Ok(Level::Trace),
+            "debug" | "debg" => Ok(Level::Debug),
+            "info" => Ok(Level::Info),
+            "warn" | "warning" => Ok(Level::Warn),
+            "error" | "erro" => Ok(Level::Error),
+            "off" => Ok(Level::Off),
+            _ => Err(ParseLevelError::new(s)),
+        }
+    }
+}
+
 /// Represents a single log entry with all associated metadata.
 ///
 /// A `LogEntry` contains the log level, message, structured fields, and timestamp.
 /// This structure is used internally by the logger to represent a complete log record
 /// before it's formatted and written to the output.
 ///
+/// New metadata may be added in future versions, so entries can't be built
+/// with a struct literal outside this crate. Create one with
+/// [`LogEntry::new`] and set the public fields as needed.
+///
 /// # Examples
 ///
 /// ```rust
 /// use ccb::{LogEntry, Level};
-/// use chrono::Local;
-/// use std::collections::HashMap;
-///
-/// let entry = LogEntry {
-///     level: Level::Info,
-///     message: "User authenticated".to_string(),
-///     fields: HashMap::new(),
-///     timestamp: Local::now(),
-/// };
+///
+/// let mut entry = LogEntry::new(Level::Info, "User authenticated");
+/// entry.fields.insert("user_id".to_string(), "12345".to_string());
 /// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct LogEntry {
     /// The severity level of this log entry.
     pub level: Level,
@@ -176,35 +483,197 @@ pub struct LogEntry {
     pub fields: HashMap<String, String>,
     /// The exact timestamp when this log entry was created.
     pub timestamp: DateTime<Local>,
+    /// The source location that logged this entry, when location capture is enabled.
+    ///
+    /// With the `serde` feature this is serialized as `"file:line"` but, being
+    /// a `'static` reference, comes back as `None` when deserialized.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize_location", skip_deserializing)
+    )]
+    pub location: Option<&'static Location<'static>>,
+    /// The logical subsystem the entry belongs to, such as `db` or `http`,
+    /// when one was given with [`Logger::log_target`] or a macro's `target:`.
+    pub target: Option<String>,
+    /// The trace and span the entry was logged in, from
+    /// [`Logger::with_trace_context`] or [`enter_trace_context`].
+    pub trace: Option<TraceContext>,
+    /// Keys in `fields` whose values were inherited from the logger's context
+    /// rather than passed to the log call. Only populated when origin
+    /// distinction is enabled.
+    pub inherited_keys: HashSet<String>,
+}
+
+impl LogEntry {
+    /// Creates an entry with the given level and message, timestamped now.
+    ///
+    /// The entry has no fields, location, target, or trace context.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The severity level of the entry
+    /// * `message` - The primary log message
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Level, LogEntry};
+    ///
+    /// let entry = LogEntry::new(Level::Warn, "Disk almost full");
+    /// assert_eq!(entry.message, "Disk almost full");
+    /// assert!(entry.fields.is_empty());
+    /// ```
+    pub fn new<M: Into<String>>(level: Level, message: M) -> Self {
+        Self {
+            level,
+            message: message.into(),
+            fields: HashMap::new(),
+            timestamp: Local::now(),
+            location: None,
+            target: None,
+            trace: None,
+            inherited_keys: HashSet::new(),
+        }
+    }
 }
 
 /// Configuration settings for logger behavior and output formatting.
 ///
 /// `Config` allows you to customize various aspects of logging behavior including
-/// the minimum log level, color usage, and timestamp display.
+/// the minimum log level, color usage, timestamp display, and output format.
+/// Settings are added over time, so a config starts from [`Config::default`]
+/// and has its fields assigned rather than being built as a struct literal.
 ///
 /// # Examples
 ///
 /// ```rust
 /// use ccb::{Config, Level};
 ///
-/// let config = Config {
-///     level: Level::Debug,
-///     use_colors: false,  // Disable colors for CI environments
-///     show_timestamp: true,
-/// };
+/// let mut config = Config::default();
+/// config.level = Level::Debug;
+/// config.use_colors = false; // Disable colors for CI environments
+/// config.show_timestamp = true;
 /// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[non_exhaustive]
 pub struct Config {
     /// The minimum log level that will be output.
     /// Messages below this level will be filtered out.
     pub level: Level,
+    /// Per-module exceptions to `level`, as `(module path, level)` pairs.
+    /// For macro calls, the most specific rule matching the calling module
+    /// wins.
+    pub module_levels: Vec<(String, Level)>,
+    /// Levels of which only a fraction is emitted, as `(level, n)` pairs
+    /// keeping one in every `n` entries.
+    pub sampling: Vec<(Level, u64)>,
     /// Whether to use colors in the output.
-    /// Automatically detected based on terminal capabilities by default.
+    /// By default, colors are used when stderr is a terminal, unless
+    /// `$NO_COLOR` is set; `$CLICOLOR_FORCE` forces them on.
     pub use_colors: bool,
+    /// Whether entries below `Warn` go to stdout instead of stderr when no
+    /// writer is set.
+    pub split_streams: bool,
     /// Whether to display timestamps in the output.
     /// When enabled, shows high-precision timestamps in gray.
     pub show_timestamp: bool,
+    /// Per-level exceptions to `show_timestamp`, as `(level, show)` pairs.
+    pub level_timestamps: Vec<(Level, bool)>,
+    /// The chrono format string for timestamps in the pretty and CSV formats.
+    /// An invalid format falls back to the default.
+    pub timestamp_format: String,
+    /// The fractional-second digits of the ISO-8601 timestamps in the JSON
+    /// and logfmt formats, and of the pretty and CSV timestamps while
+    /// `timestamp_format` is the default.
+    pub timestamp_precision: TimestampPrecision,
+    /// Whether pretty timestamps within the same second as the previous one
+    /// are shortened to their sub-second part.
+    pub timestamp_mode: TimestampMode,
+    /// The layout used to render each entry.
+    /// Defaults to the human-readable `Pretty` format.
+    pub format: Format,
+    /// Whether the JSON format nests dotted field keys such as `http.status`
+    /// into objects.
+    pub nested_json: bool,
+    /// Whether the JSON format writes a `_schema` descriptor line before the
+    /// first record of each output.
+    pub json_schema_header: bool,
+    /// How many distinct field keys the JSON format writes as top-level keys,
+    /// or `None` for no limit. Later keys go into a stringified `extra` key.
+    pub json_max_keys: Option<usize>,
+    /// Whether to show the time elapsed since the previous emitted entry.
+    /// Only the `Pretty` format renders the delta.
+    pub inter_entry_timing: bool,
+    /// Whether to record and display the source location of each log call.
+    pub show_location: bool,
+    /// Entries logged before this time are dropped. `None` means no lower bound.
+    pub active_from: Option<DateTime<Local>>,
+    /// Entries logged at or after this time are dropped. `None` means no upper bound.
+    pub active_until: Option<DateTime<Local>>,
+    /// The colors the terminal can display; richer colors are downgraded to fit.
+    pub color_support: ColorSupport,
+    /// The colors of level labels in the pretty format.
+    pub theme: Theme,
+    /// Bold labels and the colors of field keys and messages in the pretty
+    /// format.
+    pub style: StyleConfig,
+    /// Whether inherited context fields are styled differently from per-call fields.
+    pub distinguish_origin: bool,
+    /// Field keys whose values are rendered as terminal hyperlinks.
+    pub hyperlink_keys: Vec<String>,
+    /// Whether fields are rendered sorted by key instead of in arbitrary order.
+    pub sort_fields: bool,
+    /// Whether to append a counter to timestamps shared by several entries.
+    pub timestamp_sequence: bool,
+    /// How long follow-on errors are suppressed after an error, if enabled.
+    pub error_cascade_window: Option<Duration>,
+    /// Whether warnings count as errors for cascade suppression.
+    pub cascade_includes_warnings: bool,
+    /// How many identical entries may be emitted per interval, as
+    /// `(max, interval)`, if rate limiting is enabled.
+    pub rate_limit: Option<(usize, Duration)>,
+    /// How often buffered output is written even when no new entries
+    /// arrive, or `None` to wait for the buffer to fill.
+    pub flush_interval: Option<Duration>,
+    /// How many recent below-threshold entries are held back and written
+    /// when an error is logged. `0` disables the buffer.
+    pub error_context_buffer: usize,
+    /// Whether overwriting an existing context key logs a warning.
+    pub strict_context: bool,
+    /// The field key that tags status entries, which overwrite the previous
+    /// entry with the same tag on a terminal instead of scrolling.
+    pub status_key: Option<String>,
+    /// The string written after each entry. Defaults to a newline.
+    pub record_delimiter: String,
+    /// The number of fields each entry's field map is pre-sized for.
+    pub field_capacity: usize,
+    /// Produces ids wherever the logger needs one, such as for spans.
+    /// Not serialized; deserialized configs use the default generator.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub id_generator: IdGenerator,
+    /// The set of permitted field keys, or `None` to allow any key.
+    pub allowed_keys: Option<HashSet<String>>,
+    /// How fields with keys outside `allowed_keys` are handled.
+    pub unknown_key_policy: KeyPolicy,
+    /// Lowercase key patterns whose field values are written as `***`. A
+    /// leading or trailing `*` matches any prefix or suffix.
+    pub redacted_keys: Vec<String>,
+    /// Whether timestamps are displayed in UTC instead of local time.
+    pub utc: bool,
+    /// The named timezone timestamps are displayed in, instead of local time.
+    /// Requires the `chrono-tz` feature.
+    #[cfg(feature = "chrono-tz")]
+    pub timezone: Option<chrono_tz::Tz>,
+    /// Whether each emitted entry increments the `log.events` counter.
+    /// Requires the `metrics` feature.
+    #[cfg(feature = "metrics")]
+    pub count_metrics: bool,
+    /// Whether macro-logged entries carry their call site and invocation text.
+    /// Requires the `explain` feature.
+    #[cfg(feature = "explain")]
+    pub explain: bool,
 }
 
 impl Default for Config {
@@ -212,8 +681,44 @@ impl Default for Config {
     ///
     /// Default settings:
     /// - Level: `Info` (filters out Debug and Trace)
-    /// - Colors: Auto-detected based on terminal capabilities
-    /// - Timestamp: Enabled
+    /// - Module levels: None
+    /// - Sampling: None (every entry is kept)
+    /// - Colors: On when stderr is a terminal; `$NO_COLOR` turns them off and
+    ///   `$CLICOLOR_FORCE` on
+    /// - Split streams: Disabled (everything goes to stderr)
+    /// - Color support: Detected from `$COLORTERM` and `$TERM`
+    /// - Theme: Each level's own color
+    /// - Style: Bold level labels, gray field keys, plain messages
+    /// - Timestamp: Enabled, for every level
+    /// - Timestamp format: `%Y-%m-%d %H:%M:%S%.3f` (milliseconds)
+    /// - Timestamp precision: `Millis`
+    /// - Timestamp mode: `Full`
+    /// - Format: `Pretty`
+    /// - Nested JSON: Disabled
+    /// - JSON schema header: Disabled
+    /// - JSON key limit: None
+    /// - Inter-entry timing: Disabled
+    /// - Location: Disabled
+    /// - Active window: Unbounded
+    /// - Origin distinction: Disabled
+    /// - Hyperlink keys: None
+    /// - Field sorting: Disabled
+    /// - Timestamp sequence: Disabled
+    /// - Error cascade suppression: Disabled
+    /// - Rate limit: Disabled
+    /// - Flush interval: None (buffered output waits for the buffer to fill)
+    /// - Error context buffer: Disabled
+    /// - Timezone: Local (with the `chrono-tz` feature)
+    /// - Strict context: Disabled (later `with` values silently win)
+    /// - Status lines: Disabled
+    /// - Record delimiter: Newline
+    /// - Field capacity: 0 (sized to the fields present)
+    /// - Id generator: Random base62 ids
+    /// - Allowed keys: Any, with the `Warn` policy
+    /// - Redacted keys: None
+    /// - UTC timestamps: Disabled (local time)
+    /// - Metrics counting: Disabled (with the `metrics` feature)
+    /// - Explain mode: Disabled (with the `explain` feature)
     ///
     /// # Examples
     ///
@@ -227,12 +732,236 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             level: Level::Info,
-            use_colors: atty::is(atty::Stream::Stderr),
+            module_levels: Vec::new(),
+            sampling: Vec::new(),
+            use_colors: color::default_use_colors(),
+            split_streams: false,
             show_timestamp: true,
+            level_timestamps: Vec::new(),
+            timestamp_format: format::TIMESTAMP_FORMAT.to_string(),
+            timestamp_precision: TimestampPrecision::Millis,
+            timestamp_mode: TimestampMode::Full,
+            format: Format::Pretty,
+            nested_json: false,
+            json_schema_header: false,
+            json_max_keys: None,
+            inter_entry_timing: false,
+            show_location: false,
+            active_from: None,
+            active_until: None,
+            color_support: ColorSupport::detect(),
+            theme: Theme::default(),
+            style: StyleConfig::default(),
+            distinguish_origin: false,
+            hyperlink_keys: Vec::new(),
+            sort_fields: false,
+            timestamp_sequence: false,
+            error_cascade_window: None,
+            rate_limit: None,
+            flush_interval: None,
+            cascade_includes_warnings: false,
+            error_context_buffer: 0,
+            strict_context: false,
+            status_key: None,
+            record_delimiter: "\n".to_string(),
+            field_capacity: 0,
+            id_generator: IdGenerator::default(),
+            allowed_keys: None,
+            unknown_key_policy: KeyPolicy::Warn,
+            redacted_keys: Vec::new(),
+            utc: false,
+            #[cfg(feature = "chrono-tz")]
+            timezone: None,
+            #[cfg(feature = "metrics")]
+            count_metrics: false,
+            #[cfg(feature = "explain")]
+            explain: false,
         }
     }
 }
 
+impl Config {
+    /// Returns a readable summary of every setting, one per line.
+    ///
+    /// This is meant for diagnostics, such as a `--dump-log-config` flag whose
+    /// output users can paste into a bug report.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Config, Level};
+    ///
+    /// let mut config = Config::default();
+    /// config.level = Level::Debug;
+    /// assert!(config.describe().starts_with("level: DEBG\n"));
+    /// ```
+    pub fn describe(&self) -> String {
+        let on_off = |enabled: bool| if enabled { "on" } else { "off" }.to_string();
+        let list = |items: Vec<String>| {
+            if items.is_empty() {
+                "none".to_string()
+            } else {
+                items.join(", ")
+            }
+        };
+        let bound = |time: Option<DateTime<Local>>| {
+            time.map_or("unbounded".to_string(), |t| t.to_rfc3339())
+        };
+
+        let mut level_timestamps: Vec<_> = self
+            .level_timestamps
+            .iter()
+            .map(|(level, show)| format!("{}={}", level.as_str(), on_off(*show)))
+            .collect();
+        level_timestamps.sort();
+        let module_levels: Vec<_> = self
+            .module_levels
+            .iter()
+            .map(|(module, level)| format!("{}={}", module, level.as_str()))
+            .collect();
+        let sampling: Vec<_> = self
+            .sampling
+            .iter()
+            .map(|(level, n)| format!("{}=1/{}", level.as_str(), n))
+            .collect();
+        let mut allowed_keys: Vec<_> = self.allowed_keys.iter().flatten().cloned().collect();
+        allowed_keys.sort();
+
+        #[allow(unused_mut)]
+        let mut settings = vec![
+            ("level", self.level.as_str().to_string()),
+            ("module levels", list(module_levels)),
+            ("sampling", list(sampling)),
+            ("format", self.format.describe()),
+            ("nested json", on_off(self.nested_json)),
+            ("json schema header", on_off(self.json_schema_header)),
+            (
+                "json max keys",
+                self.json_max_keys
+                    .map_or("unlimited".to_string(), |max| max.to_string()),
+            ),
+            ("colors", on_off(self.use_colors)),
+            ("split streams", on_off(self.split_streams)),
+            ("color support", format!("{:?}", self.color_support)),
+            (
+                "theme",
+                if self.theme == Theme::default() {
+                    "default"
+                } else if self.theme == Theme::monochrome() {
+                    "monochrome"
+                } else {
+                    "custom"
+                }
+                .to_string(),
+            ),
+            (
+                "style",
+                if self.style == StyleConfig::default() {
+                    "default"
+                } else {
+                    "custom"
+                }
+                .to_string(),
+            ),
+            ("timestamp", on_off(self.show_timestamp)),
+            ("level timestamps", list(level_timestamps)),
+            ("timestamp format", self.timestamp_format.clone()),
+            (
+                "timestamp precision",
+                format!("{:?}", self.timestamp_precision),
+            ),
+            ("timestamp mode", format!("{:?}", self.timestamp_mode)),
+            ("timestamp sequence", on_off(self.timestamp_sequence)),
+            ("inter-entry timing", on_off(self.inter_entry_timing)),
+            ("location", on_off(self.show_location)),
+            ("active from", bound(self.active_from)),
+            ("active until", bound(self.active_until)),
+            ("distinguish origin", on_off(self.distinguish_origin)),
+            ("hyperlink keys", list(self.hyperlink_keys.clone())),
+            ("sorted fields", on_off(self.sort_fields)),
+            (
+                "error cascade window",
+                self.error_cascade_window
+                    .map_or("off".to_string(), |window| format!("{:?}", window)),
+            ),
+            (
+                "cascade includes warnings",
+                on_off(self.cascade_includes_warnings),
+            ),
+            (
+                "rate limit",
+                self.rate_limit
+                    .map_or("off".to_string(), |(max, interval)| {
+                        format!("{} per {:?}", max, interval)
+                    }),
+            ),
+            (
+                "flush interval",
+                self.flush_interval
+                    .map_or("off".to_string(), |interval| format!("{:?}", interval)),
+            ),
+            ("strict context", on_off(self.strict_context)),
+            (
+                "status key",
+                self.status_key
+                    .clone()
+                    .unwrap_or_else(|| "none".to_string()),
+            ),
+            (
+                "error context buffer",
+                self.error_context_buffer.to_string(),
+            ),
+            ("record delimiter", format!("{:?}", self.record_delimiter)),
+            ("field capacity", self.field_capacity.to_string()),
+            (
+                "allowed keys",
+                if self.allowed_keys.is_some() {
+                    list(allowed_keys)
+                } else {
+                    "any".to_string()
+                },
+            ),
+            (
+                "unknown key policy",
+                format!("{:?}", self.unknown_key_policy),
+            ),
+            ("redacted keys", list(self.redacted_keys.clone())),
+            ("utc", on_off(self.utc)),
+        ];
+        #[cfg(feature = "chrono-tz")]
+        settings.push((
+            "timezone",
+            self.timezone
+                .map_or("local".to_string(), |tz| tz.name().to_string()),
+        ));
+        #[cfg(feature = "metrics")]
+        settings.push(("metrics", on_off(self.count_metrics)));
+        #[cfg(feature = "explain")]
+        settings.push(("explain", on_off(self.explain)));
+
+        settings
+            .into_iter()
+            .map(|(name, value)| format!("{}: {}\n", name, value))
+            .collect()
+    }
+}
+
+/// What to do with fields whose keys are not in the logger's allowlist.
+///
+/// See [`Logger::with_allowed_keys`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum KeyPolicy {
+    /// Keep the field, and log a warning the first time each unknown key is seen.
+    #[default]
+    Warn,
+    /// Silently remove the field from the entry.
+    Drop,
+    /// Reject the whole entry and log an error naming the unknown keys instead.
+    Error,
+}
+
 /// A structured logger with configurable output formatting and context management.
 ///
 /// `Logger` is the core component that handles log formatting, filtering, and output.
@@ -257,6 +986,223 @@ pub struct Logger {
     config: Config,
     /// Persistent context key-value pairs applied to all log entries.
     context: HashMap<String, String>,
+    /// Mutable state shared between clones of this logger.
+    state: Arc<LoggerState>,
+    /// Where entries are written; stderr when `None`.
+    writer: Option<SharedWriter>,
+    /// The rotating file behind `writer`, kept to reopen and rotate it.
+    rotating_file: Option<Arc<Mutex<rotating_file::RotatingFile>>>,
+    /// Whether colors were chosen with `with_colors` rather than detected.
+    colors_explicit: bool,
+    /// Rendered entries waiting to be written, when buffering is enabled.
+    buffer: Option<Arc<OutputBuffer>>,
+    /// Files that receive a plain copy of every entry.
+    tees: Vec<Tee>,
+    /// Callbacks run for every emitted entry, in registration order.
+    hooks: Vec<EntryHook>,
+    /// The trace and span ids attached to every entry, kept apart from
+    /// `context`.
+    trace_context: Option<TraceContext>,
+    /// The queue entries are handed to when writing happens in the background.
+    async_sink: Option<Arc<async_sink::AsyncSink>>,
+    /// Entries seen per sampled level, in the order of `config.sampling`.
+    /// Shared between clones so they sample as one logger.
+    sample_counters: Arc<[AtomicU64]>,
+    /// The syslog connection entries are sent to instead of the output.
+    #[cfg(feature = "syslog")]
+    syslog: Option<syslog_sink::SyslogSink>,
+}
+
+/// An output sink shared between clones of a logger.
+#[derive(Clone)]
+struct SharedWriter(Arc<Mutex<dyn Write + Send>>);
+
+impl fmt::Debug for SharedWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SharedWriter")
+    }
+}
+
+/// A callback run for every emitted entry, added with [`Logger::with_hook`].
+#[derive(Clone)]
+struct EntryHook(Arc<dyn Fn(&LogEntry) + Send + Sync>);
+
+impl fmt::Debug for EntryHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EntryHook")
+    }
+}
+
+/// A file receiving a plain copy of the output, added with [`Logger::with_tee`].
+#[derive(Debug, Clone)]
+struct Tee {
+    /// The path the file was opened from.
+    path: PathBuf,
+    /// The open file, replaced in place when it is reopened.
+    writer: Arc<Mutex<std::fs::File>>,
+    /// The format of the copy, or `None` to follow the logger's format.
+    format: Option<Format>,
+    /// Set once the JSON schema header has been written to the file.
+    schema_written: Arc<AtomicBool>,
+    /// Whether the file is a terminal that gets colored copies.
+    use_colors: bool,
+}
+
+/// Rendered entries held back by [`Logger::with_buffering`].
+///
+/// Shared between clones of a logger. Whatever is still pending when the last
+/// clone is dropped gets written then.
+#[derive(Debug)]
+struct OutputBuffer {
+    /// Number of pending bytes that triggers a write.
+    capacity: usize,
+    /// Rendered bytes not yet written.
+    pending: Mutex<Vec<u8>>,
+    /// Where pending bytes are written; stderr when `None`.
+    writer: Option<SharedWriter>,
+}
+
+impl OutputBuffer {
+    /// Creates a buffer to share between clones, with a thread that writes
+    /// out pending bytes every `flush_interval`, if given.
+    fn shared(
+        capacity: usize,
+        writer: Option<SharedWriter>,
+        flush_interval: Option<Duration>,
+    ) -> Arc<Self> {
+        let buffer = Arc::new(Self {
+            capacity,
+            pending: Mutex::new(Vec::with_capacity(capacity)),
+            writer,
+        });
+        if let Some(interval) = flush_interval {
+            // The thread only holds a weak reference, so it never keeps the
+            // buffer alive and exits once the buffer is gone
+            let weak = Arc::downgrade(&buffer);
+            let _ = std::thread::Builder::new()
+                .name("ccb-flush".to_string())
+                .spawn(move || loop {
+                    std::thread::sleep(interval);
+                    let Some(buffer) = weak.upgrade() else {
+                        return;
+                    };
+                    if let Err(err) = buffer.flush() {
+                        report_error(&CcbError::Io(err));
+                    }
+                });
+        }
+        buffer
+    }
+
+    /// Appends a rendered entry, writing everything out once the capacity is
+    /// reached.
+    fn write(&self, bytes: &[u8]) -> std::io::Result<()> {
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        pending.extend_from_slice(bytes);
+        if pending.len() >= self.capacity {
+            self.write_out(&mut pending)?;
+        }
+        Ok(())
+    }
+
+    /// Writes out all pending bytes.
+    fn flush(&self) -> std::io::Result<()> {
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        self.write_out(&mut pending)
+    }
+
+    /// Writes `pending` to the output and clears it, even if writing fails.
+    fn write_out(&self, pending: &mut Vec<u8>) -> std::io::Result<()> {
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let result = match &self.writer {
+            Some(SharedWriter(writer)) => {
+                let mut writer = writer.lock().unwrap_or_else(PoisonError::into_inner);
+                writer.write_all(pending).and_then(|()| writer.flush())
+            }
+            None => {
+                let mut stderr = std::io::stderr().lock();
+                stderr.write_all(pending).and_then(|()| stderr.flush())
+            }
+        };
+        pending.clear();
+        result
+    }
+}
+
+impl Drop for OutputBuffer {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            report_error(&CcbError::Io(err));
+        }
+    }
+}
+
+/// Mutable logger state shared between clones.
+#[derive(Debug)]
+struct LoggerState {
+    /// When the previous entry was emitted.
+    last_emit: Mutex<Option<Instant>>,
+    /// The last emitted microsecond timestamp and its sequence number.
+    last_sequence: Mutex<(i64, u32)>,
+    /// The second of the last timestamp shown, for compact timestamps.
+    last_second: Mutex<Option<i64>>,
+    /// Progress of the current error cascade, if suppression is enabled.
+    cascade: Mutex<CascadeState>,
+    /// Open rate limit windows, keyed by a hash of the level and message.
+    rate_windows: Mutex<HashMap<u64, RateWindow>>,
+    /// Unknown field keys that have already produced a warning.
+    warned_keys: Mutex<HashSet<String>>,
+    /// The tag of the status line currently at the bottom of the terminal.
+    status_tag: Mutex<Option<String>>,
+    /// Recent below-threshold entries, written when an error is logged.
+    context_buffer: Mutex<VecDeque<LogEntry>>,
+    /// Set once the JSON schema header has been written to the output.
+    schema_written: AtomicBool,
+    /// The field keys admitted as top-level JSON keys under the key limit.
+    json_keys: Mutex<HashSet<String>>,
+}
+
+impl Default for LoggerState {
+    fn default() -> Self {
+        Self {
+            last_emit: Mutex::new(None),
+            last_sequence: Mutex::new((i64::MIN, 0)),
+            last_second: Mutex::new(None),
+            cascade: Mutex::new(CascadeState::default()),
+            rate_windows: Mutex::new(HashMap::new()),
+            warned_keys: Mutex::new(HashSet::new()),
+            status_tag: Mutex::new(None),
+            context_buffer: Mutex::new(VecDeque::new()),
+            schema_written: AtomicBool::new(false),
+            json_keys: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+/// Tracks an error cascade for suppression.
+#[derive(Debug, Default)]
+struct CascadeState {
+    /// When the error that opened the current window was emitted.
+    window_start: Option<Instant>,
+    /// How many errors have been suppressed in the current window.
+    suppressed: usize,
+}
+
+/// Tracks repeats of one message for rate limiting.
+#[derive(Debug)]
+struct RateWindow {
+    /// When the first entry of the window was emitted.
+    start: Instant,
+    /// The level of the repeated entries.
+    level: Level,
+    /// The repeated message.
+    message: String,
+    /// How many entries have been emitted in the window.
+    emitted: usize,
+    /// How many entries have been suppressed in the window.
+    suppressed: usize,
 }
 
 impl Logger {
@@ -274,10 +1220,7 @@ impl Logger {
     /// logger.info("Application started", &[]);
     /// ```
     pub fn new() -> Self {
-        Self {
-            config: Config::default(),
-            context: HashMap::new(),
-        }
+        Self::with_config(Config::default())
     }
 
     /// Creates a logger with a custom configuration.
@@ -294,20 +1237,62 @@ impl Logger {
     /// ```rust
     /// use ccb::{Logger, Config, Level};
     ///
-    /// let config = Config {
-    ///     level: Level::Debug,
-    ///     use_colors: false,
-    ///     show_timestamp: true,
-    /// };
+    /// let mut config = Config::default();
+    /// config.level = Level::Debug;
+    /// config.use_colors = false;
+    /// config.show_timestamp = true;
     /// let logger = Logger::with_config(config);
     /// ```
     pub fn with_config(config: Config) -> Self {
+        let sample_counters = sample_counters(&config);
         Self {
             config,
             context: HashMap::new(),
+            state: Arc::new(LoggerState::default()),
+            writer: None,
+            rotating_file: None,
+            colors_explicit: false,
+            buffer: None,
+            tees: Vec::new(),
+            hooks: Vec::new(),
+            trace_context: None,
+            async_sink: None,
+            sample_counters,
+            #[cfg(feature = "syslog")]
+            syslog: None,
         }
     }
 
+    /// Creates a logger preset for CI environments.
+    ///
+    /// CI systems usually timestamp each line themselves and render color codes
+    /// poorly, so this preset is equivalent to:
+    ///
+    /// ```rust
+    /// # use ccb::{Format, Logger};
+    /// Logger::new()
+    ///     .with_colors(false)
+    ///     .with_timestamp(false)
+    ///     .with_format(Format::Pretty);
+    /// ```
+    ///
+    /// The level label is still shown, and every other setting keeps its default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::ci_preset();
+    /// logger.info("Build started", &[("job", "test")]); // "INFO Build started job=test"
+    /// ```
+    pub fn ci_preset() -> Self {
+        Self::new()
+            .with_colors(false)
+            .with_timestamp(false)
+            .with_format(Format::Pretty)
+    }
+
     /// Sets the minimum log level for this logger.
     ///
     /// Messages with a level below this threshold will be filtered out
@@ -334,14 +1319,19 @@ impl Logger {
         self
     }
 
-    /// Enables or disables colored output.
+    /// Sets the minimum level for log macro calls made from a module.
     ///
-    /// When colors are enabled, log levels are displayed with their associated
-    /// colors and bold formatting. This setting overrides automatic terminal detection.
+    /// Like `RUST_LOG=my_app::db=debug`, this makes one module more or less
+    /// verbose than the rest. A rule applies to the module itself and its
+    /// submodules; when several match, the longest path wins. Modules without
+    /// a matching rule use [`Logger::with_level`]. Only the logging macros
+    /// know their calling module, so rules don't affect direct method calls
+    /// such as [`Logger::log`].
     ///
     /// # Arguments
     ///
-    /// * `use_colors` - Whether to use colored output
+    /// * `module` - A module path such as `my_app::db`
+    /// * `level` - The minimum level for that module
     ///
     /// # Returns
     ///
@@ -350,23 +1340,36 @@ impl Logger {
     /// # Examples
     ///
     /// ```rust
-    /// use ccb::Logger;
+    /// use ccb::{Level, Logger};
     ///
-    /// let logger = Logger::new().with_colors(false); // Force disable colors
+    /// let logger = Logger::new()
+    ///     .with_level(Level::Warn)
+    ///     .with_module_level("my_app::db", Level::Debug)
+    ///     .with_module_level("my_app::db::pool", Level::Error);
     /// ```
-    pub fn with_colors(mut self, use_colors: bool) -> Self {
-        self.config.use_colors = use_colors;
+    pub fn with_module_level<M: Into<String>>(mut self, module: M, level: Level) -> Self {
+        let module = module.into();
+        self.config.module_levels.retain(|(m, _)| *m != module);
+        self.config.module_levels.push((module, level));
         self
     }
 
-    /// Enables or disables timestamp display in log output.
+    /// Emits only one in every `n` entries at `level`, to cut the volume of
+    /// chatty levels under load.
     ///
-    /// When enabled, each log entry is prefixed with a high-precision timestamp
-    /// in the format `2009-11-10 15:00:00.1234` displayed in gray.
+    /// The first entry is kept, then every `n`th after it. Only entries at
+    /// exactly `level` that pass the level filter are counted; other levels
+    /// are unaffected, so warnings and errors always pass when `Debug` is
+    /// sampled. An `n` of 0 or 1 keeps every entry. Clones of the logger
+    /// share the count.
+    ///
+    /// Sampling is best-effort under concurrency: the counter is atomic, but
+    /// which thread's entry lands on the boundary is up to scheduling.
     ///
     /// # Arguments
     ///
-    /// * `show_timestamp` - Whether to display timestamps
+    /// * `level` - The level to sample
+    /// * `n` - Keep one entry in every `n`
     ///
     /// # Returns
     ///
@@ -375,25 +1378,39 @@ impl Logger {
     /// # Examples
     ///
     /// ```rust
-    /// use ccb::Logger;
+    /// use ccb::{Level, Logger};
     ///
-    /// let logger = Logger::new().with_timestamp(false); // Hide timestamps
+    /// let (logger, capture) = Logger::capturing();
+    /// let logger = logger
+    ///     .with_timestamp(false)
+    ///     .with_level(Level::Debug)
+    ///     .with_sampling(Level::Debug, 100);
+    /// for i in 0..250 {
+    ///     logger.debug("Polled", &[("i", &i.to_string())]);
+    /// }
+    /// logger.warn("Slow poll", &[]);
+    ///
+    /// assert_eq!(
+    ///     capture.lines(),
+    ///     ["DEBG Polled i=0", "DEBG Polled i=100", "DEBG Polled i=200", "WARN Slow poll"]
+    /// );
     /// ```
-    pub fn with_timestamp(mut self, show_timestamp: bool) -> Self {
-        self.config.show_timestamp = show_timestamp;
+    pub fn with_sampling(mut self, level: Level, n: u64) -> Self {
+        self.config.sampling.retain(|(l, _)| *l != level);
+        self.config.sampling.push((level, n));
+        self.sample_counters = sample_counters(&self.config);
         self
     }
 
-    /// Adds a context key-value pair that will be included in all log entries.
+    /// Enables or disables colored output.
     ///
-    /// Context is persistent and gets applied to every log message from this logger.
-    /// This is useful for adding service names, versions, request IDs, or other
-    /// metadata that should appear in all logs.
+    /// When colors are enabled, log levels are displayed with their associated
+    /// colors and bold formatting. This setting overrides automatic terminal detection
+    /// as well as the `NO_COLOR` and `CLICOLOR_FORCE` environment variables.
     ///
     /// # Arguments
     ///
-    /// * `key` - The context key (converted to String)
-    /// * `value` - The context value (converted to String)
+    /// * `use_colors` - Whether to use colored output
     ///
     /// # Returns
     ///
@@ -404,632 +1421,6546 @@ impl Logger {
     /// ```rust
     /// use ccb::Logger;
     ///
-    /// let logger = Logger::new()
-    ///     .with("service", "auth")
-    ///     .with("version", "1.2.0")
-    ///     .with("request_id", "req-123");
-    ///
-    /// logger.info("Processing request", &[]); // Will include all context fields
+    /// let logger = Logger::new().with_colors(false); // Force disable colors
     /// ```
-    pub fn with<K, V>(mut self, key: K, value: V) -> Self
-    where
-        K: Into<String>,
-        V: Into<String>,
-    {
-        self.context.insert(key.into(), value.into());
+    pub fn with_colors(mut self, use_colors: bool) -> Self {
+        self.config.use_colors = use_colors;
+        self.colors_explicit = true;
         self
     }
 
-    /// Logs a message at the specified level with additional structured fields.
+    /// Adds a callback that is run for every entry this logger emits.
     ///
-    /// This is the core logging method used by all level-specific methods.
-    /// It combines the logger's persistent context with the provided fields
-    /// and outputs the message if it meets the minimum level threshold.
+    /// Hooks see each entry that passes the level filter, sampling, and rate
+    /// limiting, just before it is formatted and written, with redacted
+    /// values already hidden. This is a way to forward entries, for example to
+    /// count errors in a metrics system, without replacing the output. Hooks
+    /// run on the logging thread in the order they were added; one that
+    /// panics is reported to the [error handler](set_error_handler) and the
+    /// entry is still written.
     ///
     /// # Arguments
     ///
-    /// * `level` - The severity level for this log entry
-    /// * `message` - The primary log message
-    /// * `fields` - Additional key-value pairs for this specific log entry
+    /// * `hook` - The callback to run for each entry
     ///
-    /// # Examples
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
     ///
     /// ```rust
-    /// use ccb::{Logger, Level};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    /// use ccb::{Level, LogEntry, Logger};
     ///
-    /// let logger = Logger::new();
-    /// logger.log(Level::Info, "User authenticated", &[("user_id", "12345")]);
+    /// let errors = Arc::new(AtomicUsize::new(0));
+    /// let counter = errors.clone();
+    /// let logger = Logger::new().with_hook(Arc::new(move |entry: &LogEntry| {
+    ///     if entry.level >= Level::Error {
+    ///         counter.fetch_add(1, Ordering::Relaxed);
+    ///     }
+    /// }));
+    ///
+    /// logger.error("Upload failed", &[]);
+    /// logger.info("Retrying", &[]);
+    /// assert_eq!(errors.load(Ordering::Relaxed), 1);
     /// ```
-    pub fn log(&self, level: Level, message: &str, fields: &[(&str, &str)]) {
-        if level < self.config.level {
-            return;
-        }
-
-        let mut entry_fields = self.context.clone();
-        for (key, value) in fields {
-            entry_fields.insert(key.to_string(), value.to_string());
-        }
-
-        let entry = LogEntry {
-            level,
-            message: message.to_string(),
-            fields: entry_fields,
-            timestamp: Local::now(),
-        };
-
-        self.write_entry(&entry);
+    pub fn with_hook(mut self, hook: Arc<dyn Fn(&LogEntry) + Send + Sync>) -> Self {
+        self.hooks.push(EntryHook(hook));
+        self
     }
 
-    /// Logs a message at trace level.
+    /// Sends entries below `Warn` to stdout and the rest to stderr.
     ///
-    /// Trace messages are intended for fine-grained diagnostic information,
-    /// typically used for debugging complex flows or performance analysis.
+    /// This suits shell pipelines, where stdout carries a program's regular
+    /// output and stderr its warnings and errors. Colors are decided for each
+    /// stream separately: unless set with [`Logger::with_colors`], stdout is
+    /// colored when it is a terminal, independently of stderr, following the
+    /// same `NO_COLOR` and `CLICOLOR_FORCE` rules. Has no effect while a
+    /// writer, buffering, or syslog is in use.
     ///
     /// # Arguments
     ///
-    /// * `message` - The log message
-    /// * `fields` - Additional key-value pairs
+    /// * `enabled` - Whether to split output between stdout and stderr
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
     ///
     /// # Examples
     ///
     /// ```rust
     /// use ccb::Logger;
     ///
-    /// let logger = Logger::new();
-    /// logger.trace("Entering function", &[("function", "calculate_hash")]);
+    /// let logger = Logger::new().with_split_streams(true);
+    /// logger.info("Processed 12 files", &[]); // stdout
+    /// logger.warn("Skipped a symlink", &[]); // stderr
     /// ```
-    pub fn trace(&self, message: &str, fields: &[(&str, &str)]) {
-        self.log(Level::Trace, message, fields);
+    pub fn with_split_streams(mut self, enabled: bool) -> Self {
+        self.config.split_streams = enabled;
+        self
     }
 
-    /// Logs a message at debug level.
+    /// Writes entries to the given writer instead of stderr.
     ///
-    /// Debug messages provide detailed information for development and
-    /// troubleshooting purposes.
+    /// This redirects output to a file, a pipe, or an in-memory buffer for
+    /// assertions in tests. The writer is shared by clones of the logger, and
+    /// each entry is written to it with a single `write_all` followed by a
+    /// flush.
+    ///
+    /// Since the writer usually isn't a terminal, colors are disabled unless
+    /// they were explicitly enabled with [`Logger::with_colors`]. Unlike
+    /// stderr, stdout, and tees, an arbitrary writer has no descriptor to
+    /// probe, so this is the color decision for this sink.
     ///
     /// # Arguments
     ///
-    /// * `message` - The log message
-    /// * `fields` - Additional key-value pairs
+    /// * `writer` - The destination for rendered entries
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
     ///
     /// # Examples
     ///
     /// ```rust
+    /// use std::sync::{Arc, Mutex};
     /// use ccb::Logger;
     ///
-    /// let logger = Logger::new();
-    /// logger.debug("Cache miss", &[("key", "user:12345")]);
+    /// let output = Arc::new(Mutex::new(Vec::new()));
+    /// let logger = Logger::new().with_timestamp(false).with_writer(output.clone());
+    /// logger.info("Ready", &[]);
+    ///
+    /// assert_eq!(&*output.lock().unwrap(), b"INFO Ready\n");
     /// ```
-    pub fn debug(&self, message: &str, fields: &[(&str, &str)]) {
-        self.log(Level::Debug, message, fields);
+    pub fn with_writer(mut self, writer: Arc<Mutex<dyn Write + Send>>) -> Self {
+        self.writer = Some(SharedWriter(writer));
+        self.rotating_file = None;
+        if !self.colors_explicit {
+            self.config.use_colors = false;
+        }
+        if let Some(buffer) = &self.buffer {
+            self.buffer = Some(OutputBuffer::shared(
+                buffer.capacity,
+                self.writer.clone(),
+                self.config.flush_interval,
+            ));
+        }
+        self
     }
 
-    /// Logs a message at info level.
+    /// Batches output, writing entries once `capacity` bytes are pending.
     ///
-    /// Info messages communicate general information about application
-    /// flow and important events.
+    /// Each unbuffered entry costs at least one write system call, which
+    /// dominates the runtime of high-throughput trace logging. With buffering,
+    /// rendered entries accumulate in memory and are written together. Pending
+    /// entries are written by [`Logger::flush`], and when the last clone of the
+    /// logger is dropped. The global logger is never dropped, so flush it
+    /// before exiting. A capacity of 0 disables buffering.
     ///
     /// # Arguments
     ///
-    /// * `message` - The log message
-    /// * `fields` - Additional key-value pairs
+    /// * `capacity` - Number of pending bytes that triggers a write
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
     ///
     /// # Examples
     ///
     /// ```rust
     /// use ccb::Logger;
     ///
-    /// let logger = Logger::new();
-    /// logger.info("Server started", &[("port", "8080")]);
+    /// let logger = Logger::new().with_buffering(64 * 1024);
+    /// for i in 0..1000 {
+    ///     logger.trace("Tick", &[("i", &i.to_string())]);
+    /// }
+    /// logger.flush();
     /// ```
-    pub fn info(&self, message: &str, fields: &[(&str, &str)]) {
-        self.log(Level::Info, message, fields);
+    pub fn with_buffering(mut self, capacity: usize) -> Self {
+        self.buffer = (capacity > 0).then(|| {
+            OutputBuffer::shared(capacity, self.writer.clone(), self.config.flush_interval)
+        });
+        self
     }
 
-    /// Logs a message at warn level.
+    /// Writes buffered output at least every `interval`, even while no new
+    /// entries arrive.
     ///
-    /// Warn messages indicate potentially harmful situations that
-    /// don't prevent the application from continuing.
+    /// Without it, a quiet process can hold entries in the
+    /// [buffer](Logger::with_buffering) indefinitely, so a reader tailing the
+    /// output sees nothing. A background thread writes whatever is pending
+    /// once per interval, keeping live tailing responsive while bursts are
+    /// still batched. The thread stops within an interval after the last
+    /// clone of the logger is dropped. Has no effect unless buffering is
+    /// enabled, before or after this call.
     ///
     /// # Arguments
     ///
-    /// * `message` - The log message
-    /// * `fields` - Additional key-value pairs
+    /// * `interval` - How often pending output is written
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
     ///
     /// # Examples
     ///
     /// ```rust
+    /// use std::time::Duration;
     /// use ccb::Logger;
     ///
-    /// let logger = Logger::new();
-    /// logger.warn("High memory usage", &[("usage", "85%")]);
+    /// let logger = Logger::new()
+    ///     .with_buffering(64 * 1024)
+    ///     .with_flush_interval(Duration::from_secs(1));
+    /// logger.info("Visible within a second", &[]);
     /// ```
-    pub fn warn(&self, message: &str, fields: &[(&str, &str)]) {
-        self.log(Level::Warn, message, fields);
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.config.flush_interval = Some(interval);
+        if let Some(buffer) = &self.buffer {
+            self.buffer = Some(OutputBuffer::shared(
+                buffer.capacity,
+                self.writer.clone(),
+                self.config.flush_interval,
+            ));
+        }
+        self
     }
 
-    /// Logs a message at error level.
+    /// Sends entries to the local syslog daemon instead of stderr or the writer.
     ///
-    /// Error messages indicate failure conditions that may prevent
-    /// the application from functioning correctly.
+    /// Levels map to syslog severities by their numeric severity: `Error` to
+    /// `err`, `Warn` to `warning`, custom levels between `Info` and `Warn` to
+    /// `notice`, `Info` to `info`, and anything lower to `debug`. Since classic
+    /// syslog messages have no structured slots, fields are appended to the
+    /// message as `key=value` pairs. Formats, colors, and timestamps don't
+    /// apply; syslog adds its own timestamp. If the syslog socket can't be
+    /// reached, a warning is logged and output stays unchanged. Requires the
+    /// `syslog` feature.
     ///
     /// # Arguments
     ///
-    /// * `message` - The log message
-    /// * `fields` - Additional key-value pairs
+    /// * `facility` - The syslog facility to log under
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
     ///
     /// # Examples
     ///
-    /// ```rust
-    /// use ccb::Logger;
+    /// ```rust,no_run
+    /// use ccb::{Logger, SyslogFacility};
     ///
-    /// let logger = Logger::new();
-    /// logger.error("Database connection failed", &[("host", "localhost")]);
+    /// let logger = Logger::new().with_syslog(SyslogFacility::LOG_DAEMON);
+    /// logger.info("Daemon started", &[("pid", "4242")]);
     /// ```
-    pub fn error(&self, message: &str, fields: &[(&str, &str)]) {
-        self.log(Level::Error, message, fields);
+    #[cfg(feature = "syslog")]
+    #[track_caller]
+    pub fn with_syslog(mut self, facility: SyslogFacility) -> Self {
+        match syslog_sink::SyslogSink::connect(facility) {
+            Ok(sink) => self.syslog = Some(sink),
+            Err(err) => {
+                let error = err.to_string();
+                self.log_internal(
+                    Level::Warn,
+                    "Syslog unavailable, keeping current output",
+                    &[("error", &error)],
+                );
+                report_error(&CcbError::Io(err));
+            }
+        }
+        self
     }
 
-    /// Formats and writes a log entry to stderr.
+    /// Also writes a copy of every entry to the file at `path`.
     ///
-    /// This method handles the complete formatting process including timestamps,
-    /// colored level indicators, the message, and structured fields. Output is
-    /// written to stderr using the configured color settings.
-    ///
-    /// In test environments where stderr might not be available, write operations
-    /// are silently ignored to prevent panics.
+    /// The primary output is unchanged, so the terminal keeps its colors while
+    /// the file gets a plain copy in the logger's format. Colors are decided
+    /// for each tee on its own: a path that is itself a terminal, such as
+    /// `/dev/tty`, gets colored copies unless `$NO_COLOR` is set, independent
+    /// of [`Logger::with_colors`]. The file is created
+    /// if needed and appended to. Copies are written immediately, even when
+    /// the primary output is buffered. If the file can't be opened, a warning
+    /// is logged once, here, and the logger continues without the copy.
     ///
     /// # Arguments
     ///
-    /// * `entry` - The log entry to format and write
-    fn write_entry(&self, entry: &LogEntry) {
-        // In test environments, stderr might not be available, so we need to handle errors gracefully
-        let result = std::panic::catch_unwind(|| {
-            let color_choice = if self.config.use_colors {
-                ColorChoice::Auto
-            } else {
-                ColorChoice::Never
-            };
+    /// * `path` - The file to append entries to
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_tee("debug.log");
+    /// logger.info("Written to stderr and debug.log", &[]);
+    /// ```
+    #[track_caller]
+    pub fn with_tee<P: AsRef<Path>>(self, path: P) -> Self {
+        self.add_tee(path.as_ref(), None)
+    }
 
-            let mut stderr = StandardStream::stderr(color_choice);
+    /// Like [`Logger::with_tee`], but writes the copy in its own format.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to append entries to
+    /// * `format` - The format of the copy, such as [`Format::Json`]
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use ccb::{Format, Logger};
+    ///
+    /// let logger = Logger::new().with_tee_format("app.jsonl", Format::Json);
+    /// ```
+    #[track_caller]
+    pub fn with_tee_format<P: AsRef<Path>>(self, path: P, format: Format) -> Self {
+        self.add_tee(path.as_ref(), Some(format))
+    }
 
-            // Write timestamp if enabled
-            if self.config.show_timestamp {
-                let _ = stderr.set_color(ColorSpec::new().set_fg(Some(Color::Rgb(128, 128, 128))));
-                let _ = write!(
-                    stderr,
-                    "{} ",
-                    entry.timestamp.format("%Y-%m-%d %H:%M:%S%.3f")
+    /// Opens `path` for appending and adds it as a tee, warning on failure.
+    #[track_caller]
+    fn add_tee(mut self, path: &Path, format: Option<Format>) -> Self {
+        match open_append(path) {
+            Ok(file) => self.tees.push(Tee {
+                path: path.to_path_buf(),
+                use_colors: color::file_use_colors(&file),
+                writer: Arc::new(Mutex::new(file)),
+                format,
+                schema_written: Arc::new(AtomicBool::new(false)),
+            }),
+            Err(err) => {
+                let path = path.display().to_string();
+                let error = err.to_string();
+                self.log_internal(
+                    Level::Warn,
+                    "Tee file unavailable, skipping copy",
+                    &[("path", &path), ("error", &error)],
                 );
-                let _ = stderr.reset();
+                report_error(&CcbError::Io(err));
             }
+        }
+        self
+    }
 
-            // Write level with color and bold
-            let _ = stderr.set_color(
-                ColorSpec::new()
-                    .set_fg(Some(entry.level.color()))
-                    .set_bold(true),
-            );
-            let _ = write!(stderr, "{} ", entry.level);
-            let _ = stderr.reset();
-
-            // Write message
-            let _ = write!(stderr, "{}", entry.message);
-
-            // Write context fields
-            for (key, value) in &entry.fields {
-                let _ = stderr.set_color(ColorSpec::new().set_fg(Some(Color::Rgb(128, 128, 128))));
-                let _ = write!(stderr, " {}=", key);
-                let _ = stderr.reset();
-                let _ = write!(stderr, "{}", value);
+    /// Writes out any entries held back by [`Logger::with_buffering`], after
+    /// waiting for the queue of [`Logger::with_async_channel`] to drain.
+    ///
+    /// Does nothing when neither is enabled. Failures go to the handler
+    /// installed with [`set_error_handler`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_buffering(8192);
+    /// logger.info("Shutting down", &[]);
+    /// logger.flush();
+    /// ```
+    pub fn flush(&self) {
+        if let Some(sink) = &self.async_sink {
+            sink.drain();
+        }
+        if let Some(buffer) = &self.buffer {
+            if let Err(err) = buffer.flush() {
+                report_error(&CcbError::Io(err));
             }
+        }
+    }
 
-            let _ = writeln!(stderr);
-            let _ = stderr.flush();
-        });
+    /// Flushes pending output, then reopens the log files by path.
+    ///
+    /// Call this after an external tool moved the files away, such as
+    /// logrotate followed by `SIGHUP`, or in the child of a `fork` so it stops
+    /// sharing file descriptors with its parent. Everything buffered is
+    /// written first, to the old files; afterwards, the
+    /// [rotating file](Logger::with_rotating_file) and
+    /// [tee](Logger::with_tee) files are opened afresh at their paths,
+    /// creating them if they were moved. Clones of the logger share the files
+    /// and see the reopened ones. Other outputs are unaffected.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error from opening a file. The remaining files are
+    /// still reopened, and a file that can't be reopened keeps its previous
+    /// descriptor.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_rotating_file("app.log", 10 * 1024 * 1024, 5);
+    /// // After `mv app.log app.log.old` and SIGHUP:
+    /// logger.reopen_sinks().expect("reopen log files");
+    /// logger.info("Written to a fresh app.log", &[]);
+    /// ```
+    pub fn reopen_sinks(&self) -> std::io::Result<()> {
+        self.flush();
+        let mut result = Ok(());
+        if let Some(file) = &self.rotating_file {
+            let mut file = file.lock().unwrap_or_else(PoisonError::into_inner);
+            result = result.and(file.reopen());
+        }
+        for tee in &self.tees {
+            let reopened = open_append(&tee.path).map(|file| {
+                *tee.writer.lock().unwrap_or_else(PoisonError::into_inner) = file;
+            });
+            result = result.and(reopened);
+        }
+        result
+    }
 
-        // Silently ignore any panics that occur during writing
-        // This is primarily for test environments where stderr might not be available
-        let _ = result;
+    /// Hands entries to a background thread that formats and writes them, so
+    /// logging never waits on output I/O.
+    ///
+    /// Entries are queued, up to `capacity` of them; when the queue is full,
+    /// logging blocks until there is room. See
+    /// [`Logger::with_async_channel_policy`] to drop entries instead. Clones of
+    /// the logger share the queue. When the last clone is dropped, the queue
+    /// is drained before the thread stops; [`Logger::flush`] and
+    /// [`flush`](crate::flush) wait for it to drain, for example before
+    /// [`std::process::exit`].
+    ///
+    /// The background thread renders with a snapshot of the logger as
+    /// configured at this point, so call this after the settings that affect
+    /// output, such as the format, colors, or writer. Context fields added
+    /// later still apply, since entries are built before they are queued.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The maximum number of queued entries, at least 1
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let (logger, capture) = Logger::capturing();
+    /// let logger = logger.with_timestamp(false).with_async_channel(1024);
+    /// logger.info("Request served", &[("status", "200")]);
+    ///
+    /// logger.flush();
+    /// assert_eq!(capture.lines(), ["INFO Request served status=200"]);
+    /// ```
+    pub fn with_async_channel(self, capacity: usize) -> Self {
+        self.with_async_channel_policy(capacity, OverflowPolicy::Block)
     }
-}
 
-impl Default for Logger {
-    /// Creates a logger with default configuration.
+    /// Like [`Logger::with_async_channel`], with a choice of what happens when
+    /// the queue is full.
     ///
-    /// Equivalent to calling `Logger::new()`.
-    fn default() -> Self {
-        Self::new()
+    /// # Arguments
+    ///
+    /// * `capacity` - The maximum number of queued entries, at least 1
+    /// * `policy` - Whether to block or to drop the oldest queued entry
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Logger, OverflowPolicy};
+    ///
+    /// let logger = Logger::new().with_async_channel_policy(256, OverflowPolicy::DropOldest);
+    /// ```
+    pub fn with_async_channel_policy(mut self, capacity: usize, policy: OverflowPolicy) -> Self {
+        let mut worker = self.clone();
+        worker.async_sink = None;
+        self.async_sink = Some(Arc::new(async_sink::AsyncSink::spawn(
+            worker, capacity, policy,
+        )));
+        self
     }
-}
 
-/// Global logger instance used by the logging macros.
-///
-/// This static instance allows the logging macros to function without requiring
-/// explicit logger parameters. It can be customized using `set_global_logger()`.
-static GLOBAL_LOGGER: Lazy<Arc<Mutex<Logger>>> = Lazy::new(|| Arc::new(Mutex::new(Logger::new())));
+    /// Overrides the detected terminal color capability.
+    ///
+    /// Colors the terminal can't display are downgraded to the closest color it
+    /// can, so `Rgb` level colors still render sensibly on a 16-color terminal.
+    /// By default the capability is detected with [`ColorSupport::detect`].
+    ///
+    /// # Arguments
+    ///
+    /// * `support` - The colors the output terminal can display
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{ColorSupport, Logger};
+    ///
+    /// let logger = Logger::new().with_color_support(ColorSupport::Basic);
+    /// ```
+    pub fn with_color_support(mut self, support: ColorSupport) -> Self {
+        self.config.color_support = support;
+        self
+    }
 
-/// Sets the global logger instance used by logging macros.
-///
-/// This function replaces the default global logger with a custom configured logger.
-/// After calling this, all macro invocations will use the new logger configuration.
-///
-/// # Arguments
-///
-/// * `logger` - The logger instance to set as the global logger
-///
-/// # Examples
-///
-/// ```rust
-/// use ccb::{Logger, Level, set_global_logger, info};
-///
-/// let custom_logger = Logger::new()
-///     .with_level(Level::Debug)
-///     .with("app", "my-service");
-///
-/// set_global_logger(custom_logger);
-/// info!("This will use the custom logger configuration");
-/// ```
-pub fn set_global_logger(logger: Logger) {
-    if let Ok(mut global) = GLOBAL_LOGGER.lock() {
-        *global = logger;
+    /// Returns the terminal color capability this logger renders for.
+    pub fn color_support(&self) -> ColorSupport {
+        self.config.color_support
     }
-}
 
-/// Returns a clone of the current global logger.
-///
-/// This function provides access to the global logger instance, allowing you to
-/// inspect its configuration or use it directly instead of through macros.
-///
+    /// Sets the colors of level labels.
+    ///
+    /// The default theme uses each level's [`Level::color`];
+    /// [`Theme::monochrome`] shows every label in the terminal's default color.
+    /// Themes only take effect when colors are enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `theme` - The level colors to use
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Color, Logger, Theme};
+    ///
+    /// let light = Theme {
+    ///     info: Some(Color::Blue),
+    ///     warn: Some(Color::Magenta),
+    ///     ..Theme::default()
+    /// };
+    /// let logger = Logger::new().with_theme(light);
+    /// ```
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.config.theme = theme;
+        self
+    }
+
+    /// Sets whether level labels are bold and the colors of field keys and
+    /// messages.
+    ///
+    /// Like themes, styles only take effect when colors are enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `style` - The text styles to use
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Logger, StyleConfig};
+    ///
+    /// // Keep the colors but drop bold, which some terminals render oddly
+    /// let logger = Logger::new().with_style(StyleConfig {
+    ///     bold_level: false,
+    ///     ..StyleConfig::default()
+    /// });
+    /// ```
+    pub fn with_style(mut self, style: StyleConfig) -> Self {
+        self.config.style = style;
+        self
+    }
+
+    /// Returns a readable summary of the logger's effective configuration.
+    ///
+    /// This is [`Config::describe`] followed by the logger's output and its
+    /// context fields, sorted by key.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with("service", "auth");
+    /// if std::env::args().any(|arg| arg == "--dump-log-config") {
+    ///     print!("{}", logger.describe());
+    /// }
+    /// assert!(logger.describe().ends_with("context: service=auth\n"));
+    /// ```
+    pub fn describe(&self) -> String {
+        let mut context: Vec<_> = self
+            .context
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        context.sort();
+        let context = if context.is_empty() {
+            "none".to_string()
+        } else {
+            context.join(", ")
+        };
+        #[allow(unused_mut)]
+        let mut output = if self.writer.is_some() {
+            "custom writer"
+        } else {
+            "stderr"
+        };
+        #[cfg(feature = "syslog")]
+        if self.syslog.is_some() {
+            output = "syslog";
+        }
+        let buffering = self.buffer.as_ref().map_or("off".to_string(), |buffer| {
+            format!("{} bytes", buffer.capacity)
+        });
+        let asynchronous = self.async_sink.as_ref().map_or("off".to_string(), |sink| {
+            format!("{} entries, {:?}", sink.capacity(), sink.policy())
+        });
+        let tees: Vec<_> = self
+            .tees
+            .iter()
+            .map(|tee| tee.path.display().to_string())
+            .collect();
+        let tees = if tees.is_empty() {
+            "none".to_string()
+        } else {
+            tees.join(", ")
+        };
+        format!(
+            "{}output: {}\nbuffering: {}\nasync: {}\ntees: {}\ncontext: {}\n",
+            self.config.describe(),
+            output,
+            buffering,
+            asynchronous,
+            tees,
+            context
+        )
+    }
+
+    /// Enables or disables timestamp display in log output.
+    ///
+    /// When enabled, each log entry is prefixed with a timestamp in the format
+    /// `2009-11-10 15:00:00.123` displayed in gray; see
+    /// [`Logger::with_timestamp_precision`] for finer or coarser ones. This applies
+    /// to every level and clears any overrides set with
+    /// [`Logger::with_level_timestamp`].
+    ///
+    /// # Arguments
+    ///
+    /// * `show_timestamp` - Whether to display timestamps
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_timestamp(false); // Hide timestamps
+    /// ```
+    pub fn with_timestamp(mut self, show_timestamp: bool) -> Self {
+        self.config.show_timestamp = show_timestamp;
+        self.config.level_timestamps.clear();
+        self
+    }
+
+    /// Shows or hides timestamps for a single level.
+    ///
+    /// This overrides [`Logger::with_timestamp`] for entries at exactly
+    /// `level`, for example to keep timestamps on warnings and errors for
+    /// incident correlation while dropping them from chatty debug output.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The level the setting applies to
+    /// * `show_timestamp` - Whether entries at that level display a timestamp
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Level, Logger};
+    ///
+    /// let logger = Logger::new()
+    ///     .with_level(Level::Trace)
+    ///     .with_level_timestamp(Level::Debug, false)
+    ///     .with_level_timestamp(Level::Trace, false);
+    /// ```
+    pub fn with_level_timestamp(mut self, level: Level, show_timestamp: bool) -> Self {
+        self.config.level_timestamps.retain(|(l, _)| *l != level);
+        self.config.level_timestamps.push((level, show_timestamp));
+        self
+    }
+
+    /// Sets how many fractional-second digits timestamps show.
+    ///
+    /// This applies to the ISO-8601 timestamps of the JSON and logfmt formats
+    /// and to the default pretty and CSV layout, which becomes
+    /// `%Y-%m-%d %H:%M:%S%.6f` for `Micros`. A custom
+    /// [`Logger::with_timestamp_format`] is kept as is, whichever is set
+    /// first. The default is `Millis`.
+    ///
+    /// # Arguments
+    ///
+    /// * `precision` - Seconds, milliseconds, microseconds, or nanoseconds
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Logger, TimestampPrecision};
+    ///
+    /// let logger = Logger::new().with_timestamp_precision(TimestampPrecision::Micros);
+    /// // 2009-11-10 15:00:00.123456 INFO ...
+    /// ```
+    pub fn with_timestamp_precision(mut self, precision: TimestampPrecision) -> Self {
+        self.config.timestamp_precision = precision;
+        self
+    }
+
+    /// Shortens pretty timestamps that repeat the previous entry's second.
+    ///
+    /// With [`TimestampMode::Compact`], the full timestamp is only shown when
+    /// the second changes; other entries show just the sub-second part, such
+    /// as `+.125`, with the digits of the timestamp precision (at least
+    /// milliseconds). This cuts the noise of dense trace output. Only the
+    /// pretty format is affected, and the tracking is shared between clones.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - `Full` (the default) or `Compact`
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Logger, TimestampMode};
+    ///
+    /// let logger = Logger::new().with_timestamp_mode(TimestampMode::Compact);
+    /// logger.info("Started", &[]); // 2009-11-10 15:00:00.123 INFO Started
+    /// logger.debug("Tick", &[]); // +.125 DEBG Tick
+    /// ```
+    pub fn with_timestamp_mode(mut self, mode: TimestampMode) -> Self {
+        self.config.timestamp_mode = mode;
+        self
+    }
+
+    /// Sets the chrono format string used for timestamps.
+    ///
+    /// The format applies to the pretty and CSV formats; JSON and logfmt
+    /// always use ISO-8601, with [`Logger::with_timestamp_precision`]
+    /// digits. The default is `%Y-%m-%d %H:%M:%S%.3f`, whose fractional
+    /// digits follow the timestamp precision. An invalid format, such as an
+    /// empty string or one with an unknown specifier, is ignored with a
+    /// warning rather than producing empty or broken timestamps.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - A chrono format string, e.g. `%H:%M:%S`
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_timestamp_format("%H:%M:%S");
+    /// ```
+    #[track_caller]
+    pub fn with_timestamp_format(mut self, format: &str) -> Self {
+        if format::is_valid_timestamp_format(format) {
+            self.config.timestamp_format = format.to_string();
+        } else {
+            self.log_internal(
+                Level::Warn,
+                "Invalid timestamp format ignored",
+                &[("format", format)],
+            );
+        }
+        self
+    }
+
+    /// Enables or disables rendering the time elapsed since the previous entry.
+    ///
+    /// When enabled, each entry shows a delta such as `+12ms` after its level,
+    /// measured from the previous entry emitted by this logger or any of its
+    /// clones. The first entry shows `+0ms`. This only affects rendering in the
+    /// `Pretty` format; the absolute timestamp is still recorded and shown.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to render inter-entry deltas
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_inter_entry_timing(true);
+    /// logger.info("Loading config", &[]);
+    /// logger.info("Config loaded", &[]); // rendered with e.g. "+3ms"
+    /// ```
+    pub fn with_inter_entry_timing(mut self, enabled: bool) -> Self {
+        self.config.inter_entry_timing = enabled;
+        self
+    }
+
+    /// Enables or disables capturing the source location of each log call.
+    ///
+    /// When enabled, every entry records the file and line that called the
+    /// logging method or macro, and the `Pretty` format shows it before the
+    /// message. The level methods are `#[track_caller]`, so direct calls such as
+    /// `logger.error(...)` report the caller's location rather than CCB's.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to capture source locations
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_location(true);
+    /// logger.error("Write failed", &[]); // rendered with e.g. "src/main.rs:4"
+    /// ```
+    pub fn with_location(mut self, enabled: bool) -> Self {
+        self.config.show_location = enabled;
+        self
+    }
+
+    /// Restricts logging to a wall-clock time window.
+    ///
+    /// Entries logged outside `[start, end)` are silently dropped regardless of
+    /// their level. Either bound may be `None` to leave that side of the window
+    /// open, which makes it possible to log only after or only before a given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The earliest time at which entries are emitted (inclusive)
+    /// * `end` - The time from which entries are no longer emitted (exclusive)
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    /// use chrono::{Duration, Local};
+    ///
+    /// let now = Local::now();
+    /// let logger = Logger::new().with_active_window(Some(now), Some(now + Duration::minutes(5)));
+    /// let from_now_on = Logger::new().with_active_window(Some(now), None);
+    /// ```
+    pub fn with_active_window(
+        mut self,
+        start: Option<DateTime<Local>>,
+        end: Option<DateTime<Local>>,
+    ) -> Self {
+        self.config.active_from = start;
+        self.config.active_until = end;
+        self
+    }
+
+    /// Enables or disables visually distinguishing inherited context fields.
+    ///
+    /// When enabled, the `Pretty` format renders fields inherited from the
+    /// logger's context dimmed, while fields passed to the individual log call
+    /// keep their normal styling. This makes it easy to see what is new on each
+    /// line versus boilerplate context. A per-call field that overrides a
+    /// context key counts as per-call.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to distinguish field origins
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new()
+    ///     .with("service", "auth")
+    ///     .with_distinguish_origin(true);
+    /// logger.info("Login", &[("user", "alice")]); // service=auth is dimmed
+    /// ```
+    pub fn with_distinguish_origin(mut self, enabled: bool) -> Self {
+        self.config.distinguish_origin = enabled;
+        self
+    }
+
+    /// Renders the values of the given field keys as clickable hyperlinks.
+    ///
+    /// In the `Pretty` format with colors active, values of these fields are
+    /// wrapped in OSC 8 escape sequences, which modern terminals display as
+    /// clickable links. Values that already contain a URL scheme (such as
+    /// `https://`) link to themselves; anything else is treated as a file path
+    /// and linked with a `file://` URL, resolved against the current directory
+    /// if relative. When colors are disabled the value is written as plain text.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The field keys whose values should become hyperlinks
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_hyperlink_keys(["path", "docs"]);
+    /// logger.error("Parse failed", &[("path", "config/app.toml")]);
+    /// ```
+    pub fn with_hyperlink_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.hyperlink_keys = keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Enables or disables rendering fields sorted by key.
+    ///
+    /// Fields are stored in a hash map, so by default their order varies from
+    /// run to run. Sorting makes output stable at a small cost per entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to sort fields by key
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_sorted_fields(mut self, enabled: bool) -> Self {
+        self.config.sort_fields = enabled;
+        self
+    }
+
+    /// Configures the logger for stable, diff-friendly output.
+    ///
+    /// This is intended for golden tests that compare logged output against a
+    /// fixed expectation. When enabled, it disables colors, timestamps, and
+    /// inter-entry timing, and sorts fields by key, so the same log calls always
+    /// produce the same bytes. Disabling it only turns field sorting back off;
+    /// the other settings can be restored with their own builder methods.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to enable deterministic output
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_deterministic(true);
+    /// logger.info("Done", &[("b", "2"), ("a", "1")]); // "INFO Done a=1 b=2"
+    /// ```
+    pub fn with_deterministic(self, enabled: bool) -> Self {
+        if enabled {
+            self.with_colors(false)
+                .with_timestamp(false)
+                .with_inter_entry_timing(false)
+                .with_sorted_fields(true)
+        } else {
+            self.with_sorted_fields(false)
+        }
+    }
+
+    /// Enables or disables disambiguating entries that share a timestamp.
+    ///
+    /// At high throughput many entries can share the same microsecond, which
+    /// makes their order ambiguous. When enabled, the `Pretty` format appends a
+    /// counter to the timestamp (e.g. `14:30:25.123#2`) that starts at zero and
+    /// resets whenever the microsecond changes. The counter is shared between
+    /// clones of the logger and costs one short lock per entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to append the sequence counter
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_timestamp_sequence(true);
+    /// ```
+    pub fn with_timestamp_sequence(mut self, enabled: bool) -> Self {
+        self.config.timestamp_sequence = enabled;
+        self
+    }
+
+    /// Suppresses cascades of follow-on errors after a first error.
+    ///
+    /// When a subsystem fails it often produces a burst of secondary errors that
+    /// bury the root cause. With suppression enabled, the first error opens a
+    /// window of the given length; further errors logged within that window are
+    /// dropped. Once the window has closed, the next entry is preceded by a
+    /// `WARN` summary carrying the number of suppressed errors as `suppressed`.
+    /// Entries below the error threshold are never suppressed.
+    ///
+    /// By default only `Error` (and custom levels at least as severe) counts as
+    /// an error; see [`Logger::with_cascade_includes_warnings`].
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - How long to suppress follow-on errors after the first one
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_error_cascade_suppression(Duration::from_secs(2));
+    /// logger.error("Database unreachable", &[]);
+    /// logger.error("Query failed", &[]); // suppressed
+    /// ```
+    pub fn with_error_cascade_suppression(mut self, window: Duration) -> Self {
+        self.config.error_cascade_window = Some(window);
+        self
+    }
+
+    /// Limits how often an identical entry is emitted.
+    ///
+    /// The first entry with a given level and message opens a window of length
+    /// `interval`, in which at most `max_per_interval` entries with the same
+    /// level and message are emitted and the rest are dropped. Fields are not
+    /// compared, and the same message at different levels is counted
+    /// separately. Once a window with dropped entries has closed, the next
+    /// entry logged is preceded by a summary at the original level, such as
+    /// `Connection refused ... suppressed 41 messages`, carrying the count as
+    /// `suppressed`.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_per_interval` - How many identical entries may be emitted per window
+    /// * `interval` - How long a window lasts
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use ccb::Logger;
+    ///
+    /// let (logger, capture) = Logger::capturing();
+    /// let logger = logger
+    ///     .with_timestamp(false)
+    ///     .with_rate_limit(2, Duration::from_secs(60));
+    /// for _ in 0..5 {
+    ///     logger.error("Connection refused", &[]);
+    /// }
+    /// logger.warn("Connection refused", &[]);
+    ///
+    /// assert_eq!(
+    ///     capture.lines(),
+    ///     ["ERRO Connection refused", "ERRO Connection refused", "WARN Connection refused"]
+    /// );
+    /// ```
+    pub fn with_rate_limit(mut self, max_per_interval: usize, interval: Duration) -> Self {
+        self.config.rate_limit = Some((max_per_interval, interval));
+        self
+    }
+
+    /// Controls whether warnings count as errors for cascade suppression.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether `Warn` entries open and are suppressed by cascade windows
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_cascade_includes_warnings(mut self, enabled: bool) -> Self {
+        self.config.cascade_includes_warnings = enabled;
+        self
+    }
+
+    /// Holds back recent below-threshold entries until an error occurs.
+    ///
+    /// Entries filtered out by the level threshold are normally discarded.
+    /// With a buffer of `capacity` entries, the most recent ones are kept
+    /// instead, and when an entry at `Error` severity or above is logged, they
+    /// are written just before it, oldest first, with their original
+    /// timestamps. This gives failures their debug context without the noise
+    /// in the normal case. Entries still in the buffer when no error comes are
+    /// never written.
+    ///
+    /// Buffered entries are rendered in the configured format when they are
+    /// written. Entries outside the active window or rejected by the
+    /// allowed-keys policy are not buffered, and errors suppressed as part of
+    /// a cascade don't flush the buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - How many below-threshold entries to hold; `0` disables
+    ///   the buffer
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Level, Logger};
+    ///
+    /// let logger = Logger::new()
+    ///     .with_level(Level::Info)
+    ///     .with_error_context_buffer(50);
+    /// logger.debug("Cache miss", &[("key", "user:42")]); // held back
+    /// logger.error("Lookup failed", &[]); // writes the debug entry, then the error
+    /// ```
+    pub fn with_error_context_buffer(mut self, capacity: usize) -> Self {
+        self.config.error_context_buffer = capacity;
+        self
+    }
+
+    /// Displays timestamps in a fixed named timezone instead of local time.
+    ///
+    /// This is useful when a team's canonical operational timezone differs from
+    /// the host's. Entries still record the local time; only rendering is
+    /// affected. Requires the `chrono-tz` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `timezone` - The timezone to display timestamps in
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    /// use chrono_tz::America::New_York;
+    ///
+    /// let logger = Logger::new().with_timezone(New_York);
+    /// ```
+    #[cfg(feature = "chrono-tz")]
+    pub fn with_timezone(mut self, timezone: chrono_tz::Tz) -> Self {
+        self.config.timezone = Some(timezone);
+        self.config.utc = false;
+        self
+    }
+
+    /// Displays timestamps in UTC instead of local time.
+    ///
+    /// This makes logs from services in different timezones easy to
+    /// correlate. As with a named timezone, entries still record the local
+    /// time and only rendering is affected. Enabling UTC replaces a timezone
+    /// set with `with_timezone`.
+    ///
+    /// # Arguments
+    ///
+    /// * `utc` - Whether to display timestamps in UTC
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_utc(true);
+    /// ```
+    pub fn with_utc(mut self, utc: bool) -> Self {
+        self.config.utc = utc;
+        #[cfg(feature = "chrono-tz")]
+        if utc {
+            self.config.timezone = None;
+        }
+        self
+    }
+
+    /// Counts emitted entries through the `metrics` facade.
+    ///
+    /// When enabled, every entry that is written increments the `log.events`
+    /// counter with a `level` label, so log volume by level can be exported by
+    /// whatever recorder the application has installed. Filtered and suppressed
+    /// entries are not counted. Requires the `metrics` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to count emitted entries
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_metrics(true);
+    /// logger.error("Payment failed", &[]); // log.events{level="ERRO"} += 1
+    /// ```
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, enabled: bool) -> Self {
+        self.config.count_metrics = enabled;
+        self
+    }
+
+    /// Records where each macro-logged entry came from.
+    ///
+    /// When enabled, entries logged through [`info!`] and the other logging
+    /// macros carry a `callsite` field with the file and line of the macro call
+    /// and an `invocation` field with the macro's source text, including the
+    /// original key and value expressions. This is meant for a debugging flag
+    /// such as `--explain-logs` that helps trace noisy output back to its
+    /// source. Entries logged through methods are unaffected.
+    ///
+    /// Requires the `explain` feature; without it the macros don't capture
+    /// anything.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to attach call site details to macro entries
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{info, set_global_logger, Logger};
+    ///
+    /// set_global_logger(Logger::new().with_explain(true));
+    /// let user = "alice";
+    /// // INFO Login callsite=src/main.rs:6 invocation=info!("Login", "user", user)
+    /// info!("Login", "user", user);
+    /// ```
+    #[cfg(feature = "explain")]
+    pub fn with_explain(mut self, enabled: bool) -> Self {
+        self.config.explain = enabled;
+        self
+    }
+
+    /// Sets the string written after each entry in place of a newline.
+    ///
+    /// A NUL delimiter (`"\0"`) lets consumers such as `xargs -0` split records
+    /// reliably even when messages or field values contain newlines, and
+    /// `"\r\n"` suits line-based network protocols. The delimiter also
+    /// terminates the CSV header row. An empty delimiter would run entries
+    /// together, so it is ignored with a warning.
+    ///
+    /// # Arguments
+    ///
+    /// * `delimiter` - The string that terminates each record
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_record_delimiter("\0");
+    /// ```
+    #[track_caller]
+    pub fn with_record_delimiter(mut self, delimiter: impl Into<String>) -> Self {
+        let delimiter = delimiter.into();
+        if delimiter.is_empty() {
+            self.log_internal(Level::Warn, "Empty record delimiter ignored", &[]);
+        } else {
+            self.config.record_delimiter = delimiter;
+        }
+        self
+    }
+
+    /// Sets the line terminator written after each entry, such as `"\r\n"`.
+    ///
+    /// This is an alias of [`Logger::with_record_delimiter`], so an empty
+    /// terminator is likewise ignored with a warning and the default stays
+    /// `"\n"`.
+    ///
+    /// # Arguments
+    ///
+    /// * `terminator` - The string that ends each line
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_line_terminator("\r\n");
+    /// ```
+    #[track_caller]
+    pub fn with_line_terminator(self, terminator: &str) -> Self {
+        self.with_record_delimiter(terminator)
+    }
+
+    /// Shows live status entries on a single, continually rewritten line.
+    ///
+    /// Entries that carry a `key` field are status entries. On a terminal, a
+    /// status entry whose `key` value matches that of the entry just before it
+    /// replaces that line instead of scrolling, giving a single-line live status
+    /// without a progress-bar dependency. Any other entry ends the status line
+    /// normally. When stderr isn't a terminal, or records don't end in a plain
+    /// newline, status entries are written as ordinary lines.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The field key that tags status entries
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_status_key("status");
+    /// for done in [10, 50, 100] {
+    ///     let progress = format!("{}%", done);
+    ///     logger.info("Downloading", &[("status", "download"), ("progress", &progress)]);
+    /// }
+    /// logger.info("Download complete", &[]);
+    /// ```
+    pub fn with_status_key(mut self, key: impl Into<String>) -> Self {
+        self.config.status_key = Some(key.into());
+        self
+    }
+
+    /// Sets how the logger generates ids, such as span ids.
+    ///
+    /// By default ids are short random base62 strings. A custom generator can
+    /// produce ids in the format of another tracing system, for example UUIDs
+    /// or a monotonic counter (see [`IdGenerator::counter`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `generate` - A closure returning a new id on each call
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    /// use ccb::Logger;
+    ///
+    /// static NEXT: AtomicU64 = AtomicU64::new(0);
+    /// let logger = Logger::new()
+    ///     .with_id_generator(|| format!("op-{:06}", NEXT.fetch_add(1, Ordering::Relaxed)));
+    /// let span = logger.span_start("sync", &[]);
+    /// assert_eq!(span.id(), "op-000000");
+    /// ```
+    pub fn with_id_generator<F>(mut self, generate: F) -> Self
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        self.config.id_generator = IdGenerator::new(generate);
+        self
+    }
+
+    /// Warns when [`Logger::with`] overwrites an existing context key.
+    ///
+    /// In large logger-composition code, a child logger that sets a key its
+    /// parent already set usually shadows it by accident. In strict mode each
+    /// overwrite logs a warning naming the key and both values; the later value
+    /// still wins. The setting is inherited by loggers derived from this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to warn about overwritten context keys
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let base = Logger::new().with_strict_context(true).with("svc", "x");
+    /// let child = base.with("svc", "y"); // WARN Context key overwritten key=svc previous=x value=y
+    /// ```
+    pub fn with_strict_context(mut self, enabled: bool) -> Self {
+        self.config.strict_context = enabled;
+        self
+    }
+
+    /// Pre-sizes the field maps for entries with many fields.
+    ///
+    /// Each entry's field map is allocated with room for at least `capacity`
+    /// fields, and the logger's context map is grown to the same size, so hot
+    /// paths that log many fields don't reallocate as the maps fill up.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The expected number of fields per entry
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_field_capacity(16);
+    /// ```
+    pub fn with_field_capacity(mut self, capacity: usize) -> Self {
+        self.config.field_capacity = capacity;
+        self.context
+            .reserve(capacity.saturating_sub(self.context.len()));
+        self
+    }
+
+    /// Restricts field keys to a declared set.
+    ///
+    /// Fields whose keys are not in the set are handled according to the
+    /// [`KeyPolicy`] set with [`Logger::with_unknown_key_policy`] (by default,
+    /// `Warn`). The check applies to every field of an entry, including context
+    /// added with [`Logger::with`], so context keys must be listed as well.
+    /// Fields added by CCB itself, such as span ids, are checked too.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The permitted field keys
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{KeyPolicy, Logger};
+    ///
+    /// let logger = Logger::new()
+    ///     .with("service", "auth")
+    ///     .with_allowed_keys(["service", "user_id", "duration_ms"])
+    ///     .with_unknown_key_policy(KeyPolicy::Drop);
+    ///
+    /// logger.info("Login", &[("user_id", "42"), ("usr", "typo")]); // usr is dropped
+    /// ```
+    pub fn with_allowed_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.allowed_keys = Some(keys.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Hides the values of fields with sensitive keys.
+    ///
+    /// Matching fields are written with the value `***` in every format,
+    /// including JSON, tees, and syslog, and by [`Logger::format_entry`]. Keys
+    /// are compared case-insensitively, and a pattern may start or end with
+    /// `*` to match a suffix or prefix: `*_secret` matches `client_secret` and
+    /// `API_SECRET`. Context fields and fields added by CCB itself are
+    /// redacted too. Patterns add to those given earlier.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The key patterns to redact
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let (logger, capture) = Logger::capturing();
+    /// let logger = logger
+    ///     .with_timestamp(false)
+    ///     .with_sorted_fields(true)
+    ///     .with_redacted_keys(&["password", "*_secret"]);
+    ///
+    /// logger.info("Login", &[("user", "ada"), ("Password", "hunter2")]);
+    /// assert_eq!(capture.lines(), ["INFO Login Password=*** user=ada"]);
+    /// ```
+    pub fn with_redacted_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.config
+            .redacted_keys
+            .extend(keys.into_iter().map(|key| key.as_ref().to_lowercase()));
+        self
+    }
+
+    /// Returns whether values of fields named `key` are redacted.
+    fn is_redacted(&self, key: &str) -> bool {
+        if self.config.redacted_keys.is_empty() {
+            return false;
+        }
+        let key = key.to_lowercase();
+        self.config.redacted_keys.iter().any(|pattern| {
+            if let Some(suffix) = pattern.strip_prefix('*') {
+                key.ends_with(suffix)
+            } else if let Some(prefix) = pattern.strip_suffix('*') {
+                key.starts_with(prefix)
+            } else {
+                key == *pattern
+            }
+        })
+    }
+
+    /// Returns `entry` with redacted field values replaced, borrowing it
+    /// unchanged when no field is redacted.
+    fn redact<'a>(&self, entry: &'a LogEntry) -> Cow<'a, LogEntry> {
+        if !entry.fields.keys().any(|key| self.is_redacted(key)) {
+            return Cow::Borrowed(entry);
+        }
+        let mut entry = entry.clone();
+        for (key, value) in entry.fields.iter_mut() {
+            if self.is_redacted(key) {
+                *value = "***".to_string();
+            }
+        }
+        Cow::Owned(entry)
+    }
+
+    /// Sets how fields with keys outside the allowlist are handled.
+    ///
+    /// Has no effect unless [`Logger::with_allowed_keys`] is also used.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The policy for unknown keys
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_unknown_key_policy(mut self, policy: KeyPolicy) -> Self {
+        self.config.unknown_key_policy = policy;
+        self
+    }
+
+    /// Sets the output format used to render entries.
+    ///
+    /// Formats other than `Pretty` are intended for machine consumption and
+    /// never include color codes, regardless of the color setting.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The format to render entries with
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{CsvFormat, Format, Logger};
+    ///
+    /// let logger = Logger::new().with_format(Format::Csv(CsvFormat::new(["user"])));
+    /// ```
+    pub fn with_format(mut self, format: Format) -> Self {
+        self.config.format = format;
+        self
+    }
+
+    /// Nests dotted field keys into objects in the JSON format.
+    ///
+    /// When enabled, a field key is split on `.` and each part becomes an
+    /// object level, so `http.status` and `http.method` are written as
+    /// `"http":{"method":...,"status":...}`. Fields are applied in key order
+    /// and the later one wins a conflict, so `http.status` replaces a plain
+    /// `http` field. Keys whose first part is reserved, such as `level`, are
+    /// moved under `fields` as in the flat layout. Other formats are
+    /// unaffected.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to nest dotted keys
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Format, Logger};
+    ///
+    /// let logger = Logger::new()
+    ///     .with_format(Format::Json)
+    ///     .with_nested_json(true);
+    /// logger.info("Request", &[("http.method", "GET"), ("http.status", "200")]);
+    /// // {...,"message":"Request","http":{"method":"GET","status":"200"}}
+    /// ```
+    pub fn with_nested_json(mut self, enabled: bool) -> Self {
+        self.config.nested_json = enabled;
+        self
+    }
+
+    /// Writes a schema descriptor before the first JSON record of each output.
+    ///
+    /// Strict ingestion pipelines can read the descriptor to learn the record
+    /// layout up front. It is a single line,
+    /// `{"_schema":{"version":1,"fields":{...}}}`, mapping the keys records
+    /// may carry to their JSON types: the entry's own keys, such as
+    /// `timestamp` and `message`, and the context fields set on the logger so
+    /// far. The primary output and each [tee](Logger::with_tee) file get the
+    /// descriptor once, when they are in the JSON format.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to write the schema header
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Format, Logger};
+    ///
+    /// let (logger, capture) = Logger::capturing();
+    /// let logger = logger
+    ///     .with_format(Format::Json)
+    ///     .with_json_schema_header(true)
+    ///     .with("service", "auth");
+    /// logger.info("Started", &[]);
+    /// logger.info("Ready", &[]);
+    ///
+    /// let lines = capture.lines();
+    /// assert_eq!(lines.len(), 3);
+    /// assert!(lines[0].starts_with(r#"{"_schema":{"version":1,"fields":{"timestamp":"string","#));
+    /// assert!(lines[0].contains(r#""service":"string""#));
+    /// ```
+    pub fn with_json_schema_header(mut self, enabled: bool) -> Self {
+        self.config.json_schema_header = enabled;
+        self
+    }
+
+    /// Limits how many distinct field keys the JSON format writes as
+    /// top-level keys.
+    ///
+    /// A burst of unique keys, such as ids used as keys, can blow up the
+    /// index of a log backend. With a limit, the logger remembers the first
+    /// `max` distinct field keys it emits; fields with any other key are
+    /// collected into a single `extra` key holding them as a stringified JSON
+    /// object, which marks the record as having overflowed. The limit is
+    /// shared by clones of the logger and only counts fields, not the
+    /// entry's own keys. Other formats are unaffected, as is
+    /// [`Logger::format_entry`], which doesn't update the remembered keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `max` - The number of distinct field keys written at the top level
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Format, Logger};
+    ///
+    /// let (logger, capture) = Logger::capturing();
+    /// let logger = logger.with_format(Format::Json).with_json_max_keys(1);
+    /// logger.info("Cache", &[("hits", "3"), ("user:42", "miss")]);
+    ///
+    /// let line = &capture.lines()[0];
+    /// assert!(line.contains(r#""hits":"3""#));
+    /// assert!(line.ends_with(r#""extra":"{\"user:42\":\"miss\"}"}"#));
+    /// ```
+    pub fn with_json_max_keys(mut self, max: usize) -> Self {
+        self.config.json_max_keys = Some(max);
+        self
+    }
+
+    /// Adds a context key-value pair that will be included in all log entries.
+    ///
+    /// Context is persistent and gets applied to every log message from this logger.
+    /// This is useful for adding service names, versions, request IDs, or other
+    /// metadata that should appear in all logs.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The context key (converted to String)
+    /// * `value` - The context value (converted to String)
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new()
+    ///     .with("service", "auth")
+    ///     .with("version", "1.2.0")
+    ///     .with("request_id", "req-123");
+    ///
+    /// logger.info("Processing request", &[]); // Will include all context fields
+    /// ```
+    #[track_caller]
+    pub fn with<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let key = key.into();
+        let value = value.into();
+        if self.config.strict_context {
+            if let Some(previous) = self.context.get(&key) {
+                self.log_internal(
+                    Level::Warn,
+                    "Context key overwritten",
+                    &[("key", &key), ("previous", previous), ("value", &value)],
+                );
+            }
+        }
+        self.context.insert(key, value);
+        self
+    }
+
+    /// Adds several context key-value pairs at once.
+    ///
+    /// This is equivalent to calling [`Logger::with`] for each pair in order,
+    /// so it accumulates with existing context and a later pair overrides an
+    /// earlier one with the same key.
+    ///
+    /// # Arguments
+    ///
+    /// * `fields` - The context key-value pairs
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new()
+    ///     .with_fields(&[("service", "auth"), ("version", "1.2.0")])
+    ///     .with("region", "eu-west-1");
+    /// ```
+    #[track_caller]
+    pub fn with_fields(self, fields: &[(&str, &str)]) -> Self {
+        fields
+            .iter()
+            .fold(self, |logger, (key, value)| logger.with(*key, *value))
+    }
+
+    /// Creates a child logger with this logger's configuration and context.
+    ///
+    /// Chain [`Logger::with`] onto the child to add context, such as a request
+    /// id, without changing the parent. The child writes to the same output
+    /// and shares the parent's state, so settings such as rate limiting and
+    /// sampling count the entries of both together.
+    ///
+    /// # Returns
+    ///
+    /// A new logger that can be configured independently of this one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let (base, capture) = Logger::capturing();
+    /// let base = base.with_timestamp(false).with("service", "auth");
+    ///
+    /// let request = base.child().with("req", "42");
+    /// request.info("Handled", &[]);
+    /// base.info("Idle", &[]);
+    ///
+    /// let lines = capture.lines();
+    /// assert!(lines[0].contains("req=42") && lines[0].contains("service=auth"));
+    /// assert_eq!(lines[1], "INFO Idle service=auth");
+    /// ```
+    pub fn child(&self) -> Logger {
+        self.clone()
+    }
+
+    /// Returns whether entries at `level` pass this logger's level filter.
+    ///
+    /// Use it to skip building expensive fields for entries that would be
+    /// dropped. Per-module levels only apply to macro calls; see
+    /// [`enabled!`] for a check that honors them.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The level to check
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Level, Logger};
+    ///
+    /// let logger = Logger::new().with_level(Level::Info);
+    /// assert!(!logger.is_enabled(Level::Debug));
+    ///
+    /// if logger.is_enabled(Level::Debug) {
+    ///     let dump = format!("{:?}", vec![0u8; 4096]);
+    ///     logger.debug("Buffer", &[("dump", &dump)]);
+    /// }
+    /// ```
+    pub fn is_enabled(&self, level: Level) -> bool {
+        self.is_enabled_in(None, level)
+    }
+
+    /// Returns whether entries at `level` logged from `module` pass the level
+    /// filter.
+    fn is_enabled_in(&self, module: Option<&str>, level: Level) -> bool {
+        let threshold = self.level_for(module);
+        level != Level::Off && threshold != Level::Off && level >= threshold
+    }
+
+    /// Logs a message at the specified level with additional structured fields.
+    ///
+    /// This is the core logging method used by all level-specific methods.
+    /// It combines the logger's persistent context with the provided fields
+    /// and outputs the message if it meets the minimum level threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The severity level for this log entry
+    /// * `message` - The primary log message
+    /// * `fields` - Additional key-value pairs for this specific log entry
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Logger, Level};
+    ///
+    /// let logger = Logger::new();
+    /// logger.log(Level::Info, "User authenticated", &[("user_id", "12345")]);
+    /// ```
+    #[track_caller]
+    pub fn log(&self, level: Level, message: &str, fields: &[(&str, &str)]) {
+        self.log_from(None, None, level, message, fields);
+    }
+
+    /// Logs a message tagged with a target, a logical subsystem such as `db`
+    /// or `http` that is independent of the module path.
+    ///
+    /// The pretty format shows the target between the level and the message;
+    /// JSON and logfmt output carry it as a `target` key. Unlike a macro's
+    /// `target:`, which names a registered logger and warns once when none is
+    /// registered, this never consults the registry, so it is the way to tag
+    /// entries with a subsystem that has no logger of its own.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The severity level for this log entry
+    /// * `target` - The subsystem the entry belongs to
+    /// * `message` - The primary log message
+    /// * `fields` - Additional key-value pairs
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Level, Logger};
+    ///
+    /// let (logger, capture) = Logger::capturing();
+    /// let logger = logger.with_timestamp(false);
+    /// logger.log_target(Level::Info, "db", "Connected", &[("pool", "4")]);
+    ///
+    /// assert_eq!(capture.lines(), ["INFO db: Connected pool=4"]);
+    /// ```
+    #[track_caller]
+    pub fn log_target(&self, level: Level, target: &str, message: &str, fields: &[(&str, &str)]) {
+        self.log_from(None, Some(target), level, message, fields);
+    }
+
+    /// Logs an entry made from `module`, if known, applying any module level.
+    #[track_caller]
+    fn log_from(
+        &self,
+        module: Option<&str>,
+        target: Option<&str>,
+        level: Level,
+        message: &str,
+        fields: &[(&str, &str)],
+    ) {
+        let threshold = self.level_for(module);
+        if level == Level::Off || threshold == Level::Off {
+            return;
+        }
+        if !self.is_in_active_window(Local::now()) {
+            return;
+        }
+        if level < threshold {
+            if self.config.error_context_buffer > 0 {
+                let mut entry = self.build_entry(level, message, fields);
+                entry.target = target.map(String::from);
+                self.buffer_context_entry(entry);
+            }
+            return;
+        }
+        if !self.is_sampled(level) {
+            return;
+        }
+        let (emit, closed) = self.check_rate_limit(level, message);
+        for window in closed {
+            let summary = format!(
+                "{} ... suppressed {} messages",
+                window.message, window.suppressed
+            );
+            let suppressed = window.suppressed.to_string();
+            let entry = self.build_entry(window.level, &summary, &[("suppressed", &suppressed)]);
+            self.write_entry(&entry);
+        }
+        if !emit {
+            return;
+        }
+
+        let (emit, suppressed) = self.check_error_cascade(level);
+        if suppressed > 0 {
+            let suppressed = suppressed.to_string();
+            let summary = self.build_entry(
+                Level::Warn,
+                "Suppressed follow-on errors",
+                &[("suppressed", &suppressed)],
+            );
+            self.write_entry(&summary);
+        }
+        if !emit {
+            return;
+        }
+
+        let mut entry = self.build_entry(level, message, fields);
+        entry.target = target.map(String::from);
+        if let Err(unknown) = self.enforce_allowed_keys(&mut entry) {
+            let keys = unknown.join(",");
+            self.log_internal(
+                Level::Error,
+                "Log entry rejected: unknown field keys",
+                &[("keys", &keys), ("rejected_message", message)],
+            );
+            return;
+        }
+        if level >= Level::Error {
+            self.flush_context_buffer();
+        }
+        self.run_hooks(&entry);
+        self.write_entry(&entry);
+    }
+
+    /// Runs the hooks on an entry about to be written, with redacted fields
+    /// already replaced. A panicking hook is reported to the error handler
+    /// and doesn't stop the others.
+    fn run_hooks(&self, entry: &LogEntry) {
+        if self.hooks.is_empty() {
+            return;
+        }
+        let entry = self.redact(entry);
+        for EntryHook(hook) in &self.hooks {
+            if std::panic::catch_unwind(AssertUnwindSafe(|| hook(&entry))).is_err() {
+                report_error(&CcbError::HookPanicked);
+            }
+        }
+    }
+
+    /// Delivers a telemetry event to the hooks and structured outputs, but
+    /// not to the console.
+    ///
+    /// An event is an entry at [`Level::EVENT`] that bypasses the level
+    /// filter, sampling, and rate limiting. It carries the logger's context
+    /// and redaction like any other entry, which lets hooks added with
+    /// [`Logger::with_hook`] use the logger as a lightweight event bus for
+    /// metrics. It is also written to outputs in a machine-readable format
+    /// (JSON, logfmt, CSV, or MessagePack), whether the main output, going
+    /// through the async channel or syslog if set, or a tee, but never in the
+    /// human-readable pretty format. Without hooks or such outputs, this does
+    /// nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the event, stored as the entry's message
+    /// * `fields` - Additional key-value pairs
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::sync::{Arc, Mutex};
+    /// use ccb::{Level, LogEntry, Logger};
+    ///
+    /// let events = Arc::new(Mutex::new(Vec::new()));
+    /// let seen = events.clone();
+    /// let (logger, capture) = Logger::capturing();
+    /// let logger = logger.with_hook(Arc::new(move |entry: &LogEntry| {
+    ///     if entry.level == Level::EVENT {
+    ///         seen.lock().unwrap().push(entry.message.clone());
+    ///     }
+    /// }));
+    ///
+    /// logger.event("cache.miss", &[("key", "user:42")]);
+    /// assert_eq!(*events.lock().unwrap(), ["cache.miss"]);
+    /// assert!(capture.lines().is_empty());
+    /// ```
+    #[track_caller]
+    pub fn event(&self, name: &str, fields: &[(&str, &str)]) {
+        if self.hooks.is_empty() && !self.writes_events() {
+            return;
+        }
+        let entry = self.build_entry(Level::EVENT, name, fields);
+        self.run_hooks(&entry);
+        self.write_entry(&entry);
+    }
+
+    /// Returns whether any output is in a structured format that events are
+    /// written to.
+    fn writes_events(&self) -> bool {
+        self.config.format.is_structured()
+            || self.tees.iter().any(|tee| {
+                tee.format
+                    .as_ref()
+                    .unwrap_or(&self.config.format)
+                    .is_structured()
+            })
+    }
+
+    /// Logs a message with owned field values.
+    ///
+    /// This is [`Logger::log`] for values that had to be formatted first, for
+    /// example numbers turned into strings. The logging macros use it to
+    /// accept any `Display` value.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The severity level for this log entry
+    /// * `message` - The primary log message
+    /// * `fields` - Additional key-value pairs with owned values
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Level, Logger};
+    ///
+    /// let logger = Logger::new();
+    /// let count = 42;
+    /// logger.log_owned(Level::Info, "Batch done", &[("count", count.to_string())]);
+    /// ```
+    #[track_caller]
+    pub fn log_owned(&self, level: Level, message: &str, fields: &[(&str, String)]) {
+        self.log(level, message, &borrow_fields(fields));
+    }
+
+    /// Logs a message with fields that are only computed if the entry is kept.
+    ///
+    /// `fields` is called after the level check passes, so expensive values,
+    /// such as serialized structs, cost nothing when the level is filtered out.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The severity level for this log entry
+    /// * `message` - The primary log message
+    /// * `fields` - A closure returning the entry's key-value pairs
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Level, Logger};
+    ///
+    /// let logger = Logger::new();
+    /// let state = vec![1, 2, 3];
+    /// logger.log_lazy(Level::Trace, "State dump", || {
+    ///     vec![("state", format!("{:?}", state))] // not run: Trace is filtered
+    /// });
+    /// ```
+    #[track_caller]
+    pub fn log_lazy<'a, F>(&self, level: Level, message: &str, fields: F)
+    where
+        F: FnOnce() -> Vec<(&'a str, String)>,
+    {
+        if level_rank(level) < self.min_rank() || level == Level::Off {
+            return;
+        }
+        self.log_owned(level, message, &fields());
+    }
+
+    /// Returns the minimum level for entries logged from `module`.
+    fn level_for(&self, module: Option<&str>) -> Level {
+        let Some(module) = module else {
+            return self.config.level;
+        };
+        self.config
+            .module_levels
+            .iter()
+            .filter(|(path, _)| {
+                module
+                    .strip_prefix(path.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .max_by_key(|(path, _)| path.len())
+            .map_or(self.config.level, |(_, level)| *level)
+    }
+
+    /// Counts an entry at `level` and returns whether sampling keeps it.
+    fn is_sampled(&self, level: Level) -> bool {
+        let Some(index) = self.config.sampling.iter().position(|(l, _)| *l == level) else {
+            return true;
+        };
+        let n = self.config.sampling[index].1.max(1);
+        let seen = self.sample_counters[index].fetch_add(1, AtomicOrdering::Relaxed);
+        seen.is_multiple_of(n)
+    }
+
+    /// Returns the lowest level rank this logger acts on.
+    ///
+    /// Entries ranked below it are dropped without any other effect. Module
+    /// levels may lower it. When the error context buffer is enabled, every
+    /// entry may be buffered, so the rank is 0.
+    fn min_rank(&self) -> u16 {
+        if self.config.error_context_buffer > 0 {
+            return 0;
+        }
+        self.config
+            .module_levels
+            .iter()
+            .map(|(_, level)| level_rank(*level))
+            .fold(level_rank(self.config.level), u16::min)
+    }
+
+    /// Holds a below-threshold entry in the error context buffer, evicting the
+    /// oldest entry when the buffer is full.
+    fn buffer_context_entry(&self, mut entry: LogEntry) {
+        if self.enforce_allowed_keys(&mut entry).is_err() {
+            return;
+        }
+        if let Ok(mut buffer) = self.state.context_buffer.lock() {
+            while buffer.len() >= self.config.error_context_buffer {
+                buffer.pop_front();
+            }
+            buffer.push_back(entry);
+        }
+    }
+
+    /// Writes and clears the entries held in the error context buffer.
+    fn flush_context_buffer(&self) {
+        let buffered = match self.state.context_buffer.lock() {
+            Ok(mut buffer) => std::mem::take(&mut *buffer),
+            Err(_) => return,
+        };
+        for entry in &buffered {
+            self.write_entry(entry);
+        }
+    }
+
+    /// Writes a diagnostic entry produced by CCB itself.
+    ///
+    /// Only the level threshold applies; filters that act on user entries, such
+    /// as the allowed-keys policy, are bypassed so diagnostics can't recurse.
+    #[track_caller]
+    fn log_internal(&self, level: Level, message: &str, fields: &[(&str, &str)]) {
+        if level >= self.config.level && level != Level::Off {
+            let entry = self.build_entry(level, message, fields);
+            self.write_entry(&entry);
+        }
+    }
+
+    /// Applies the allowed-keys policy to an entry's fields.
+    ///
+    /// Returns the sorted unknown keys as an error when the policy rejects the
+    /// entry. Under the `Warn` policy, a warning is written for each unknown key
+    /// the first time it is seen.
+    fn enforce_allowed_keys(&self, entry: &mut LogEntry) -> Result<(), Vec<String>> {
+        let Some(allowed) = &self.config.allowed_keys else {
+            return Ok(());
+        };
+
+        let mut unknown: Vec<String> = entry
+            .fields
+            .keys()
+            .filter(|key| !allowed.contains(*key))
+            .cloned()
+            .collect();
+        if unknown.is_empty() {
+            return Ok(());
+        }
+        unknown.sort();
+
+        match self.config.unknown_key_policy {
+            KeyPolicy::Warn => {
+                let newly_seen: Vec<String> = match self.state.warned_keys.lock() {
+                    Ok(mut warned) => unknown
+                        .into_iter()
+                        .filter(|key| warned.insert(key.clone()))
+                        .collect(),
+                    Err(_) => Vec::new(),
+                };
+                for key in newly_seen {
+                    self.log_internal(Level::Warn, "Unknown field key", &[("key", &key)]);
+                }
+                Ok(())
+            }
+            KeyPolicy::Drop => {
+                for key in &unknown {
+                    entry.fields.remove(key);
+                    entry.inherited_keys.remove(key);
+                }
+                Ok(())
+            }
+            KeyPolicy::Error => Err(unknown),
+        }
+    }
+
+    /// Applies error cascade suppression to an entry at `level`.
+    ///
+    /// Returns whether the entry should be emitted, and the number of errors
+    /// suppressed in a window that has just closed, which should be summarized
+    /// before the entry.
+    fn check_error_cascade(&self, level: Level) -> (bool, usize) {
+        let Some(window) = self.config.error_cascade_window else {
+            return (true, 0);
+        };
+        let Ok(mut cascade) = self.state.cascade.lock() else {
+            return (true, 0);
+        };
+
+        let now = Instant::now();
+        let window_open = cascade
+            .window_start
+            .is_some_and(|start| now.duration_since(start) < window);
+        let threshold = if self.config.cascade_includes_warnings {
+            Level::Warn
+        } else {
+            Level::Error
+        };
+        let is_error = level >= threshold;
+
+        if window_open {
+            if is_error {
+                cascade.suppressed += 1;
+                return (false, 0);
+            }
+            return (true, 0);
+        }
+
+        let suppressed = std::mem::take(&mut cascade.suppressed);
+        cascade.window_start = if is_error { Some(now) } else { None };
+        (true, suppressed)
+    }
+
+    /// Applies rate limiting to an entry at `level` with `message`.
+    ///
+    /// Returns whether the entry should be emitted, and the windows that have
+    /// closed with suppressed entries, which should be summarized before it.
+    fn check_rate_limit(&self, level: Level, message: &str) -> (bool, Vec<RateWindow>) {
+        let Some((max, interval)) = self.config.rate_limit else {
+            return (true, Vec::new());
+        };
+        let Ok(mut windows) = self.state.rate_windows.lock() else {
+            return (true, Vec::new());
+        };
+
+        let now = Instant::now();
+        let expired: Vec<u64> = windows
+            .iter()
+            .filter(|(_, window)| now.duration_since(window.start) >= interval)
+            .map(|(key, _)| *key)
+            .collect();
+        let mut closed: Vec<RateWindow> = expired
+            .into_iter()
+            .filter_map(|key| windows.remove(&key))
+            .filter(|window| window.suppressed > 0)
+            .collect();
+        closed.sort_by_key(|window| window.start);
+
+        let mut hasher = DefaultHasher::new();
+        (level.severity(), level.as_str(), message).hash(&mut hasher);
+        let window = windows
+            .entry(hasher.finish())
+            .or_insert_with(|| RateWindow {
+                start: now,
+                level,
+                message: message.to_string(),
+                emitted: 0,
+                suppressed: 0,
+            });
+        if window.emitted < max {
+            window.emitted += 1;
+            (true, closed)
+        } else {
+            window.suppressed += 1;
+            (false, closed)
+        }
+    }
+
+    /// Returns whether `now` falls inside the configured active window.
+    fn is_in_active_window(&self, now: DateTime<Local>) -> bool {
+        self.config.active_from.is_none_or(|start| now >= start)
+            && self.config.active_until.is_none_or(|end| now < end)
+    }
+
+    /// Builds a log entry by merging the persistent context with per-call fields.
+    ///
+    /// The caller's location is recorded when location capture is enabled.
+    #[track_caller]
+    fn build_entry(&self, level: Level, message: &str, fields: &[(&str, &str)]) -> LogEntry {
+        let capacity = self
+            .config
+            .field_capacity
+            .max(self.context.len() + fields.len());
+        let mut entry_fields = if fields.is_empty() {
+            // Fast path for fieldless calls: cloning copies the context's
+            // table in one allocation instead of rehashing every key
+            let mut entry_fields = self.context.clone();
+            entry_fields.reserve(capacity.saturating_sub(entry_fields.len()));
+            entry_fields
+        } else {
+            let mut entry_fields = HashMap::with_capacity(capacity);
+            entry_fields.extend(self.context.iter().map(|(k, v)| (k.clone(), v.clone())));
+            entry_fields
+        };
+        context::with_scoped_fields(|scoped| entry_fields.extend(scoped.iter().cloned()));
+
+        let inherited_keys = if self.config.distinguish_origin {
+            entry_fields
+                .keys()
+                .filter(|key| !fields.iter().any(|(k, _)| k == key))
+                .cloned()
+                .collect()
+        } else {
+            HashSet::new()
+        };
+
+        for (key, value) in fields {
+            entry_fields.insert(key.to_string(), value.to_string());
+        }
+
+        LogEntry {
+            level,
+            message: message.to_string(),
+            fields: entry_fields,
+            timestamp: Local::now(),
+            location: if self.config.show_location {
+                Some(Location::caller())
+            } else {
+                None
+            },
+            target: None,
+            trace: self.active_trace_context(),
+            inherited_keys,
+        }
+    }
+
+    /// Logs a message at trace level.
+    ///
+    /// Trace messages are intended for fine-grained diagnostic information,
+    /// typically used for debugging complex flows or performance analysis.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The log message
+    /// * `fields` - Additional key-value pairs
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new();
+    /// logger.trace("Entering function", &[("function", "calculate_hash")]);
+    /// ```
+    #[track_caller]
+    pub fn trace(&self, message: &str, fields: &[(&str, &str)]) {
+        self.log(Level::Trace, message, fields);
+    }
+
+    /// Logs a message at debug level.
+    ///
+    /// Debug messages provide detailed information for development and
+    /// troubleshooting purposes.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The log message
+    /// * `fields` - Additional key-value pairs
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new();
+    /// logger.debug("Cache miss", &[("key", "user:12345")]);
+    /// ```
+    #[track_caller]
+    pub fn debug(&self, message: &str, fields: &[(&str, &str)]) {
+        self.log(Level::Debug, message, fields);
+    }
+
+    /// Logs a message at info level.
+    ///
+    /// Info messages communicate general information about application
+    /// flow and important events.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The log message
+    /// * `fields` - Additional key-value pairs
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new();
+    /// logger.info("Server started", &[("port", "8080")]);
+    /// ```
+    #[track_caller]
+    pub fn info(&self, message: &str, fields: &[(&str, &str)]) {
+        self.log(Level::Info, message, fields);
+    }
+
+    /// Logs a message at warn level.
+    ///
+    /// Warn messages indicate potentially harmful situations that
+    /// don't prevent the application from continuing.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The log message
+    /// * `fields` - Additional key-value pairs
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new();
+    /// logger.warn("High memory usage", &[("usage", "85%")]);
+    /// ```
+    #[track_caller]
+    pub fn warn(&self, message: &str, fields: &[(&str, &str)]) {
+        self.log(Level::Warn, message, fields);
+    }
+
+    /// Logs a message at error level.
+    ///
+    /// Error messages indicate failure conditions that may prevent
+    /// the application from functioning correctly.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The log message
+    /// * `fields` - Additional key-value pairs
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new();
+    /// logger.error("Database connection failed", &[("host", "localhost")]);
+    /// ```
+    #[track_caller]
+    pub fn error(&self, message: &str, fields: &[(&str, &str)]) {
+        self.log(Level::Error, message, fields);
+    }
+
+    /// Logs a message at error level along with an error and its causes.
+    ///
+    /// The error's message is added as the `err.0` field, and each error
+    /// reached through [`Error::source`](std::error::Error::source) as
+    /// `err.1`, `err.2`, and so on, keeping the whole causal chain in
+    /// structured output.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The log message
+    /// * `err` - The error to log
+    /// * `fields` - Additional key-value pairs
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{CcbError, Logger};
+    ///
+    /// let (logger, capture) = Logger::capturing();
+    /// let logger = logger.with_timestamp(false);
+    ///
+    /// let cause = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+    /// logger.error_with("Config not loaded", &CcbError::Io(cause), &[]);
+    ///
+    /// let line = &capture.lines()[0];
+    /// assert!(line.contains("err.1=no such file"));
+    /// ```
+    #[track_caller]
+    pub fn error_with(&self, message: &str, err: &dyn std::error::Error, fields: &[(&str, &str)]) {
+        let mut chain = Vec::new();
+        let mut current = Some(err);
+        while let Some(err) = current {
+            chain.push((format!("err.{}", chain.len()), err.to_string()));
+            current = err.source();
+        }
+        let mut all_fields: Vec<(&str, &str)> = fields.to_vec();
+        all_fields.extend(chain.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+        self.log(Level::Error, message, &all_fields);
+    }
+
+    /// Logs an entry on behalf of one of the logging macros.
+    ///
+    /// `module` is the calling module, for module levels. `invocation` is the
+    /// macro's source text, captured only with the `explain` feature. Not part
+    /// of the public API.
+    #[doc(hidden)]
+    #[track_caller]
+    #[cfg_attr(not(feature = "explain"), allow(unused_variables))]
+    pub fn __log_invocation(
+        &self,
+        level: Level,
+        message: &str,
+        fields: &[(&str, String)],
+        module: &'static str,
+        target: Option<&str>,
+        invocation: Option<&'static str>,
+    ) {
+        #[allow(unused_mut)]
+        let mut fields = borrow_fields(fields);
+        #[cfg(feature = "explain")]
+        let callsite;
+        #[cfg(feature = "explain")]
+        if let Some(invocation) = invocation.filter(|_| self.config.explain) {
+            let location = Location::caller();
+            callsite = format!("{}:{}", location.file(), location.line());
+            fields.push(("callsite", &callsite));
+            fields.push(("invocation", invocation));
+        }
+
+        self.log_from(Some(module), target, level, message, &fields);
+    }
+
+    /// Logs an entry for the [`logf!`] macro: interpolates `template` with the
+    /// argument values and keeps each argument as a field. Not part of the
+    /// public API.
+    ///
+    /// Arguments are keyed by their name, or by the source text of a plain
+    /// identifier; any other expression gets `arg0`, `arg1`, and so on by
+    /// position.
+    #[doc(hidden)]
+    #[track_caller]
+    pub fn __log_template(
+        &self,
+        level: Level,
+        template: &str,
+        args: &[(&str, String)],
+        module: &'static str,
+        invocation: Option<&'static str>,
+    ) {
+        let keys: Vec<Cow<str>> = args
+            .iter()
+            .enumerate()
+            .map(|(index, (key, _))| {
+                if is_identifier(key) {
+                    Cow::Borrowed(*key)
+                } else {
+                    Cow::Owned(format!("arg{}", index))
+                }
+            })
+            .collect();
+        let fields: Vec<(&str, String)> = keys
+            .iter()
+            .zip(args)
+            .map(|(key, (_, value))| (key.as_ref(), value.clone()))
+            .collect();
+        let message = interpolate(template, &fields);
+        self.__log_invocation(level, &message, &fields, module, None, invocation);
+    }
+
+    /// Logs the outcome of an operation and passes the result through.
+    ///
+    /// The entry uses `operation` as its message and carries an `outcome` field
+    /// set to `ok` or `err`. On failure, the error's `Display` output is added as
+    /// an `error` field. The result is returned unchanged so the call can wrap an
+    /// expression inline.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The severity level for the entry
+    /// * `operation` - A description of the operation, used as the message
+    /// * `result` - The operation's result
+    ///
+    /// # Returns
+    ///
+    /// Returns `result` unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Level, Logger};
+    ///
+    /// let logger = Logger::new();
+    /// let port: Result<u16, _> = logger.log_result(Level::Info, "parse port", "8080".parse());
+    /// assert_eq!(port, Ok(8080));
+    /// ```
+    #[track_caller]
+    pub fn log_result<T, E>(
+        &self,
+        level: Level,
+        operation: &str,
+        result: Result<T, E>,
+    ) -> Result<T, E>
+    where
+        E: fmt::Display,
+    {
+        match &result {
+            Ok(_) => self.log(level, operation, &[("outcome", "ok")]),
+            Err(err) => {
+                let error = err.to_string();
+                self.log(level, operation, &[("outcome", "err"), ("error", &error)]);
+            }
+        }
+        result
+    }
+
+    /// Logs an I/O error at error level with its kind and OS error number.
+    ///
+    /// The entry carries the error's `kind` (e.g. `NotFound`), its `errno` when
+    /// the error originated from the operating system, and its `Display` output
+    /// as `error`.
+    ///
+    /// # Arguments
+    ///
+    /// * `err` - The I/O error to log
+    /// * `message` - The log message
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new();
+    /// if let Err(err) = std::fs::read("/nonexistent/config.toml") {
+    ///     logger.log_io_error(&err, "Failed to read config");
+    ///     // ERRO Failed to read config kind=NotFound errno=2 error=No such file or directory (os error 2)
+    /// }
+    /// ```
+    #[track_caller]
+    pub fn log_io_error(&self, err: &std::io::Error, message: &str) {
+        self.log_owned(Level::Error, message, &io_error_fields(err));
+    }
+
+    /// Logs an HTTP request/response exchange with conventional field names.
+    ///
+    /// The message is `"<method> <path>"` and the entry carries `http.method`,
+    /// `http.path`, `http.status`, and `http.duration_ms` fields, so request logs
+    /// are keyed consistently across a codebase.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The severity level for the entry
+    /// * `method` - The request method, e.g. `GET`
+    /// * `path` - The request path
+    /// * `status` - The response status code
+    /// * `duration` - How long the request took
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use ccb::{Level, Logger};
+    ///
+    /// let logger = Logger::new();
+    /// logger.log_http(Level::Info, "GET", "/api/users", 200, Duration::from_millis(42));
+    /// // INFO GET /api/users http.method=GET http.path=/api/users http.status=200 http.duration_ms=42
+    /// ```
+    #[track_caller]
+    pub fn log_http(
+        &self,
+        level: Level,
+        method: &str,
+        path: &str,
+        status: u16,
+        duration: Duration,
+    ) {
+        let message = format!("{} {}", method, path);
+        let status = status.to_string();
+        let duration_ms = duration.as_millis().to_string();
+        self.log(
+            level,
+            &message,
+            &[
+                ("http.method", method),
+                ("http.path", path),
+                ("http.status", &status),
+                ("http.duration_ms", &duration_ms),
+            ],
+        );
+    }
+
+    /// Starts timing an operation whose completion is logged explicitly.
+    ///
+    /// The returned closure logs `name` at `level` when called, with the time
+    /// since `start_timer` as an `elapsed_ms` field plus any fields passed to
+    /// it. Unlike a guard that logs on drop, the closure can be called whenever
+    /// the operation actually completes, even outside the scope that started it.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The severity level for the completion entry
+    /// * `name` - The message for the completion entry
+    ///
+    /// # Returns
+    ///
+    /// A closure that logs the completion entry with the given extra fields.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Level, Logger};
+    ///
+    /// let logger = Logger::new();
+    /// let done = logger.start_timer(Level::Info, "render");
+    /// // ... render frames ...
+    /// done(&[("frames", "60")]); // INFO render frames=60 elapsed_ms=16
+    /// ```
+    pub fn start_timer(&self, level: Level, name: &str) -> impl FnOnce(&[(&str, &str)]) {
+        let logger = self.clone();
+        let name = name.to_string();
+        let started = Instant::now();
+        move |fields: &[(&str, &str)]| {
+            let elapsed_ms = started.elapsed().as_millis().to_string();
+            let mut fields = fields.to_vec();
+            fields.push(("elapsed_ms", &elapsed_ms));
+            logger.log(level, &name, &fields);
+        }
+    }
+
+    /// Renders a log entry to a string in the configured format, without colors.
+    ///
+    /// The returned string includes the trailing newline. Details computed only
+    /// when an entry is actually emitted, such as the inter-entry delta and the
+    /// timestamp sequence, are not included and no logger state is updated.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The log entry to render
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Level, LogEntry, Logger};
+    ///
+    /// let logger = Logger::new().with_timestamp(false);
+    /// let entry = LogEntry::new(Level::Info, "Ready");
+    /// assert_eq!(logger.format_entry(&entry), "INFO Ready\n");
+    /// ```
+    pub fn format_entry(&self, entry: &LogEntry) -> String {
+        self.format_entry_into(entry, termcolor::Buffer::no_color())
+    }
+
+    /// Renders a log entry to a string in the configured format, including ANSI
+    /// color codes.
+    ///
+    /// Colors are emitted regardless of the logger's color setting or terminal
+    /// detection, which makes the result suitable for buffering and writing to
+    /// destinations that interpret ANSI sequences, such as a TUI widget. They
+    /// are downgraded to the configured [`ColorSupport`], but never below
+    /// `Basic`, so a `dumb` or missing `$TERM` still yields colors. As with
+    /// [`Logger::format_entry`], emit-time details are not included.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The log entry to render
+    pub fn format_entry_colored(&self, entry: &LogEntry) -> String {
+        if self.config.color_support == ColorSupport::None {
+            return self
+                .clone()
+                .with_color_support(ColorSupport::Basic)
+                .format_entry_colored(entry);
+        }
+        self.format_entry_into(entry, termcolor::Buffer::ansi())
+    }
+
+    /// Renders an entry into the given in-memory buffer and returns its contents.
+    fn format_entry_into(&self, entry: &LogEntry, mut buffer: termcolor::Buffer) -> String {
+        let entry = self.redact(entry);
+        let annotations = Annotations {
+            preview: true,
+            ..Annotations::default()
+        };
+        let _ = self.render(&entry, &annotations, &mut buffer);
+        String::from_utf8_lossy(buffer.as_slice()).into_owned()
+    }
+
+    /// Formats and writes a log entry to the configured writer or stderr.
+    ///
+    /// The entry is rendered according to the configured format and written
+    /// to the writer set with [`Logger::with_writer`], or to stderr, using the
+    /// configured color settings.
+    ///
+    /// In test environments where stderr might not be available, write operations
+    /// are silently ignored to prevent panics.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The log entry to format and write
+    fn write_entry(&self, entry: &LogEntry) {
+        let entry = &*self.redact(entry);
+        if let Some(sink) = &self.async_sink {
+            sink.send(entry.clone());
+            return;
+        }
+
+        #[cfg(feature = "metrics")]
+        if self.config.count_metrics {
+            metrics::counter!("log.events", "level" => entry.level.as_str()).increment(1);
+        }
+
+        let annotations = self.annotate(entry);
+
+        // In test environments, stderr might not be available, so we need to handle errors gracefully
+        // Asserting unwind safety is fine: a panic can't leave shared state
+        // inconsistent, since locks are released by unwinding
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            // Events skip the console and only reach structured outputs
+            if entry.level == Level::EVENT && !self.config.format.is_structured() {
+                return Ok(());
+            }
+
+            // The decision was already made, from the environment or by an
+            // explicit `with_colors`, so termcolor must not second-guess it
+            let color_choice = if self.config.use_colors {
+                ColorChoice::Always
+            } else {
+                ColorChoice::Never
+            };
+
+            #[cfg(feature = "syslog")]
+            if let Some(syslog) = &self.syslog {
+                return syslog.send(entry);
+            }
+
+            let schema = self.schema_header(&self.config.format, &self.state.schema_written);
+            let new_buffer = || {
+                if self.config.use_colors {
+                    termcolor::Buffer::ansi()
+                } else {
+                    termcolor::Buffer::no_color()
+                }
+            };
+            if let Some(output) = &self.buffer {
+                let mut buffer = new_buffer();
+                write!(buffer, "{}", schema)?;
+                if self.writer.is_none() && atty::is(atty::Stream::Stderr) {
+                    write!(buffer, "{}", self.status_line_prefix(entry))?;
+                }
+                self.render(entry, &annotations, &mut buffer)?;
+                return output.write(buffer.as_slice());
+            }
+            if let Some(SharedWriter(writer)) = &self.writer {
+                let mut buffer = new_buffer();
+                write!(buffer, "{}", schema)?;
+                self.render(entry, &annotations, &mut buffer)?;
+                let mut writer = writer.lock().unwrap_or_else(PoisonError::into_inner);
+                writer.write_all(buffer.as_slice())?;
+                return writer.flush();
+            }
+
+            let stream = self.output_stream(entry.level);
+            let mut out = match stream {
+                atty::Stream::Stdout => {
+                    let color_choice = if self.colors_explicit {
+                        color_choice
+                    } else if color::stream_use_colors(stream) {
+                        ColorChoice::Always
+                    } else {
+                        ColorChoice::Never
+                    };
+                    StandardStream::stdout(color_choice)
+                }
+                _ => StandardStream::stderr(color_choice),
+            };
+            write!(out, "{}", schema)?;
+            if atty::is(stream) {
+                write!(out, "{}", self.status_line_prefix(entry))?;
+            }
+            self.render(entry, &annotations, &mut out)?;
+            out.flush()
+        }));
+        report_write_result(result);
+
+        for tee in &self.tees {
+            let format = tee.format.as_ref().unwrap_or(&self.config.format);
+            if entry.level == Level::EVENT && !format.is_structured() {
+                continue;
+            }
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                let mut buffer = if tee.use_colors {
+                    termcolor::Buffer::ansi()
+                } else {
+                    termcolor::Buffer::no_color()
+                };
+                write!(
+                    buffer,
+                    "{}",
+                    self.schema_header(format, &tee.schema_written)
+                )?;
+                self.render_as(format, entry, &annotations, &mut buffer)?;
+                let mut writer = tee.writer.lock().unwrap_or_else(PoisonError::into_inner);
+                writer.write_all(buffer.as_slice())?;
+                writer.flush()
+            }));
+            report_write_result(result);
+        }
+    }
+
+    /// Returns the schema header line to write before the next entry of an
+    /// output in `format`, or an empty string if it isn't wanted or has
+    /// already been written.
+    fn schema_header(&self, format: &Format, written: &AtomicBool) -> String {
+        if !self.config.json_schema_header
+            || !matches!(format, Format::Json)
+            || written.swap(true, AtomicOrdering::Relaxed)
+        {
+            return String::new();
+        }
+        let mut keys: Vec<_> = self.context.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        format!(
+            "{}{}",
+            format::json_schema(&keys, self.config.nested_json),
+            self.config.record_delimiter
+        )
+    }
+
+    /// Returns the standard stream an entry at `level` goes to when no writer
+    /// is set: stdout for levels below `Warn` with split streams, and stderr
+    /// otherwise.
+    fn output_stream(&self, level: Level) -> atty::Stream {
+        if self.config.split_streams && level < Level::Warn {
+            atty::Stream::Stdout
+        } else {
+            atty::Stream::Stderr
+        }
+    }
+
+    /// Returns the escape sequence that erases the previous status line when
+    /// `entry` replaces it, or an empty string.
+    ///
+    /// An entry replaces the previous one when both carry the same value for
+    /// the configured status key. Entries are only ever rewritten in place
+    /// when records end in a plain newline.
+    fn status_line_prefix(&self, entry: &LogEntry) -> &'static str {
+        let Some(key) = &self.config.status_key else {
+            return "";
+        };
+        if self.config.record_delimiter != "\n" {
+            return "";
+        }
+        let Ok(mut current) = self.state.status_tag.lock() else {
+            return "";
+        };
+
+        let tag = entry.fields.get(key).cloned();
+        let replaces = tag.is_some() && *current == tag;
+        *current = tag;
+        if replaces {
+            // Move up to the previous line and clear it
+            "\x1b[1A\x1b[2K"
+        } else {
+            ""
+        }
+    }
+
+    /// Computes the per-emit annotations for an entry about to be written.
+    fn annotate(&self, entry: &LogEntry) -> Annotations {
+        Annotations {
+            elapsed: self.elapsed_since_last_emit(),
+            sequence: self.timestamp_sequence(entry),
+            same_second: self.repeats_second(entry),
+            json_overflow: self.json_overflow(entry),
+            preview: false,
+        }
+    }
+
+    /// Admits the entry's field keys under the JSON key limit, in key order,
+    /// and returns those that don't fit.
+    fn json_overflow(&self, entry: &LogEntry) -> Vec<String> {
+        let Some(max) = self.config.json_max_keys else {
+            return Vec::new();
+        };
+        let mut known = self
+            .state
+            .json_keys
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut keys: Vec<_> = entry.fields.keys().collect();
+        keys.sort_unstable();
+        let mut overflow = Vec::new();
+        for key in keys {
+            if known.contains(key) {
+                continue;
+            }
+            if known.len() < max {
+                known.insert(key.clone());
+            } else {
+                overflow.push(key.clone());
+            }
+        }
+        overflow
+    }
+
+    /// Records the second of the entry's timestamp and returns whether it
+    /// matches the previous one, so a compact timestamp can be shown.
+    ///
+    /// Always `false` unless the compact timestamp mode is on and the entry
+    /// shows a timestamp.
+    fn repeats_second(&self, entry: &LogEntry) -> bool {
+        if self.config.timestamp_mode != TimestampMode::Compact
+            || !self.shows_timestamp(entry.level)
+        {
+            return false;
+        }
+
+        let second = entry.timestamp.timestamp();
+        let Ok(mut last) = self.state.last_second.lock() else {
+            return false;
+        };
+        last.replace(second) == Some(second)
+    }
+
+    /// Returns the entry's position among entries sharing its microsecond.
+    ///
+    /// Returns `None` when timestamp sequencing is disabled. Otherwise the
+    /// counter starts at zero and resets whenever the microsecond changes.
+    fn timestamp_sequence(&self, entry: &LogEntry) -> Option<u32> {
+        if !self.config.timestamp_sequence {
+            return None;
+        }
+
+        let micros = entry.timestamp.timestamp_micros();
+        let mut last = self.state.last_sequence.lock().ok()?;
+        let sequence = match *last {
+            (last_micros, sequence) if last_micros == micros => sequence + 1,
+            _ => 0,
+        };
+        *last = (micros, sequence);
+        Some(sequence)
+    }
+
+    /// Records the current emit time and returns the time since the previous one.
+    ///
+    /// Returns `None` when inter-entry timing is disabled, and a zero duration
+    /// for the first entry.
+    fn elapsed_since_last_emit(&self) -> Option<Duration> {
+        if !self.config.inter_entry_timing {
+            return None;
+        }
+
+        let now = Instant::now();
+        let mut last_emit = self.state.last_emit.lock().ok()?;
+        let previous = last_emit.replace(now);
+        Some(previous.map_or(Duration::ZERO, |previous| now - previous))
+    }
+
+    /// Renders a log entry in the configured format.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The log entry to render
+    /// * `annotations` - Per-emit details such as the inter-entry delta
+    /// * `out` - The destination; colors are only applied in the `Pretty` format
+    fn render(
+        &self,
+        entry: &LogEntry,
+        annotations: &Annotations,
+        out: &mut dyn WriteColor,
+    ) -> std::io::Result<()> {
+        self.render_as(&self.config.format, entry, annotations, out)
+    }
+
+    /// Renders a log entry in the given format, recursing into composites.
+    fn render_as(
+        &self,
+        format: &Format,
+        entry: &LogEntry,
+        annotations: &Annotations,
+        out: &mut dyn WriteColor,
+    ) -> std::io::Result<()> {
+        match format {
+            Format::Pretty => self.render_pretty(entry, annotations, out),
+            Format::Csv(csv) => csv.render(
+                entry,
+                &self.format_timestamp(&entry.timestamp),
+                &self.config.record_delimiter,
+                annotations.preview,
+                out,
+            ),
+            Format::Json => format::render_json(
+                entry,
+                &self.format_timestamp_as(
+                    &entry.timestamp,
+                    self.config.timestamp_precision.iso_format(),
+                ),
+                self.config.nested_json,
+                &annotations.json_overflow,
+                &self.config.record_delimiter,
+                out,
+            ),
+            Format::Logfmt => format::render_logfmt(
+                entry,
+                &self.format_timestamp_as(
+                    &entry.timestamp,
+                    self.config.timestamp_precision.iso_format(),
+                ),
+                &self.config.record_delimiter,
+                out,
+            ),
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => format::render_msgpack(entry, out),
+            Format::Composite(composite) => {
+                for (index, format) in composite.formats().iter().enumerate() {
+                    if index > 0 {
+                        write!(out, "{}", composite.separator())?;
+                    }
+                    self.render_as(format, entry, annotations, out)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Formats a timestamp for output, converting it to the configured timezone.
+    ///
+    /// The default layout takes its fractional digits from the timestamp
+    /// precision; a custom one is used as is.
+    fn format_timestamp(&self, timestamp: &DateTime<Local>) -> String {
+        let pattern = if self.config.timestamp_format == format::TIMESTAMP_FORMAT {
+            self.config.timestamp_precision.timestamp_format()
+        } else {
+            &self.config.timestamp_format
+        };
+        self.format_timestamp_as(timestamp, pattern)
+    }
+
+    /// Formats a timestamp with the given pattern, converting it to UTC or the
+    /// configured timezone.
+    fn format_timestamp_as(&self, timestamp: &DateTime<Local>, pattern: &str) -> String {
+        if self.config.utc {
+            return format::format_datetime(&timestamp.with_timezone(&Utc), pattern);
+        }
+        #[cfg(feature = "chrono-tz")]
+        if let Some(timezone) = self.config.timezone {
+            return format::format_datetime(&timestamp.with_timezone(&timezone), pattern);
+        }
+
+        format::format_datetime(timestamp, pattern)
+    }
+
+    /// Returns whether entries at `level` display a timestamp.
+    fn shows_timestamp(&self, level: Level) -> bool {
+        self.config
+            .level_timestamps
+            .iter()
+            .find(|(l, _)| *l == level)
+            .map_or(self.config.show_timestamp, |&(_, show)| show)
+    }
+
+    /// Renders a log entry in the human-readable terminal layout.
+    ///
+    /// This handles timestamps, colored level indicators, inter-entry deltas,
+    /// the message, and structured fields.
+    fn render_pretty(
+        &self,
+        entry: &LogEntry,
+        annotations: &Annotations,
+        out: &mut dyn WriteColor,
+    ) -> std::io::Result<()> {
+        // Write timestamp if enabled
+        if self.shows_timestamp(entry.level) {
+            out.set_color(&self.config.color_support.foreground(MUTED))?;
+            if annotations.same_second {
+                let fraction = self.config.timestamp_precision.fraction_format();
+                write!(
+                    out,
+                    "+{}",
+                    self.format_timestamp_as(&entry.timestamp, fraction)
+                )?;
+            } else {
+                write!(out, "{}", self.format_timestamp(&entry.timestamp))?;
+            }
+            if let Some(sequence) = annotations.sequence {
+                write!(out, "#{}", sequence)?;
+            }
+            write!(out, " ")?;
+            out.reset()?;
+        }
+
+        // Write level with its theme color, bold unless styled otherwise
+        let mut level_spec = self.color_spec(self.config.theme.color(entry.level));
+        out.set_color(level_spec.set_bold(self.config.style.bold_level))?;
+        write!(out, "{} ", entry.level)?;
+        out.reset()?;
+
+        // Write trace context
+        if let Some(trace) = &entry.trace {
+            out.set_color(&self.config.color_support.foreground(MUTED))?;
+            write!(out, "{} ", trace)?;
+            out.reset()?;
+        }
+
+        // Write time since the previous entry
+        if let Some(elapsed) = annotations.elapsed {
+            out.set_color(&self.config.color_support.foreground(MUTED))?;
+            write!(out, "{} ", format_elapsed(elapsed))?;
+            out.reset()?;
+        }
+
+        // Write source location
+        if let Some(location) = entry.location {
+            out.set_color(&self.config.color_support.foreground(MUTED))?;
+            write!(out, "{}:{} ", location.file(), location.line())?;
+            out.reset()?;
+        }
+
+        // Write target
+        if let Some(target) = &entry.target {
+            out.set_color(self.config.color_support.foreground(MUTED).set_bold(true))?;
+            write!(out, "{}:", target)?;
+            out.reset()?;
+            write!(out, " ")?;
+        }
+
+        // Write message
+        match self.config.style.message_color {
+            Some(color) => {
+                out.set_color(&self.config.color_support.foreground(color))?;
+                write!(out, "{}", entry.message)?;
+                out.reset()?;
+            }
+            None => write!(out, "{}", entry.message)?,
+        }
+
+        // Write context fields
+        let mut fields: Vec<_> = entry.fields.iter().collect();
+        if self.config.sort_fields {
+            fields.sort_unstable_by_key(|(key, _)| *key);
+        }
+        for (key, value) in fields {
+            let inherited = entry.inherited_keys.contains(key);
+            out.set_color(
+                self.color_spec(self.config.style.key_color)
+                    .set_dimmed(inherited),
+            )?;
+            write!(out, " {}=", key)?;
+            out.reset()?;
+            if inherited {
+                out.set_color(ColorSpec::new().set_dimmed(true))?;
+            }
+            if out.supports_color() && self.config.hyperlink_keys.contains(key) {
+                write!(
+                    out,
+                    "\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\",
+                    hyperlink_target(value),
+                    value
+                )?;
+            } else {
+                write!(out, "{}", value)?;
+            }
+            if inherited {
+                out.reset()?;
+            }
+        }
+
+        write!(out, "{}", self.config.record_delimiter)
+    }
+
+    /// Returns a spec with `color` downgraded to the terminal's capability, or
+    /// an empty spec for the default color.
+    fn color_spec(&self, color: Option<Color>) -> ColorSpec {
+        color.map_or_else(ColorSpec::new, |color| {
+            self.config.color_support.foreground(color)
+        })
+    }
+}
+
+/// Color of the secondary parts of an entry: timestamps, timings, locations,
+/// and, by default, field keys.
+const MUTED: Color = Color::Rgb(128, 128, 128);
+
+/// Creates a zeroed counter for each sampled level in `config`.
+fn sample_counters(config: &Config) -> Arc<[AtomicU64]> {
+    config.sampling.iter().map(|_| AtomicU64::new(0)).collect()
+}
+
+/// Reports the outcome of writing an entry. Failures never reach the caller;
+/// they go to the error handler, if any.
+fn report_write_result(result: std::thread::Result<std::io::Result<()>>) {
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => report_error(&CcbError::Io(err)),
+        Err(_) => report_error(&CcbError::Panicked),
+    }
+}
+
+/// Opens `path` for appending, creating the file if needed.
+fn open_append(path: &Path) -> std::io::Result<std::fs::File> {
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+}
+
+/// Borrows owned field values so they can be passed to [`Logger::log`].
+fn borrow_fields<'a>(fields: &'a [(&'a str, String)]) -> Vec<(&'a str, &'a str)> {
+    fields
+        .iter()
+        .map(|(key, value)| (*key, value.as_str()))
+        .collect()
+}
+
+/// Returns the structured fields describing an I/O error.
+fn io_error_fields(err: &std::io::Error) -> Vec<(&'static str, String)> {
+    let mut fields = vec![("kind", format!("{:?}", err.kind()))];
+    if let Some(errno) = err.raw_os_error() {
+        fields.push(("errno", errno.to_string()));
+    }
+    fields.push(("error", err.to_string()));
+    fields
+}
+
+/// Returns the URL a hyperlinked field value should point to.
+///
+/// Values with a URL scheme are used as-is; anything else is treated as a
+/// file path and converted to a `file://` URL.
+fn hyperlink_target(value: &str) -> String {
+    if value.contains("://") {
+        return value.to_string();
+    }
+
+    let path = std::path::Path::new(value);
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .map(|dir| dir.join(path))
+            .unwrap_or_else(|_| path.to_path_buf())
+    };
+    format!("file://{}", path.display())
+}
+
+/// Details computed when an entry is emitted rather than when it is created.
+#[derive(Debug, Default)]
+struct Annotations {
+    /// Time since the previous emitted entry, if inter-entry timing is enabled.
+    elapsed: Option<Duration>,
+    /// Position among entries sharing the same microsecond, if enabled.
+    sequence: Option<u32>,
+    /// Whether a compact timestamp replaces the full one.
+    same_second: bool,
+    /// Field keys over the JSON key limit, written under `extra`.
+    json_overflow: Vec<String>,
+    /// Whether the entry is only rendered to a string, leaving shared output
+    /// state such as the CSV header untouched.
+    preview: bool,
+}
+
+/// Formats an inter-entry delta compactly, e.g. `+850µs`, `+12ms`, or `+1.50s`.
+fn format_elapsed(elapsed: Duration) -> String {
+    if elapsed < Duration::from_millis(1) && !elapsed.is_zero() {
+        format!("+{}µs", elapsed.as_micros())
+    } else if elapsed < Duration::from_secs(1) {
+        format!("+{}ms", elapsed.as_millis())
+    } else {
+        format!("+{:.2}s", elapsed.as_secs_f64())
+    }
+}
+
+impl Default for Logger {
+    /// Creates a logger with default configuration.
+    ///
+    /// Equivalent to calling `Logger::new()`.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global logger instance used by the logging macros.
+///
+/// This static instance allows the logging macros to function without requiring
+/// explicit logger parameters. It can be customized using `set_global_logger()`.
+static GLOBAL_LOGGER: Lazy<Arc<Mutex<Logger>>> = Lazy::new(|| Arc::new(Mutex::new(Logger::new())));
+
+/// Locks the global logger, recovering it if a thread panicked while holding
+/// the lock, so logging keeps working after a panic.
+///
+/// The logger is never left half-updated: it is only replaced or has its
+/// level set, both single assignments.
+fn lock_global_logger() -> MutexGuard<'static, Logger> {
+    GLOBAL_LOGGER.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The lowest severity rank the global logger acts on (see
+/// [`Logger::min_rank`]), so macros can skip disabled levels without locking.
+///
+/// Starts at the rank of `Info`, the default logger's level, and is updated
+/// by [`set_global_logger`].
+static GLOBAL_MAX_LEVEL: AtomicU16 = AtomicU16::new(20);
+
+/// Returns the rank used by the macros' fast path: the severity, with `Off`
+/// above every custom level.
+fn level_rank(level: Level) -> u16 {
+    match level {
+        Level::Off => u16::from(u8::MAX) + 1,
+        level => u16::from(level.severity()),
+    }
+}
+
+/// Returns whether a macro call at `level` could produce any output on this
+/// thread. Not part of the public API.
+///
+/// This only rejects levels that are certainly filtered, using an atomic load
+/// for the global logger instead of its lock; the logger still makes the
+/// final decision.
+#[doc(hidden)]
+pub fn __level_enabled(level: Level) -> bool {
+    let rank = level_rank(level);
+    THREAD_LOGGER
+        .try_with(|slot| match slot.try_borrow().as_deref() {
+            Ok(Some(logger)) => rank >= logger.min_rank(),
+            Ok(None) => rank >= GLOBAL_MAX_LEVEL.load(AtomicOrdering::Relaxed),
+            Err(_) => true,
+        })
+        .unwrap_or(true)
+}
+
+/// Returns whether a macro call at `level` from `module` passes the current
+/// logger's level filter. Not part of the public API; see [`enabled!`].
+#[doc(hidden)]
+pub fn __enabled(level: Level, module: &str) -> bool {
+    if !__level_enabled(level) {
+        return false;
+    }
+    let mut enabled = false;
+    with_current_logger(|logger| enabled = logger.is_enabled_in(Some(module), level));
+    enabled
+}
+
+/// Returns whether `text` is a plain identifier such as `user` or `peer_ip`.
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .is_some_and(|first| first == '_' || first.is_alphabetic())
+        && chars.all(|c| c == '_' || c.is_alphanumeric())
+}
+
+/// Fills the placeholders of a [`logf!`] template.
+///
+/// `{}` takes the next argument in order and `{name}` the argument with that
+/// key; `{{` and `}}` write literal braces. A placeholder without a matching
+/// argument is kept as written.
+fn interpolate(template: &str, args: &[(&str, String)]) -> String {
+    let mut message = String::with_capacity(template.len());
+    let mut next = args.iter();
+    let mut rest = template;
+    while let Some(index) = rest.find(['{', '}']) {
+        message.push_str(&rest[..index]);
+        let tail = &rest[index..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            message.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        let end = match tail.find('}') {
+            Some(end) if tail.starts_with('{') => end,
+            _ => {
+                message.push_str(&tail[..1]);
+                rest = &tail[1..];
+                continue;
+            }
+        };
+        let name = &tail[1..end];
+        let value = if name.is_empty() {
+            next.next()
+        } else {
+            args.iter().find(|(key, _)| *key == name)
+        };
+        match value {
+            Some((_, value)) => message.push_str(value),
+            None => message.push_str(&tail[..=end]),
+        }
+        rest = &tail[end + 1..];
+    }
+    message.push_str(rest);
+    message
+}
+
+/// Sets the global logger instance used by logging macros.
+///
+/// This function replaces the default global logger with a custom configured logger.
+/// After calling this, all macro invocations will use the new logger configuration.
+///
+/// # Arguments
+///
+/// * `logger` - The logger instance to set as the global logger
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{Logger, Level, set_global_logger, info};
+///
+/// let custom_logger = Logger::new()
+///     .with_level(Level::Debug)
+///     .with("app", "my-service");
+///
+/// set_global_logger(custom_logger);
+/// info!("This will use the custom logger configuration");
+/// ```
+pub fn set_global_logger(logger: Logger) {
+    let mut global = lock_global_logger();
+    GLOBAL_MAX_LEVEL.store(logger.min_rank(), AtomicOrdering::Relaxed);
+    *global = logger;
+}
+
+/// Changes the minimum level of the global logger in place.
+///
+/// Unlike rebuilding the logger and passing it to [`set_global_logger`], this
+/// keeps its context and every other setting, which makes it suitable for
+/// toggling debug output in a running daemon, for example from a `SIGUSR1`
+/// handler thread. Per-module levels and thread loggers are unaffected.
+///
+/// # Arguments
+///
+/// * `level` - The new minimum level
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{debug, global_logger, set_global_level, set_global_logger, Level, Logger};
+///
+/// let (logger, capture) = Logger::capturing();
+/// set_global_logger(logger.with_timestamp(false).with("app", "daemon"));
+///
+/// debug!("Hidden");
+/// set_global_level(Level::Debug);
+/// debug!("Shown");
+///
+/// assert_eq!(capture.lines(), ["DEBG Shown app=daemon"]);
+/// ```
+pub fn set_global_level(level: Level) {
+    let mut global = lock_global_logger();
+    global.config.level = level;
+    GLOBAL_MAX_LEVEL.store(global.min_rank(), AtomicOrdering::Relaxed);
+}
+
+/// Returns a clone of the current global logger.
+///
+/// This function provides access to the global logger instance, allowing you to
+/// inspect its configuration or use it directly instead of through macros.
+///
+/// # Returns
+///
+/// A cloned copy of the current global logger.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{global_logger, Level};
+///
+/// let logger = global_logger();
+/// logger.info("Direct logger usage", &[("source", "global")]);
+/// ```
+pub fn global_logger() -> Logger {
+    lock_global_logger().clone()
+}
+
+/// Executes a closure with access to the global logger.
+///
+/// This function provides thread-safe access to the global logger by acquiring
+/// a lock and executing the provided closure with the logger reference.
+///
+/// # Arguments
+///
+/// * `f` - A closure that receives a reference to the global logger
+///
+/// # Type Parameters
+///
+/// * `F` - The closure type that takes a `&Logger` parameter
+/// * `R` - The closure's result type
+///
 /// # Returns
 ///
-/// A cloned copy of the current global logger.
+/// The closure's result. This is always `Some`: a lock poisoned by a panic
+/// in another thread is recovered rather than skipping the closure.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{with_global_logger, Level};
+///
+/// let debug = with_global_logger(|logger| logger.is_enabled(Level::Debug));
+/// assert_eq!(debug, Some(false));
+/// ```
+pub fn with_global_logger<F, R>(f: F) -> Option<R>
+where
+    F: FnOnce(&Logger) -> R,
+{
+    let logger = lock_global_logger();
+    let _holding = HoldingGlobalLogger::enter();
+    Some(f(&logger))
+}
+
+thread_local! {
+    /// Set while this thread holds the global logger's lock in
+    /// [`with_global_logger`], so a panic hook knows not to lock it again.
+    static HOLDS_GLOBAL_LOGGER: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Marks the current thread as holding the global logger's lock until dropped,
+/// including when unwinding from a panic.
+struct HoldingGlobalLogger(bool);
+
+impl HoldingGlobalLogger {
+    fn enter() -> Self {
+        Self(HOLDS_GLOBAL_LOGGER.with(|flag| flag.replace(true)))
+    }
+}
+
+impl Drop for HoldingGlobalLogger {
+    fn drop(&mut self) {
+        HOLDS_GLOBAL_LOGGER.with(|flag| flag.set(self.0));
+    }
+}
+
+/// Writes out any entries the global logger holds back.
+///
+/// Call this before leaving with [`std::process::exit`], which skips
+/// destructors, so output from a logger set up with
+/// [`Logger::with_buffering`] isn't lost. Does nothing when the global logger
+/// isn't buffered.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{info, set_global_logger, Logger};
+///
+/// let (logger, capture) = Logger::capturing();
+/// set_global_logger(logger.with_timestamp(false).with_buffering(8192));
+///
+/// info!("Exiting");
+/// assert!(capture.lines().is_empty());
+///
+/// ccb::flush();
+/// assert_eq!(capture.lines(), ["INFO Exiting"]);
+/// ```
+pub fn flush() {
+    with_global_logger(Logger::flush);
+}
+
+thread_local! {
+    /// Per-thread logger that takes precedence over the global logger in macros.
+    static THREAD_LOGGER: RefCell<Option<Logger>> = const { RefCell::new(None) };
+}
+
+/// Installs a logger for the current thread.
+///
+/// While a thread logger is installed, the logging macros invoked on this thread
+/// use it instead of the global logger. Other threads are unaffected. This makes
+/// it possible to give each worker thread its own context without passing a
+/// logger around.
+///
+/// The macros resolve their logger in this order:
+///
+/// 1. The current thread's logger, if one is installed
+/// 2. The global logger
+///
+/// # Arguments
+///
+/// * `logger` - The logger to use for macro calls on the current thread
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{clear_thread_logger, info, set_thread_logger, Logger};
+///
+/// std::thread::spawn(|| {
+///     set_thread_logger(Logger::new().with("worker", "3"));
+///     info!("Job started"); // includes worker=3
+///     clear_thread_logger();
+/// })
+/// .join()
+/// .unwrap();
+/// ```
+pub fn set_thread_logger(logger: Logger) {
+    THREAD_LOGGER.with(|slot| *slot.borrow_mut() = Some(logger));
+}
+
+/// Removes the current thread's logger, if any.
+///
+/// Subsequent macro calls on this thread fall back to the global logger.
+pub fn clear_thread_logger() {
+    THREAD_LOGGER.with(|slot| *slot.borrow_mut() = None);
+}
+
+/// Executes a closure with the logger the macros would use on this thread.
+///
+/// This resolves to the thread logger installed with [`set_thread_logger`] if
+/// there is one, and to the global logger otherwise.
+///
+/// # Arguments
+///
+/// * `f` - A closure that receives a reference to the resolved logger
+pub fn with_current_logger<F>(f: F)
+where
+    F: FnOnce(&Logger),
+{
+    THREAD_LOGGER.with(|slot| match &*slot.borrow() {
+        Some(logger) => f(logger),
+        None => {
+            with_global_logger(f);
+        }
+    });
+}
+
+/// Registry of named loggers that macros can target with `target: "name"`.
+static NAMED_LOGGERS: Lazy<Mutex<HashMap<String, Logger>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Unknown target names that have already produced a fallback warning.
+static WARNED_TARGETS: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Registers a logger under a name so log calls can be routed to it.
+///
+/// Registered loggers are addressed with the `target:` prefix in the logging
+/// macros, mirroring the `log` crate's syntax; the entries are also tagged
+/// with the target (see [`LogEntry::target`]). Registering a name again
+/// replaces the previous logger.
+///
+/// # Arguments
+///
+/// * `name` - The name used to address the logger
+/// * `logger` - The logger to register
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{register_logger, warn, Logger};
+///
+/// register_logger("audit", Logger::new().with("channel", "audit"));
+/// warn!(target: "audit", "Permission changed", "user", "alice");
+/// ```
+pub fn register_logger<N: Into<String>>(name: N, logger: Logger) {
+    if let Ok(mut loggers) = NAMED_LOGGERS.lock() {
+        loggers.insert(name.into(), logger);
+    }
+}
+
+/// Removes a named logger, returning it if it was registered.
+pub fn unregister_logger(name: &str) -> Option<Logger> {
+    NAMED_LOGGERS.lock().ok()?.remove(name)
+}
+
+/// Returns a clone of the logger registered under `name`, if any.
+pub fn named_logger(name: &str) -> Option<Logger> {
+    NAMED_LOGGERS.lock().ok()?.get(name).cloned()
+}
+
+/// Executes a closure with the logger registered under `target`.
+///
+/// If no logger is registered under that name, the closure receives the
+/// current logger instead (see [`with_current_logger`]), and a warning is
+/// logged the first time each unknown target is used. To tag entries with a
+/// subsystem that has no logger of its own, use [`Logger::log_target`]
+/// instead, which doesn't consult the registry.
+///
+/// # Arguments
+///
+/// * `target` - The name of the registered logger
+/// * `f` - A closure that receives a reference to the resolved logger
+pub fn with_target_logger<F>(target: &str, f: F)
+where
+    F: FnOnce(&Logger),
+{
+    if let Some(logger) = named_logger(target) {
+        f(&logger);
+        return;
+    }
+
+    let first_use = WARNED_TARGETS
+        .lock()
+        .map(|mut warned| warned.insert(target.to_string()))
+        .unwrap_or(false);
+    with_current_logger(|logger| {
+        if first_use {
+            logger.warn(
+                "Unknown log target, falling back to the default logger",
+                &[("target", target)],
+            );
+        }
+        f(logger);
+    });
+}
+
+/// A handler for failures that occur while emitting entries.
+type ErrorHandler = Arc<dyn Fn(&CcbError) + Send + Sync>;
+
+/// The handler installed with [`set_error_handler`], if any.
+static ERROR_HANDLER: Lazy<Mutex<Option<ErrorHandler>>> = Lazy::new(|| Mutex::new(None));
+
+thread_local! {
+    /// Set while this thread runs the error handler, so failures caused by
+    /// the handler itself aren't reported back to it.
+    static IN_ERROR_HANDLER: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Installs a process-wide handler for logging failures.
+///
+/// Logging calls never return errors. When writing an entry fails, for
+/// example because of a broken pipe or a full disk, the error is passed to
+/// this handler instead. Without a handler such failures are ignored.
+///
+/// The handler may itself log; failures while it runs are not reported again.
+/// Installing a handler replaces the previous one.
+///
+/// # Arguments
+///
+/// * `handler` - A closure that receives each logging failure
 ///
 /// # Examples
 ///
 /// ```rust
-/// use ccb::{global_logger, Level};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use ccb::{set_error_handler, CcbError};
 ///
-/// let logger = global_logger();
-/// logger.info("Direct logger usage", &[("source", "global")]);
+/// static FAILURES: AtomicUsize = AtomicUsize::new(0);
+///
+/// set_error_handler(|err: &CcbError| {
+///     FAILURES.fetch_add(1, Ordering::Relaxed);
+///     eprintln!("logging failed: {}", err);
+/// });
 /// ```
-pub fn global_logger() -> Logger {
-    GLOBAL_LOGGER.lock().unwrap().clone()
+pub fn set_error_handler<F>(handler: F)
+where
+    F: Fn(&CcbError) + Send + Sync + 'static,
+{
+    if let Ok(mut slot) = ERROR_HANDLER.lock() {
+        *slot = Some(Arc::new(handler));
+    }
+}
+
+/// Passes a logging failure to the installed error handler, if any.
+fn report_error(err: &CcbError) {
+    if IN_ERROR_HANDLER.with(Cell::get) {
+        return;
+    }
+    let Some(handler) = ERROR_HANDLER.lock().ok().and_then(|slot| slot.clone()) else {
+        return;
+    };
+
+    let _running = RunningErrorHandler::enter();
+    handler(err);
+}
+
+/// Marks the current thread as running the error handler until dropped,
+/// including when unwinding from a panicking handler.
+struct RunningErrorHandler;
+
+impl RunningErrorHandler {
+    fn enter() -> Self {
+        IN_ERROR_HANDLER.with(|flag| flag.set(true));
+        Self
+    }
+}
+
+impl Drop for RunningErrorHandler {
+    fn drop(&mut self) {
+        IN_ERROR_HANDLER.with(|flag| flag.set(false));
+    }
+}
+
+/// Expands to the source text of a logging macro invocation when the `explain`
+/// feature is enabled, and to `None` otherwise.
+#[doc(hidden)]
+#[cfg(feature = "explain")]
+#[macro_export]
+macro_rules! __invocation {
+    ($name:ident, $($args:tt)*) => {
+        Some(concat!(stringify!($name), "!(", stringify!($($args)*), ")"))
+    };
+}
+
+/// Expands to the source text of a logging macro invocation when the `explain`
+/// feature is enabled, and to `None` otherwise.
+#[doc(hidden)]
+#[cfg(not(feature = "explain"))]
+#[macro_export]
+macro_rules! __invocation {
+    ($name:ident, $($args:tt)*) => {
+        None
+    };
+}
+
+/// Logs a message at trace level using the current logger (see [`with_current_logger`]).
+///
+/// This macro provides a convenient way to log trace-level messages with optional
+/// structured key-value pairs. The first argument is the message, and subsequent
+/// arguments are treated as alternating keys and values.
+///
+/// # Arguments
+///
+/// * `target: $target` - Optional target the entry is tagged with, also naming a
+///   registered logger to route to (see [`register_logger`]); an unregistered
+///   target warns once and uses the default logger
+/// * `$msg` - The log message (expression that implements `Into<String>`)
+/// * `$key`, `$value` - Optional alternating key-value pairs for structured logging;
+///   values may be any type implementing `Display`
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::trace;
+///
+/// trace!("Function entry");
+/// trace!("Processing item", "id", "12345", "type", "user");
+/// ```
+#[macro_export]
+macro_rules! trace {
+    (target: $target:expr, $msg:expr) => {{
+        let __target = $target;
+        $crate::with_target_logger(::std::convert::AsRef::<str>::as_ref(&__target), |logger| {
+            let invocation = $crate::__invocation!(trace, target: $target, $msg);
+            logger.__log_invocation($crate::Level::Trace, $msg, &[], ::std::module_path!(), ::std::option::Option::Some(::std::convert::AsRef::<str>::as_ref(&__target)), invocation);
+        });
+    }};
+    (target: $target:expr, $msg:expr, $($key:expr, $value:expr),* $(,)?) => {{
+        let __target = $target;
+        $crate::with_target_logger(::std::convert::AsRef::<str>::as_ref(&__target), |logger| {
+            let fields = &[$(($key, ::std::string::ToString::to_string(&$value))),*];
+            let invocation = $crate::__invocation!(trace, target: $target, $msg $(, $key, $value)*);
+            logger.__log_invocation($crate::Level::Trace, $msg, fields, ::std::module_path!(), ::std::option::Option::Some(::std::convert::AsRef::<str>::as_ref(&__target)), invocation);
+        });
+    }};
+    ($msg:expr) => {
+        if $crate::__level_enabled($crate::Level::Trace) {
+            $crate::with_current_logger(|logger| {
+                let invocation = $crate::__invocation!(trace, $msg);
+                logger.__log_invocation($crate::Level::Trace, $msg, &[], ::std::module_path!(), ::std::option::Option::None, invocation);
+            });
+        }
+    };
+    ($msg:expr, $($key:expr, $value:expr),* $(,)?) => {
+        if $crate::__level_enabled($crate::Level::Trace) {
+            $crate::with_current_logger(|logger| {
+                let fields = &[$(($key, ::std::string::ToString::to_string(&$value))),*];
+                let invocation = $crate::__invocation!(trace, $msg $(, $key, $value)*);
+                logger.__log_invocation($crate::Level::Trace, $msg, fields, ::std::module_path!(), ::std::option::Option::None, invocation);
+            });
+        }
+    };
+}
+
+/// Macro for debug level logging
+#[macro_export]
+macro_rules! debug {
+    (target: $target:expr, $msg:expr) => {{
+        let __target = $target;
+        $crate::with_target_logger(::std::convert::AsRef::<str>::as_ref(&__target), |logger| {
+            let invocation = $crate::__invocation!(debug, target: $target, $msg);
+            logger.__log_invocation($crate::Level::Debug, $msg, &[], ::std::module_path!(), ::std::option::Option::Some(::std::convert::AsRef::<str>::as_ref(&__target)), invocation);
+        });
+    }};
+    (target: $target:expr, $msg:expr, $($key:expr, $value:expr),* $(,)?) => {{
+        let __target = $target;
+        $crate::with_target_logger(::std::convert::AsRef::<str>::as_ref(&__target), |logger| {
+            let fields = &[$(($key, ::std::string::ToString::to_string(&$value))),*];
+            let invocation = $crate::__invocation!(debug, target: $target, $msg $(, $key, $value)*);
+            logger.__log_invocation($crate::Level::Debug, $msg, fields, ::std::module_path!(), ::std::option::Option::Some(::std::convert::AsRef::<str>::as_ref(&__target)), invocation);
+        });
+    }};
+    ($msg:expr) => {
+        if $crate::__level_enabled($crate::Level::Debug) {
+            $crate::with_current_logger(|logger| {
+                let invocation = $crate::__invocation!(debug, $msg);
+                logger.__log_invocation($crate::Level::Debug, $msg, &[], ::std::module_path!(), ::std::option::Option::None, invocation);
+            });
+        }
+    };
+    ($msg:expr, $($key:expr, $value:expr),* $(,)?) => {
+        if $crate::__level_enabled($crate::Level::Debug) {
+            $crate::with_current_logger(|logger| {
+                let fields = &[$(($key, ::std::string::ToString::to_string(&$value))),*];
+                let invocation = $crate::__invocation!(debug, $msg $(, $key, $value)*);
+                logger.__log_invocation($crate::Level::Debug, $msg, fields, ::std::module_path!(), ::std::option::Option::None, invocation);
+            });
+        }
+    };
+}
+
+/// Logs a message at info level using the current logger (see [`with_current_logger`]).
+///
+/// This macro provides a convenient way to log informational messages with optional
+/// structured key-value pairs. Info messages communicate general application flow
+/// and important events.
+///
+/// # Arguments
+///
+/// * `target: $target` - Optional target the entry is tagged with, also naming a
+///   registered logger to route to (see [`register_logger`]); an unregistered
+///   target warns once and uses the default logger
+/// * `$msg` - The log message (expression that implements `Into<String>`)
+/// * `$key`, `$value` - Optional alternating key-value pairs for structured logging;
+///   values may be any type implementing `Display`
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::info;
+///
+/// info!("Server started successfully");
+/// info!("User logged in", "user_id", "12345", "ip", "192.168.1.100");
+/// info!("Batch done", "count", 42, "ok", true);
+/// ```
+#[macro_export]
+macro_rules! info {
+    (target: $target:expr, $msg:expr) => {{
+        let __target = $target;
+        $crate::with_target_logger(::std::convert::AsRef::<str>::as_ref(&__target), |logger| {
+            let invocation = $crate::__invocation!(info, target: $target, $msg);
+            logger.__log_invocation($crate::Level::Info, $msg, &[], ::std::module_path!(), ::std::option::Option::Some(::std::convert::AsRef::<str>::as_ref(&__target)), invocation);
+        });
+    }};
+    (target: $target:expr, $msg:expr, $($key:expr, $value:expr),* $(,)?) => {{
+        let __target = $target;
+        $crate::with_target_logger(::std::convert::AsRef::<str>::as_ref(&__target), |logger| {
+            let fields = &[$(($key, ::std::string::ToString::to_string(&$value))),*];
+            let invocation = $crate::__invocation!(info, target: $target, $msg $(, $key, $value)*);
+            logger.__log_invocation($crate::Level::Info, $msg, fields, ::std::module_path!(), ::std::option::Option::Some(::std::convert::AsRef::<str>::as_ref(&__target)), invocation);
+        });
+    }};
+    ($msg:expr) => {
+        if $crate::__level_enabled($crate::Level::Info) {
+            $crate::with_current_logger(|logger| {
+                let invocation = $crate::__invocation!(info, $msg);
+                logger.__log_invocation($crate::Level::Info, $msg, &[], ::std::module_path!(), ::std::option::Option::None, invocation);
+            });
+        }
+    };
+    ($msg:expr, $($key:expr, $value:expr),* $(,)?) => {
+        if $crate::__level_enabled($crate::Level::Info) {
+            $crate::with_current_logger(|logger| {
+                let fields = &[$(($key, ::std::string::ToString::to_string(&$value))),*];
+                let invocation = $crate::__invocation!(info, $msg $(, $key, $value)*);
+                logger.__log_invocation($crate::Level::Info, $msg, fields, ::std::module_path!(), ::std::option::Option::None, invocation);
+            });
+        }
+    };
+}
+
+/// Logs a message at warn level using the current logger (see [`with_current_logger`]).
+///
+/// This macro provides a convenient way to log warning messages with optional
+/// structured key-value pairs. Warnings indicate potentially harmful situations
+/// that don't prevent continued operation.
+///
+/// # Arguments
+///
+/// * `target: $target` - Optional target the entry is tagged with, also naming a
+///   registered logger to route to (see [`register_logger`]); an unregistered
+///   target warns once and uses the default logger
+/// * `$msg` - The log message (expression that implements `Into<String>`)
+/// * `$key`, `$value` - Optional alternating key-value pairs for structured logging;
+///   values may be any type implementing `Display`
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::warn;
+///
+/// warn!("Configuration file not found, using defaults");
+/// warn!(target: "audit", "Permission changed", "user", "alice");
+/// warn!("High memory usage detected", "usage_percent", "87", "threshold", "80");
+/// ```
+#[macro_export]
+macro_rules! warn {
+    (target: $target:expr, $msg:expr) => {{
+        let __target = $target;
+        $crate::with_target_logger(::std::convert::AsRef::<str>::as_ref(&__target), |logger| {
+            let invocation = $crate::__invocation!(warn, target: $target, $msg);
+            logger.__log_invocation($crate::Level::Warn, $msg, &[], ::std::module_path!(), ::std::option::Option::Some(::std::convert::AsRef::<str>::as_ref(&__target)), invocation);
+        });
+    }};
+    (target: $target:expr, $msg:expr, $($key:expr, $value:expr),* $(,)?) => {{
+        let __target = $target;
+        $crate::with_target_logger(::std::convert::AsRef::<str>::as_ref(&__target), |logger| {
+            let fields = &[$(($key, ::std::string::ToString::to_string(&$value))),*];
+            let invocation = $crate::__invocation!(warn, target: $target, $msg $(, $key, $value)*);
+            logger.__log_invocation($crate::Level::Warn, $msg, fields, ::std::module_path!(), ::std::option::Option::Some(::std::convert::AsRef::<str>::as_ref(&__target)), invocation);
+        });
+    }};
+    ($msg:expr) => {
+        if $crate::__level_enabled($crate::Level::Warn) {
+            $crate::with_current_logger(|logger| {
+                let invocation = $crate::__invocation!(warn, $msg);
+                logger.__log_invocation($crate::Level::Warn, $msg, &[], ::std::module_path!(), ::std::option::Option::None, invocation);
+            });
+        }
+    };
+    ($msg:expr, $($key:expr, $value:expr),* $(,)?) => {
+        if $crate::__level_enabled($crate::Level::Warn) {
+            $crate::with_current_logger(|logger| {
+                let fields = &[$(($key, ::std::string::ToString::to_string(&$value))),*];
+                let invocation = $crate::__invocation!(warn, $msg $(, $key, $value)*);
+                logger.__log_invocation($crate::Level::Warn, $msg, fields, ::std::module_path!(), ::std::option::Option::None, invocation);
+            });
+        }
+    };
+}
+
+/// Logs a message at error level using the current logger (see [`with_current_logger`]).
+///
+/// This macro provides a convenient way to log error messages with optional
+/// structured key-value pairs. Error messages indicate failure conditions that
+/// may prevent correct application functioning.
+///
+/// # Arguments
+///
+/// * `target: $target` - Optional target the entry is tagged with, also naming a
+///   registered logger to route to (see [`register_logger`]); an unregistered
+///   target warns once and uses the default logger
+/// * `$msg` - The log message (expression that implements `Into<String>`)
+/// * `$key`, `$value` - Optional alternating key-value pairs for structured logging;
+///   values may be any type implementing `Display`
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::error;
+///
+/// error!("Failed to connect to database");
+/// error!("Authentication failed", "username", "alice", "reason", "invalid_password");
+/// ```
+#[macro_export]
+macro_rules! error {
+    (target: $target:expr, $msg:expr) => {{
+        let __target = $target;
+        $crate::with_target_logger(::std::convert::AsRef::<str>::as_ref(&__target), |logger| {
+            let invocation = $crate::__invocation!(error, target: $target, $msg);
+            logger.__log_invocation($crate::Level::Error, $msg, &[], ::std::module_path!(), ::std::option::Option::Some(::std::convert::AsRef::<str>::as_ref(&__target)), invocation);
+        });
+    }};
+    (target: $target:expr, $msg:expr, $($key:expr, $value:expr),* $(,)?) => {{
+        let __target = $target;
+        $crate::with_target_logger(::std::convert::AsRef::<str>::as_ref(&__target), |logger| {
+            let fields = &[$(($key, ::std::string::ToString::to_string(&$value))),*];
+            let invocation = $crate::__invocation!(error, target: $target, $msg $(, $key, $value)*);
+            logger.__log_invocation($crate::Level::Error, $msg, fields, ::std::module_path!(), ::std::option::Option::Some(::std::convert::AsRef::<str>::as_ref(&__target)), invocation);
+        });
+    }};
+    ($msg:expr) => {
+        if $crate::__level_enabled($crate::Level::Error) {
+            $crate::with_current_logger(|logger| {
+                let invocation = $crate::__invocation!(error, $msg);
+                logger.__log_invocation($crate::Level::Error, $msg, &[], ::std::module_path!(), ::std::option::Option::None, invocation);
+            });
+        }
+    };
+    ($msg:expr, $($key:expr, $value:expr),* $(,)?) => {
+        if $crate::__level_enabled($crate::Level::Error) {
+            $crate::with_current_logger(|logger| {
+                let fields = &[$(($key, ::std::string::ToString::to_string(&$value))),*];
+                let invocation = $crate::__invocation!(error, $msg $(, $key, $value)*);
+                logger.__log_invocation($crate::Level::Error, $msg, fields, ::std::module_path!(), ::std::option::Option::None, invocation);
+            });
+        }
+    };
+}
+
+/// Logs a message at a level chosen at runtime using the current logger (see
+/// [`with_current_logger`]).
+///
+/// This is the general form of [`trace!`] through [`error!`], for when the
+/// level is computed, for example to escalate a failure once retries are
+/// exhausted. The level expression is evaluated once.
+///
+/// # Arguments
+///
+/// * `target: $target` - Optional target the entry is tagged with, also naming a
+///   registered logger to route to (see [`register_logger`]); an unregistered
+///   target warns once and uses the default logger
+/// * `$level` - The [`Level`] to log at
+/// * `$msg` - The log message (expression that implements `Into<String>`)
+/// * `$key`, `$value` - Optional alternating key-value pairs for structured logging;
+///   values may be any type implementing `Display`
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{log, Level};
+///
+/// let attempt = 3;
+/// let level = if attempt >= 3 { Level::Error } else { Level::Warn };
+/// log!(level, "Upload failed", "attempt", attempt);
+/// log!(Level::Info, "Retrying");
+/// ```
+#[macro_export]
+macro_rules! log {
+    (target: $target:expr, $level:expr, $msg:expr) => {{
+        let __target = $target;
+        $crate::with_target_logger(::std::convert::AsRef::<str>::as_ref(&__target), |logger| {
+            let invocation = $crate::__invocation!(log, target: $target, $level, $msg);
+            logger.__log_invocation($level, $msg, &[], ::std::module_path!(), ::std::option::Option::Some(::std::convert::AsRef::<str>::as_ref(&__target)), invocation);
+        });
+    }};
+    (target: $target:expr, $level:expr, $msg:expr, $($key:expr, $value:expr),* $(,)?) => {{
+        let __target = $target;
+        $crate::with_target_logger(::std::convert::AsRef::<str>::as_ref(&__target), |logger| {
+            let fields = &[$(($key, ::std::string::ToString::to_string(&$value))),*];
+            let invocation = $crate::__invocation!(log, target: $target, $level, $msg $(, $key, $value)*);
+            logger.__log_invocation($level, $msg, fields, ::std::module_path!(), ::std::option::Option::Some(::std::convert::AsRef::<str>::as_ref(&__target)), invocation);
+        });
+    }};
+    ($level:expr, $msg:expr) => {{
+        let level: $crate::Level = $level;
+        if $crate::__level_enabled(level) {
+            $crate::with_current_logger(|logger| {
+                let invocation = $crate::__invocation!(log, $level, $msg);
+                logger.__log_invocation(level, $msg, &[], ::std::module_path!(), ::std::option::Option::None, invocation);
+            });
+        }
+    }};
+    ($level:expr, $msg:expr, $($key:expr, $value:expr),* $(,)?) => {{
+        let level: $crate::Level = $level;
+        if $crate::__level_enabled(level) {
+            $crate::with_current_logger(|logger| {
+                let fields = &[$(($key, ::std::string::ToString::to_string(&$value))),*];
+                let invocation = $crate::__invocation!(log, $level, $msg $(, $key, $value)*);
+                logger.__log_invocation(level, $msg, fields, ::std::module_path!(), ::std::option::Option::None, invocation);
+            });
+        }
+    }};
+}
+
+/// Returns whether a macro call at the given level would pass the current
+/// logger's level filter (see [`with_current_logger`]).
+///
+/// Like `log::log_enabled!`, this guards costly diagnostics. Per-module levels
+/// set with [`Logger::with_module_level`] apply to the calling module.
+///
+/// # Arguments
+///
+/// * `$level` - The [`Level`] to check
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{debug, enabled, Level};
+///
+/// if enabled!(Level::Debug) {
+///     let snapshot = format!("{:?}", std::env::vars().collect::<Vec<_>>());
+///     debug!("Environment", "vars", snapshot);
+/// }
+/// ```
+#[macro_export]
+macro_rules! enabled {
+    ($level:expr) => {
+        $crate::__enabled($level, ::std::module_path!())
+    };
+}
+
+/// Logs a message with `{}` placeholders filled from the arguments, keeping
+/// each argument as a structured field, using the current logger (see
+/// [`with_current_logger`]).
+///
+/// Unlike [`info!`] and the other level macros, which take alternating keys
+/// and values, this takes the arguments of the template directly. `{}` takes
+/// the next argument in order and `{name}` the argument with that key; `{{`
+/// and `}}` write literal braces. Arguments are either all positional or all
+/// named with `name = value`. A positional argument that is a plain identifier
+/// is kept under its own name, and any other expression under `arg0`, `arg1`,
+/// and so on by position.
+///
+/// # Arguments
+///
+/// * `$level` - The [`Level`] to log at
+/// * `$template` - The message template (expression that implements `AsRef<str>`)
+/// * `$value` or `$key = $value` - The arguments filling the placeholders;
+///   values may be any type implementing `Display`
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{clear_thread_logger, logf, set_thread_logger, Level, Logger};
+///
+/// let (logger, capture) = Logger::capturing();
+/// set_thread_logger(logger.with_timestamp(false).with_sorted_fields(true));
+///
+/// let user = "alice";
+/// let ip = "10.0.0.7";
+/// logf!(Level::Info, "user {} logged in from {}", user, ip);
+/// logf!(Level::Warn, "{count} failed attempts", count = 3);
+/// clear_thread_logger();
+///
+/// assert_eq!(
+///     capture.lines(),
+///     [
+///         "INFO user alice logged in from 10.0.0.7 ip=10.0.0.7 user=alice",
+///         "WARN 3 failed attempts count=3",
+///     ]
+/// );
+/// ```
+#[macro_export]
+macro_rules! logf {
+    ($level:expr, $template:expr, $($key:ident = $value:expr),+ $(,)?) => {{
+        let level: $crate::Level = $level;
+        if $crate::__level_enabled(level) {
+            $crate::with_current_logger(|logger| {
+                let args = &[$((::std::stringify!($key), ::std::string::ToString::to_string(&$value))),+];
+                let invocation = $crate::__invocation!(logf, $level, $template $(, $key = $value)+);
+                logger.__log_template(level, ::std::convert::AsRef::<str>::as_ref(&$template), args, ::std::module_path!(), invocation);
+            });
+        }
+    }};
+    ($level:expr, $template:expr $(, $value:expr)* $(,)?) => {{
+        let level: $crate::Level = $level;
+        if $crate::__level_enabled(level) {
+            $crate::with_current_logger(|logger| {
+                let args: &[(&str, ::std::string::String)] = &[$((::std::stringify!($value), ::std::string::ToString::to_string(&$value))),*];
+                let invocation = $crate::__invocation!(logf, $level, $template $(, $value)*);
+                logger.__log_template(level, ::std::convert::AsRef::<str>::as_ref(&$template), args, ::std::module_path!(), invocation);
+            });
+        }
+    }};
 }
 
-/// Executes a closure with access to the global logger.
-///
-/// This function provides thread-safe access to the global logger by acquiring
-/// a lock and executing the provided closure with the logger reference.
-///
-/// # Arguments
-///
-/// * `f` - A closure that receives a reference to the global logger
-///
-/// # Type Parameters
-///
-/// * `F` - The closure type that takes a `&Logger` parameter
-pub fn with_global_logger<F>(f: F)
-where
-    F: FnOnce(&Logger),
-{
-    if let Ok(logger) = GLOBAL_LOGGER.lock() {
-        f(&*logger);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use termcolor::Buffer;
+
+    /// Builds an entry with a fixed timestamp so rendered output is stable.
+    fn fixed_entry(level: Level, message: &str, fields: &[(&str, &str)]) -> LogEntry {
+        LogEntry {
+            level,
+            message: message.to_string(),
+            fields: fields
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            timestamp: Local.with_ymd_and_hms(2024, 1, 15, 14, 30, 25).unwrap()
+                + chrono::Duration::milliseconds(123),
+            location: None,
+            target: None,
+            trace: None,
+            inherited_keys: HashSet::new(),
+        }
+    }
+
+    /// Renders an entry through the logger's formatter into a string.
+    fn render_to_string(logger: &Logger, entry: &LogEntry, elapsed: Option<Duration>) -> String {
+        let mut buffer = if logger.config.use_colors {
+            Buffer::ansi()
+        } else {
+            Buffer::no_color()
+        };
+        let annotations = Annotations {
+            elapsed,
+            ..Annotations::default()
+        };
+        logger.render(entry, &annotations, &mut buffer).unwrap();
+        String::from_utf8(buffer.into_inner()).unwrap()
+    }
+
+    #[test]
+    /// Verifies that log levels are properly ordered by severity.
+    fn test_level_ordering() {
+        assert!(Level::Trace < Level::Debug);
+        assert!(Level::Debug < Level::Info);
+        assert!(Level::Info < Level::Warn);
+        assert!(Level::Warn < Level::Error);
+    }
+
+    #[test]
+    /// Verifies that custom levels order, compare, and render like built-ins.
+    fn test_custom_levels() {
+        const NOTICE: Level = Level::Custom(25, "NOTE", Color::Magenta);
+
+        assert!(Level::Info < NOTICE && NOTICE < Level::Warn);
+        assert_eq!(NOTICE.as_str(), "NOTE");
+        assert_eq!(NOTICE.color(), Color::Magenta);
+        assert_eq!(NOTICE, Level::Custom(25, "NOTE", Color::Cyan));
+        assert_ne!(NOTICE, Level::Custom(25, "NTCE", Color::Magenta));
+        assert_eq!(Level::Custom(20, "INFO", Color::Green), Level::Info);
+
+        let logger = Logger::new().with_level(NOTICE);
+        assert!(logger.build_entry(NOTICE, "msg", &[]).level >= logger.config.level);
+        assert!(Level::Info < logger.config.level);
+    }
+
+    #[test]
+    /// Tests that all log levels return the correct four-character string representation.
+    fn test_level_strings() {
+        assert_eq!(Level::Trace.as_str(), "TRCE");
+        assert_eq!(Level::Debug.as_str(), "DEBG");
+        assert_eq!(Level::Info.as_str(), "INFO");
+        assert_eq!(Level::Warn.as_str(), "WARN");
+        assert_eq!(Level::Error.as_str(), "ERRO");
+    }
+
+    #[test]
+    /// Verifies logger creation with default and custom configurations.
+    fn test_logger_creation() {
+        let logger = Logger::new();
+        assert_eq!(logger.config.level, Level::Info);
+
+        let logger = Logger::new().with_level(Level::Debug);
+        assert_eq!(logger.config.level, Level::Debug);
+    }
+
+    #[test]
+    /// Tests that context key-value pairs are properly stored and accessible.
+    fn test_logger_with_context() {
+        let logger = Logger::new()
+            .with("service", "test")
+            .with("version", "1.0.0");
+
+        assert_eq!(logger.context.get("service"), Some(&"test".to_string()));
+        assert_eq!(logger.context.get("version"), Some(&"1.0.0".to_string()));
+    }
+
+    #[test]
+    /// Verifies that custom configurations are properly applied to loggers.
+    fn test_logger_configuration() {
+        let config = Config {
+            level: Level::Debug,
+            use_colors: false,
+            show_timestamp: false,
+            ..Config::default()
+        };
+
+        let logger = Logger::with_config(config.clone());
+        assert_eq!(logger.config.level, Level::Debug);
+        assert!(!logger.config.use_colors);
+        assert!(!logger.config.show_timestamp);
+    }
+
+    /// Serializes tests that replace the global logger.
+    static GLOBAL_LOGGER_TESTS: Mutex<()> = Mutex::new(());
+
+    #[test]
+    /// Tests setting and retrieving the global logger instance.
+    fn test_global_logger() {
+        let _serial = GLOBAL_LOGGER_TESTS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let custom_logger = Logger::new()
+            .with_level(Level::Trace)
+            .with("global", "test");
+
+        set_global_logger(custom_logger);
+
+        let retrieved = global_logger();
+        assert_eq!(retrieved.config.level, Level::Trace);
+        assert_eq!(retrieved.context.get("global"), Some(&"test".to_string()));
+    }
+
+    #[test]
+    /// Verifies that logging through the global logger continues after a
+    /// thread panicked while holding its lock.
+    fn test_global_logger_poisoned() {
+        let _serial = GLOBAL_LOGGER_TESTS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let (logger, capture) = Logger::capturing();
+        set_global_logger(logger.with_timestamp(false));
+
+        let panicked = std::thread::spawn(|| {
+            with_global_logger(|_| panic!("poison the global logger"));
+        })
+        .join();
+        assert!(panicked.is_err());
+        assert!(GLOBAL_LOGGER.is_poisoned());
+
+        assert_eq!(
+            with_global_logger(|logger| logger.info("Still logging", &[])),
+            Some(())
+        );
+        set_global_level(Level::Debug);
+        crate::debug!("After panic");
+        assert_eq!(capture.lines(), ["INFO Still logging", "DEBG After panic"]);
+        set_global_logger(Logger::new());
+    }
+
+    #[test]
+    /// Verifies that the thread logger takes precedence and can be cleared.
+    fn test_thread_logger_resolution() {
+        std::thread::spawn(|| {
+            let worker_of = || {
+                let mut worker = None;
+                with_current_logger(|logger| worker = logger.context.get("worker").cloned());
+                worker
+            };
+
+            set_thread_logger(Logger::new().with("worker", "7"));
+            assert_eq!(worker_of(), Some("7".to_string()));
+
+            clear_thread_logger();
+            assert_eq!(worker_of(), None);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    /// Verifies routing to named loggers and the fallback for unknown targets.
+    fn test_target_logger_resolution() {
+        let channel_of = |target: &str| {
+            let mut channel = None;
+            with_target_logger(target, |logger| {
+                channel = logger.context.get("channel").cloned();
+            });
+            channel
+        };
+
+        register_logger("test-audit", Logger::new().with("channel", "audit"));
+        assert_eq!(channel_of("test-audit"), Some("audit".to_string()));
+        assert!(named_logger("test-audit").is_some());
+
+        assert!(unregister_logger("test-audit").is_some());
+        assert_eq!(channel_of("test-audit"), None);
+        assert!(WARNED_TARGETS.lock().unwrap().contains("test-audit"));
+    }
+
+    #[test]
+    /// Verifies that an unregistered macro target warns once, while
+    /// `log_target` tags entries without consulting the registry.
+    fn test_unknown_target_warning() {
+        let (logger, capture) = Logger::capturing();
+        let logger = logger.with_timestamp(false);
+        set_thread_logger(logger.clone());
+        warn!(target: "test-missing", "Routed", "key", "value");
+        error!(target: "test-missing", "Routed again");
+        clear_thread_logger();
+        logger.log_target(Level::Info, "test-db", "Connected", &[("pool", "4")]);
+
+        assert_eq!(
+            capture.lines(),
+            [
+                "WARN Unknown log target, falling back to the default logger target=test-missing",
+                "WARN test-missing: Routed key=value",
+                "ERRO test-missing: Routed again",
+                "INFO test-db: Connected pool=4",
+            ]
+        );
+    }
+
+    #[test]
+    /// Verifies the message, fields, and levels of `log_http` entries.
+    fn test_log_http() {
+        let (logger, capture) = Logger::capturing();
+        let logger = logger.with_timestamp(false).with_sorted_fields(true);
+        let status_level = |status: u16| match status {
+            500.. => Level::Error,
+            400..=499 => Level::Warn,
+            _ => Level::Info,
+        };
+        for (method, path, status, millis) in [
+            ("GET", "/api/users", 200, 42),
+            ("POST", "/api/login", 401, 7),
+            ("GET", "/api/report", 503, 1500),
+        ] {
+            logger.log_http(
+                status_level(status),
+                method,
+                path,
+                status,
+                Duration::from_millis(millis),
+            );
+        }
+
+        assert_eq!(
+            capture.lines(),
+            [
+                "INFO GET /api/users http.duration_ms=42 http.method=GET \
+                 http.path=/api/users http.status=200",
+                "WARN POST /api/login http.duration_ms=7 http.method=POST \
+                 http.path=/api/login http.status=401",
+                "ERRO GET /api/report http.duration_ms=1500 http.method=GET \
+                 http.path=/api/report http.status=503",
+            ]
+        );
+    }
+
+    #[test]
+    /// Verifies that a macro's target expression is evaluated only once.
+    fn test_target_evaluated_once() {
+        let (logger, capture) = Logger::capturing();
+        register_logger("test-once", logger.with_timestamp(false));
+        let calls = Cell::new(0);
+        let target = || {
+            calls.set(calls.get() + 1);
+            String::from("test-once")
+        };
+
+        info!(target: target(), "Routed", "n", 1);
+        log!(target: target(), Level::Warn, "Again");
+        assert_eq!(calls.get(), 2);
+        assert_eq!(
+            capture.lines(),
+            ["INFO test-once: Routed n=1", "WARN test-once: Again"]
+        );
+        unregister_logger("test-once");
+    }
+
+    #[test]
+    /// Ensures that all logging macros compile and execute without errors.
+    /// In a real testing environment, stderr output would be captured for verification.
+    fn test_macros_compile() {
+        // Test that macros compile without panicking
+        // In a real test environment, you might want to capture stderr
+        trace!("Test trace message");
+        debug!("Test debug message");
+        info!("Test info message");
+        warn!("Test warn message");
+        error!("Test error message");
+
+        trace!("Test with fields", "key1", "value1", "key2", "value2");
+        info!("User login", "user_id", "12345", "ip", "192.168.1.1");
+    }
+
+    #[test]
+    /// Verifies that LogEntry structures are created correctly with all required fields.
+    fn test_log_entry_creation() {
+        let _logger = Logger::new();
+        let now = Local::now();
+
+        let entry = LogEntry {
+            level: Level::Info,
+            message: "test message".to_string(),
+            fields: HashMap::new(),
+            timestamp: now,
+            location: None,
+            target: None,
+            trace: None,
+            inherited_keys: HashSet::new(),
+        };
+
+        assert_eq!(entry.level, Level::Info);
+        assert_eq!(entry.message, "test message");
+        assert!(entry.fields.is_empty());
+    }
+
+    #[test]
+    /// Tests that the logger properly sets the minimum log level for filtering.
+    fn test_level_filtering() {
+        let logger = Logger::new().with_level(Level::Warn);
+
+        // This test would need a way to capture output to verify filtering
+        // For now, we just test that the configuration is set correctly
+        assert_eq!(logger.config.level, Level::Warn);
+    }
+
+    #[test]
+    /// Verifies that color configuration can be enabled and disabled correctly.
+    fn test_colors_configuration() {
+        let logger_with_colors = Logger::new().with_colors(true);
+        let logger_without_colors = Logger::new().with_colors(false);
+
+        assert!(logger_with_colors.config.use_colors);
+        assert!(!logger_without_colors.config.use_colors);
+    }
+
+    #[test]
+    /// Tests that timestamp display can be configured independently.
+    fn test_timestamp_configuration() {
+        let logger_with_timestamp = Logger::new().with_timestamp(true);
+        let logger_without_timestamp = Logger::new().with_timestamp(false);
+
+        assert!(logger_with_timestamp.config.show_timestamp);
+        assert!(!logger_without_timestamp.config.show_timestamp);
+    }
+
+    #[test]
+    /// Validates that the Config::default() implementation provides sensible defaults.
+    fn test_default_config() {
+        let config = Config::default();
+        assert_eq!(config.level, Level::Info);
+        assert!(config.show_timestamp);
+        // use_colors depends on terminal detection, so we don't assert its value
+    }
+
+    #[test]
+    /// Ensures that logger methods can be chained together fluently.
+    fn test_method_chaining() {
+        let logger = Logger::new()
+            .with_level(Level::Trace)
+            .with_colors(false)
+            .with_timestamp(true)
+            .with("chain", "test")
+            .with("fluent", "api");
+
+        assert_eq!(logger.config.level, Level::Trace);
+        assert!(!logger.config.use_colors);
+        assert!(logger.config.show_timestamp);
+        assert_eq!(logger.context.len(), 2);
+    }
+
+    #[test]
+    /// Tests that level colors are assigned correctly for terminal output.
+    fn test_level_colors() {
+        assert_eq!(Level::Trace.color(), Color::Cyan);
+        assert_eq!(Level::Debug.color(), Color::Blue);
+        assert_eq!(Level::Info.color(), Color::Green);
+        assert_eq!(Level::Warn.color(), Color::Yellow);
+        assert_eq!(Level::Error.color(), Color::Red);
+    }
+
+    #[test]
+    /// Tests inter-entry delta tracking and its compact formatting.
+    fn test_inter_entry_timing() {
+        let logger = Logger::new();
+        assert_eq!(logger.elapsed_since_last_emit(), None);
+
+        let logger = logger.with_inter_entry_timing(true);
+        assert_eq!(logger.elapsed_since_last_emit(), Some(Duration::ZERO));
+        assert!(logger.clone().elapsed_since_last_emit().is_some());
+        assert!(logger.state.last_emit.lock().unwrap().is_some());
+
+        assert_eq!(format_elapsed(Duration::ZERO), "+0ms");
+        assert_eq!(format_elapsed(Duration::from_micros(850)), "+850µs");
+        assert_eq!(format_elapsed(Duration::from_millis(12)), "+12ms");
+        assert_eq!(format_elapsed(Duration::from_millis(1500)), "+1.50s");
+    }
+
+    #[test]
+    /// Snapshot of the plain pretty format with and without timestamps.
+    fn test_render_pretty_plain() {
+        let logger = Logger::new().with_colors(false);
+        let entry = fixed_entry(Level::Info, "User login", &[("user_id", "12345")]);
+
+        assert_eq!(
+            render_to_string(&logger, &entry, None),
+            "2024-01-15 14:30:25.123 INFO User login user_id=12345\n"
+        );
+        assert_eq!(
+            render_to_string(&logger.with_timestamp(false), &entry, None),
+            "INFO User login user_id=12345\n"
+        );
+    }
+
+    #[test]
+    /// Snapshot of the pretty format with an inter-entry delta.
+    fn test_render_pretty_elapsed() {
+        let logger = Logger::new().with_colors(false).with_timestamp(false);
+        let entry = fixed_entry(Level::Debug, "Cache hit", &[]);
+
+        assert_eq!(
+            render_to_string(&logger, &entry, Some(Duration::from_millis(12))),
+            "DEBG +12ms Cache hit\n"
+        );
+    }
+
+    #[test]
+    /// Snapshot of the colored pretty format, including ANSI escape codes.
+    fn test_render_pretty_colored() {
+        let logger = Logger::new()
+            .with_colors(true)
+            .with_color_support(ColorSupport::TrueColor)
+            .with_timestamp(false);
+        let entry = fixed_entry(Level::Error, "Failed", &[("code", "7")]);
+
+        assert_eq!(
+            render_to_string(&logger, &entry, None),
+            "\x1b[0m\x1b[1m\x1b[31mERRO \x1b[0mFailed\x1b[0m\x1b[38;2;128;128;128m code=\x1b[0m7\n"
+        );
+    }
+
+    #[test]
+    /// Snapshot of colored output downgraded for a 256-color terminal.
+    fn test_render_pretty_ansi256() {
+        let logger = Logger::new()
+            .with_colors(true)
+            .with_color_support(ColorSupport::Ansi256)
+            .with_timestamp(false);
+        let entry = fixed_entry(Level::Error, "Failed", &[("code", "7")]);
+
+        assert_eq!(
+            render_to_string(&logger, &entry, None),
+            "\x1b[0m\x1b[1m\x1b[31mERRO \x1b[0mFailed\x1b[0m\x1b[38;5;244m code=\x1b[0m7\n"
+        );
+    }
+
+    #[test]
+    /// Tests that entries are terminated by the configured record delimiter.
+    fn test_record_delimiter() {
+        let entry = fixed_entry(Level::Info, "line one\nline two", &[]);
+        let logger = Logger::new()
+            .with_colors(false)
+            .with_timestamp(false)
+            .with_record_delimiter("\0");
+        assert_eq!(
+            render_to_string(&logger, &entry, None),
+            "INFO line one\nline two\0"
+        );
+
+        let logger = logger.with_format(Format::Csv(CsvFormat::new(["user_id"])));
+        assert_eq!(
+            render_to_string(&logger, &entry, None),
+            "timestamp,level,message,user_id,extra\0\
+             2024-01-15 14:30:25.123,INFO,\"line one\nline two\",,\0"
+        );
+
+        let logger = logger
+            .with_record_delimiter("\r\n")
+            .with_record_delimiter("");
+        assert_eq!(logger.config.record_delimiter, "\r\n");
+
+        let logger = Logger::new()
+            .with_colors(false)
+            .with_timestamp(false)
+            .with_line_terminator("\r\n")
+            .with_line_terminator("");
+        assert_eq!(
+            render_to_string(&logger, &entry, None),
+            "INFO line one\nline two\r\n"
+        );
+    }
+
+    #[test]
+    /// Tests that per-level timestamp settings override the global toggle.
+    fn test_level_timestamps() {
+        let logger = Logger::new()
+            .with_colors(false)
+            .with_level_timestamp(Level::Debug, false);
+        let debug = fixed_entry(Level::Debug, "Polling", &[]);
+        let warn = fixed_entry(Level::Warn, "Slow", &[]);
+
+        assert_eq!(render_to_string(&logger, &debug, None), "DEBG Polling\n");
+        assert_eq!(
+            render_to_string(&logger, &warn, None),
+            "2024-01-15 14:30:25.123 WARN Slow\n"
+        );
+
+        let logger = logger
+            .with_timestamp(false)
+            .with_level_timestamp(Level::Warn, true);
+        assert_eq!(render_to_string(&logger, &debug, None), "DEBG Polling\n");
+        assert!(render_to_string(&logger, &warn, None).starts_with("2024-01-15"));
+    }
+
+    #[test]
+    /// Snapshot of an entry rendered through a composite of two formats.
+    fn test_render_composite() {
+        let composite = CompositeFormat::new([
+            Format::Pretty,
+            Format::Csv(CsvFormat::new(["user_id"]).with_overflow_column(false)),
+        ])
+        .with_separator("# ");
+        let logger = Logger::new()
+            .with_colors(false)
+            .with_format(Format::Composite(composite));
+        let entry = fixed_entry(Level::Info, "User login", &[("user_id", "12345")]);
+
+        assert_eq!(
+            render_to_string(&logger, &entry, None),
+            "2024-01-15 14:30:25.123 INFO User login user_id=12345\n\
+             # timestamp,level,message,user_id\n\
+             2024-01-15 14:30:25.123,INFO,User login,12345\n"
+        );
+    }
+
+    #[test]
+    /// Snapshot of the JSON format, including escaping and key collisions.
+    fn test_render_json() {
+        let logger = Logger::new().with_colors(true).with_format(Format::Json);
+        let entry = fixed_entry(
+            Level::Warn,
+            "Disk \"sda\" almost full",
+            &[
+                ("path", "C:\\data"),
+                ("level", "high"),
+                ("note", "line1\nline2\u{1}"),
+            ],
+        );
+        let timestamp = entry.timestamp.format(format::ISO_TIMESTAMP_FORMAT);
+        assert_eq!(
+            render_to_string(&logger, &entry, None),
+            format!(
+                "{{\"timestamp\":\"{}\",\"level\":\"warn\",\"message\":\"Disk \\\"sda\\\" almost full\",\
+                 \"fields.level\":\"high\",\"note\":\"line1\\nline2\\u0001\",\"path\":\"C:\\\\data\"}}\n",
+                timestamp
+            )
+        );
+    }
+
+    #[test]
+    /// Verifies nesting of dotted keys, including leaf/prefix conflicts and
+    /// reserved top-level keys.
+    fn test_render_nested_json() {
+        let logger = Logger::new()
+            .with_format(Format::Json)
+            .with_nested_json(true);
+        let entry = fixed_entry(
+            Level::Info,
+            "Request",
+            &[
+                ("http.status", "200"),
+                ("http.method", "GET"),
+                ("http", "plain"),
+                ("db.pool.size", "4"),
+                ("level.sub", "x"),
+            ],
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&render_to_string(&logger, &entry, None)).unwrap();
+        assert_eq!(
+            json["http"],
+            serde_json::json!({"method": "GET", "status": "200"})
+        );
+        assert_eq!(json["db"]["pool"]["size"], "4");
+        assert_eq!(json["level"], "info");
+        assert_eq!(json["fields"]["level"]["sub"], "x");
+    }
+
+    #[test]
+    /// Verifies that a trace context renders in its dedicated position in
+    /// each format, and that colliding fields are moved aside.
+    fn test_render_trace_context() {
+        let logger = Logger::new()
+            .with_colors(false)
+            .with_timestamp(false)
+            .with_trace_context("t1", "s1");
+        let entry = logger.build_entry(Level::Info, "Charged", &[("span_id", "local")]);
+
+        assert_eq!(
+            render_to_string(&logger, &entry, None),
+            "INFO t1/s1 Charged span_id=local\n"
+        );
+        let json = render_to_string(&logger.clone().with_format(Format::Json), &entry, None);
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["trace_id"], "t1");
+        assert_eq!(json["span_id"], "s1");
+        assert_eq!(json["fields.span_id"], "local");
+        let logfmt = render_to_string(&logger.with_format(Format::Logfmt), &entry, None);
+        assert!(logfmt.contains(" msg=Charged trace_id=t1 span_id=s1 fields.span_id=local"));
+
+        // Without a trace context, `span_id` is an ordinary field
+        let plain = Logger::new().with_format(Format::Json);
+        let entry = plain.build_entry(Level::Info, "Span", &[("span_id", "x")]);
+        assert!(render_to_string(&plain, &entry, None).contains(r#""span_id":"x""#));
+    }
+
+    #[test]
+    /// Snapshot of the logfmt format rendered through a logger.
+    fn test_render_logfmt() {
+        let logger = Logger::new()
+            .with_colors(true)
+            .with_format(Format::Logfmt)
+            .with("service", "auth");
+        let entry = logger.build_entry(Level::Info, "User login", &[("user", "alice")]);
+
+        let timestamp = entry.timestamp.format(format::ISO_TIMESTAMP_FORMAT);
+        assert_eq!(
+            render_to_string(&logger, &entry, None),
+            format!(
+                "level=info ts={} msg=\"User login\" service=auth user=alice\n",
+                timestamp
+            )
+        );
+    }
+
+    #[test]
+    /// Snapshot of the CSV format rendered through a logger.
+    fn test_render_csv() {
+        let logger = Logger::new().with_format(Format::Csv(CsvFormat::new(["user_id"])));
+        let entry = fixed_entry(Level::Info, "User login", &[("user_id", "12345")]);
+
+        assert_eq!(
+            render_to_string(&logger, &entry, None),
+            "timestamp,level,message,user_id,extra\n\
+             2024-01-15 14:30:25.123,INFO,User login,12345,\n"
+        );
+    }
+
+    #[test]
+    /// Verifies that the caller's location is captured only when enabled.
+    fn test_location_capture() {
+        let logger = Logger::new();
+        assert!(logger
+            .build_entry(Level::Info, "msg", &[])
+            .location
+            .is_none());
+
+        let logger = logger.with_location(true);
+        let entry = logger.build_entry(Level::Info, "msg", &[]);
+        let location = entry.location.unwrap();
+        assert_eq!(location.file(), file!());
+        assert_eq!(location.line(), line!() - 3);
+    }
+
+    #[test]
+    /// Tests closed and open-ended active windows.
+    fn test_active_window() {
+        let now = Local::now();
+        let hour = chrono::Duration::hours(1);
+
+        assert!(Logger::new().is_in_active_window(now));
+
+        let closed = Logger::new().with_active_window(Some(now - hour), Some(now + hour));
+        assert!(closed.is_in_active_window(now));
+        assert!(!closed.is_in_active_window(now + hour));
+        assert!(!closed.is_in_active_window(now - hour * 2));
+
+        let from = Logger::new().with_active_window(Some(now), None);
+        assert!(from.is_in_active_window(now + hour * 1000));
+        assert!(!from.is_in_active_window(now - hour));
+
+        let until = Logger::new().with_active_window(None, Some(now));
+        assert!(until.is_in_active_window(now - hour * 1000));
+        assert!(!until.is_in_active_window(now));
     }
-}
 
-/// Logs a message at trace level using the global logger.
-///
-/// This macro provides a convenient way to log trace-level messages with optional
-/// structured key-value pairs. The first argument is the message, and subsequent
-/// arguments are treated as alternating keys and values.
-///
-/// # Arguments
-///
-/// * `$msg` - The log message (expression that implements `Into<String>`)
-/// * `$key`, `$value` - Optional alternating key-value pairs for structured logging
-///
-/// # Examples
-///
-/// ```rust
-/// use ccb::trace;
-///
-/// trace!("Function entry");
-/// trace!("Processing item", "id", 12345, "type", "user");
-/// ```
-#[macro_export]
-macro_rules! trace {
-    ($msg:expr) => {
-        $crate::with_global_logger(|logger| logger.trace($msg, &[]));
-    };
-    ($msg:expr, $($key:expr, $value:expr),* $(,)?) => {
-        $crate::with_global_logger(|logger| {
-            let fields = &[$(($key, $value)),*];
-            logger.trace($msg, fields);
-        });
-    };
-}
+    #[test]
+    /// Snapshot of the CI preset: no color, no timestamp, level label kept.
+    fn test_ci_preset() {
+        let logger = Logger::ci_preset();
+        assert!(!logger.config.use_colors);
+        assert!(!logger.config.show_timestamp);
 
-/// Macro for debug level logging
-#[macro_export]
-macro_rules! debug {
-    ($msg:expr) => {
-        $crate::with_global_logger(|logger| logger.debug($msg, &[]));
-    };
-    ($msg:expr, $($key:expr, $value:expr),* $(,)?) => {
-        $crate::with_global_logger(|logger| {
-            let fields = &[$(($key, $value)),*];
-            logger.debug($msg, fields);
-        });
-    };
-}
+        let entry = fixed_entry(Level::Warn, "Flaky test", &[("name", "io")]);
+        assert_eq!(
+            render_to_string(&logger, &entry, None),
+            "WARN Flaky test name=io\n"
+        );
+    }
 
-/// Logs a message at info level using the global logger.
-///
-/// This macro provides a convenient way to log informational messages with optional
-/// structured key-value pairs. Info messages communicate general application flow
-/// and important events.
-///
-/// # Arguments
-///
-/// * `$msg` - The log message (expression that implements `Into<String>`)
-/// * `$key`, `$value` - Optional alternating key-value pairs for structured logging
-///
-/// # Examples
-///
-/// ```rust
-/// use ccb::info;
-///
-/// info!("Server started successfully");
-/// info!("User logged in", "user_id", "12345", "ip", "192.168.1.100");
-/// ```
-#[macro_export]
-macro_rules! info {
-    ($msg:expr) => {
-        $crate::with_global_logger(|logger| logger.info($msg, &[]));
-    };
-    ($msg:expr, $($key:expr, $value:expr),* $(,)?) => {
-        $crate::with_global_logger(|logger| {
-            let fields = &[$(($key, $value)),*];
-            logger.info($msg, fields);
-        });
-    };
-}
+    #[test]
+    /// Ensures that log_result returns the result it was given.
+    fn test_log_result_passthrough() {
+        let logger = Logger::new().with_level(Level::Error);
 
-/// Logs a message at warn level using the global logger.
-///
-/// This macro provides a convenient way to log warning messages with optional
-/// structured key-value pairs. Warnings indicate potentially harmful situations
-/// that don't prevent continued operation.
-///
-/// # Arguments
-///
-/// * `$msg` - The log message (expression that implements `Into<String>`)
-/// * `$key`, `$value` - Optional alternating key-value pairs for structured logging
-///
-/// # Examples
-///
-/// ```rust
-/// use ccb::warn;
-///
-/// warn!("Configuration file not found, using defaults");
-/// warn!("High memory usage detected", "usage_percent", 87, "threshold", 80);
-/// ```
-#[macro_export]
-macro_rules! warn {
-    ($msg:expr) => {
-        $crate::with_global_logger(|logger| logger.warn($msg, &[]));
-    };
-    ($msg:expr, $($key:expr, $value:expr),* $(,)?) => {
-        $crate::with_global_logger(|logger| {
-            let fields = &[$(($key, $value)),*];
-            logger.warn($msg, fields);
+        assert_eq!(
+            logger.log_result(Level::Info, "ok", Ok::<_, String>(3)),
+            Ok(3)
+        );
+        assert_eq!(
+            logger.log_result(Level::Info, "fail", Err::<u8, _>("boom")),
+            Err("boom")
+        );
+    }
+
+    #[test]
+    /// Tests that inherited context keys are tracked only when enabled.
+    fn test_field_origin_tracking() {
+        let logger = Logger::new().with("service", "auth").with("env", "prod");
+        let entry = logger.build_entry(Level::Info, "msg", &[("user", "alice")]);
+        assert!(entry.inherited_keys.is_empty());
+
+        let logger = logger.with_distinguish_origin(true);
+        let entry = logger.build_entry(Level::Info, "msg", &[("env", "dev"), ("user", "a")]);
+        assert_eq!(entry.inherited_keys, HashSet::from(["service".to_string()]));
+        assert_eq!(entry.fields.get("env"), Some(&"dev".to_string()));
+    }
+
+    #[test]
+    /// Snapshot of hyperlinked fields with and without colors.
+    fn test_render_hyperlinks() {
+        let logger = Logger::new()
+            .with_timestamp(false)
+            .with_hyperlink_keys(["url"]);
+        let entry = fixed_entry(Level::Info, "See", &[("url", "https://example.com")]);
+
+        assert_eq!(
+            render_to_string(&logger.clone().with_colors(false), &entry, None),
+            "INFO See url=https://example.com\n"
+        );
+        assert!(render_to_string(&logger.with_colors(true), &entry, None)
+            .contains("\x1b]8;;https://example.com\x1b\\https://example.com\x1b]8;;\x1b\\"));
+
+        assert_eq!(
+            hyperlink_target("/var/log/app.log"),
+            "file:///var/log/app.log"
+        );
+        assert!(hyperlink_target("app.log").ends_with("/app.log"));
+    }
+
+    #[test]
+    /// Snapshot of deterministic output with several fields.
+    fn test_render_deterministic() {
+        let logger = Logger::new()
+            .with_colors(true)
+            .with_inter_entry_timing(true)
+            .with_deterministic(true);
+        let entry = fixed_entry(Level::Info, "Done", &[("c", "3"), ("a", "1"), ("b", "2")]);
+
+        assert!(!logger.config.use_colors);
+        assert!(!logger.config.inter_entry_timing);
+        assert_eq!(
+            render_to_string(&logger, &entry, None),
+            "INFO Done a=1 b=2 c=3\n"
+        );
+    }
+
+    #[test]
+    /// Tests the fields extracted from OS and custom I/O errors.
+    fn test_io_error_fields() {
+        let os_error = std::io::Error::from_raw_os_error(2);
+        let fields = io_error_fields(&os_error);
+        assert_eq!(fields[0], ("kind", "NotFound".to_string()));
+        assert_eq!(fields[1], ("errno", "2".to_string()));
+        assert_eq!(fields[2], ("error", os_error.to_string()));
+
+        let custom = std::io::Error::new(std::io::ErrorKind::InvalidData, "bad header");
+        assert_eq!(
+            io_error_fields(&custom),
+            vec![
+                ("kind", "InvalidData".to_string()),
+                ("error", "bad header".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    /// Tests that the sequence counter increments within a microsecond and resets after.
+    fn test_timestamp_sequence() {
+        let logger = Logger::new();
+        let first = fixed_entry(Level::Info, "a", &[]);
+        assert_eq!(logger.timestamp_sequence(&first), None);
+
+        let logger = logger.with_timestamp_sequence(true).with_colors(false);
+        let mut later = first.clone();
+        later.timestamp += chrono::Duration::microseconds(1);
+
+        assert_eq!(logger.timestamp_sequence(&first), Some(0));
+        assert_eq!(logger.timestamp_sequence(&first), Some(1));
+        assert_eq!(logger.clone().timestamp_sequence(&first), Some(2));
+        assert_eq!(logger.timestamp_sequence(&later), Some(0));
+
+        let mut buffer = Buffer::no_color();
+        logger
+            .render(&first, &logger.annotate(&first), &mut buffer)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(buffer.into_inner()).unwrap(),
+            "2024-01-15 14:30:25.123#0 INFO a\n"
+        );
+    }
+
+    #[test]
+    /// Verifies that previewing a CSV entry leaves the header for the real
+    /// output.
+    fn test_format_entry_csv_header() {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let logger = Logger::new()
+            .with_writer(output.clone())
+            .with_format(Format::Csv(CsvFormat::new(["port"])));
+        let entry = fixed_entry(Level::Info, "Ready", &[]);
+
+        let header = "timestamp,level,message,port,extra\n";
+        assert!(logger.format_entry(&entry).starts_with(header));
+        assert!(logger.format_entry(&entry).starts_with(header));
+
+        logger.info("Ready", &[("port", "80")]);
+        logger.info("Ready", &[("port", "81")]);
+        let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        assert!(output.starts_with(header));
+        assert_eq!(output.matches(header).count(), 1);
+        assert!(!logger.format_entry(&entry).contains(header));
+    }
+
+    #[test]
+    /// Verifies plain and colored string rendering independent of color settings.
+    fn test_format_entry() {
+        let logger = Logger::new()
+            .with_colors(false)
+            .with_timestamp(false)
+            .with_color_support(ColorSupport::Basic);
+        let entry = fixed_entry(Level::Warn, "Slow", &[]);
+
+        assert_eq!(logger.format_entry(&entry), "WARN Slow\n");
+        assert_eq!(
+            logger.format_entry_colored(&entry),
+            "\x1b[0m\x1b[1m\x1b[33mWARN \x1b[0mSlow\n"
+        );
+
+        let dumb = logger.with_color_support(ColorSupport::None);
+        assert_eq!(
+            dumb.format_entry_colored(&entry),
+            "\x1b[0m\x1b[1m\x1b[33mWARN \x1b[0mSlow\n"
+        );
+    }
+
+    #[test]
+    /// Tests that errors within a window are suppressed and summarized afterwards.
+    fn test_error_cascade_suppression() {
+        let logger = Logger::new();
+        assert_eq!(logger.check_error_cascade(Level::Error), (true, 0));
+        assert_eq!(logger.check_error_cascade(Level::Error), (true, 0));
+
+        let logger = Logger::new().with_error_cascade_suppression(Duration::from_millis(50));
+        assert_eq!(logger.check_error_cascade(Level::Error), (true, 0));
+        assert_eq!(logger.check_error_cascade(Level::Error), (false, 0));
+        assert_eq!(logger.check_error_cascade(Level::Warn), (true, 0));
+        assert_eq!(logger.check_error_cascade(Level::Error), (false, 0));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(logger.check_error_cascade(Level::Info), (true, 2));
+        assert_eq!(logger.check_error_cascade(Level::Error), (true, 0));
+
+        let logger = Logger::new()
+            .with_error_cascade_suppression(Duration::from_secs(60))
+            .with_cascade_includes_warnings(true);
+        assert_eq!(logger.check_error_cascade(Level::Warn), (true, 0));
+        assert_eq!(logger.check_error_cascade(Level::Error), (false, 0));
+    }
+
+    #[test]
+    #[cfg(feature = "metrics")]
+    /// Verifies that emitted entries are counted by level and filtered ones aren't.
+    fn test_metrics_counts_by_level() {
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let logger = Logger::new().with_level(Level::Warn).with_metrics(true);
+
+        metrics::with_local_recorder(&recorder, || {
+            logger.info("Filtered", &[]);
+            logger.warn("Slow", &[]);
+            logger.error("Failed", &[]);
+            logger.error("Failed again", &[]);
         });
-    };
-}
 
-/// Logs a message at error level using the global logger.
-///
-/// This macro provides a convenient way to log error messages with optional
-/// structured key-value pairs. Error messages indicate failure conditions that
-/// may prevent correct application functioning.
-///
-/// # Arguments
-///
-/// * `$msg` - The log message (expression that implements `Into<String>`)
-/// * `$key`, `$value` - Optional alternating key-value pairs for structured logging
-///
-/// # Examples
-///
-/// ```rust
-/// use ccb::error;
-///
-/// error!("Failed to connect to database");
-/// error!("Authentication failed", "username", "alice", "reason", "invalid_password");
-/// ```
-#[macro_export]
-macro_rules! error {
-    ($msg:expr) => {
-        $crate::with_global_logger(|logger| logger.error($msg, &[]));
-    };
-    ($msg:expr, $($key:expr, $value:expr),* $(,)?) => {
-        $crate::with_global_logger(|logger| {
-            let fields = &[$(($key, $value)),*];
-            logger.error($msg, fields);
+        let mut counts: Vec<_> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| {
+                let level = key.key().labels().next().unwrap().value().to_string();
+                (level, value)
+            })
+            .collect();
+        counts.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            counts,
+            vec![
+                ("ERRO".to_string(), DebugValue::Counter(2)),
+                ("WARN".to_string(), DebugValue::Counter(1)),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "explain")]
+    /// Verifies that macros capture their source text only with the feature.
+    fn test_macro_invocation_text() {
+        let user = "alice";
+        assert_eq!(
+            __invocation!(info, target: "auth", "Login", "user", user),
+            Some(r#"info!(target: "auth", "Login", "user", user)"#)
+        );
+
+        let logger = Logger::new().with_level(Level::Error).with_explain(true);
+        set_thread_logger(logger);
+        error!("Failed", "user", user);
+        clear_thread_logger();
+    }
+
+    #[test]
+    #[cfg(feature = "chrono-tz")]
+    /// Tests that timestamps are rendered in the configured named timezone.
+    fn test_named_timezone() {
+        let entry = fixed_entry(Level::Info, "Tick", &[]);
+        let logger = Logger::new()
+            .with_colors(false)
+            .with_timezone(chrono_tz::Asia::Tokyo);
+
+        let expected = entry
+            .timestamp
+            .with_timezone(&chrono::FixedOffset::east_opt(9 * 3600).unwrap())
+            .format(format::TIMESTAMP_FORMAT);
+        assert_eq!(
+            logger.format_entry(&entry),
+            format!("{} INFO Tick\n", expected)
+        );
+    }
+
+    #[test]
+    /// Tests each unknown-key policy, including validation of context keys.
+    fn test_allowed_keys_policies() {
+        let base = Logger::new()
+            .with_level(Level::Error)
+            .with("service", "auth")
+            .with_allowed_keys(["user"]);
+        let fields = [("user", "alice"), ("token", "x")];
+
+        let logger = base.clone().with_unknown_key_policy(KeyPolicy::Drop);
+        let mut entry = logger.build_entry(Level::Info, "msg", &fields);
+        assert_eq!(logger.enforce_allowed_keys(&mut entry), Ok(()));
+        assert_eq!(entry.fields.keys().collect::<Vec<_>>(), vec!["user"]);
+
+        let logger = base.clone().with_unknown_key_policy(KeyPolicy::Error);
+        let mut entry = logger.build_entry(Level::Info, "msg", &fields);
+        assert_eq!(
+            logger.enforce_allowed_keys(&mut entry),
+            Err(vec!["service".to_string(), "token".to_string()])
+        );
+
+        let mut entry = base.build_entry(Level::Info, "msg", &fields);
+        assert_eq!(base.enforce_allowed_keys(&mut entry), Ok(()));
+        assert_eq!(entry.fields.len(), 3);
+        assert!(base.state.warned_keys.lock().unwrap().contains("token"));
+    }
+
+    #[test]
+    /// Tests that a timer can be completed after the logger that started it is
+    /// gone, logging the extra fields and the elapsed time at its level.
+    fn test_start_timer() {
+        let (logger, capture) = Logger::capturing();
+        let logger = logger.with_timestamp(false).with_sorted_fields(true);
+        let done = logger.start_timer(Level::Info, "render");
+        drop(logger);
+
+        let frames = 60.to_string();
+        done(&[("frames", &frames)]);
+
+        let lines = capture.lines();
+        assert_eq!(lines.len(), 1);
+        assert!(
+            lines[0].starts_with("INFO render elapsed_ms="),
+            "{}",
+            lines[0]
+        );
+        assert!(lines[0].ends_with(" frames=60"), "{}", lines[0]);
+    }
+
+    #[test]
+    /// Snapshot of the configuration summary for a customized logger.
+    fn test_describe() {
+        let logger = Logger::new()
+            .with_level(Level::Warn)
+            .with_colors(false)
+            .with_color_support(ColorSupport::Ansi256)
+            .with_level_timestamp(Level::Debug, false)
+            .with_format(Format::Csv(CsvFormat::new(["user_id"])))
+            .with_allowed_keys(["user_id", "ip"])
+            .with("service", "auth")
+            .with("env", "prod");
+        let description = logger.describe();
+
+        for line in [
+            "level: WARN",
+            "format: csv (columns: user_id; extra column: on)",
+            "colors: off",
+            "color support: Ansi256",
+            "level timestamps: DEBG=off",
+            "error cascade window: off",
+            "record delimiter: \"\\n\"",
+            "allowed keys: ip, user_id",
+            "context: env=prod, service=auth",
+        ] {
+            assert!(
+                description.lines().any(|l| l == line),
+                "missing {:?} in\n{}",
+                line,
+                description
+            );
+        }
+    }
+
+    #[test]
+    /// Verifies that only consecutive entries with the same tag are rewritten.
+    fn test_status_line_prefix() {
+        let logger = Logger::new().with_status_key("status");
+        let download = fixed_entry(Level::Info, "Downloading", &[("status", "download")]);
+        let unpack = fixed_entry(Level::Info, "Unpacking", &[("status", "unpack")]);
+        let plain = fixed_entry(Level::Info, "Done", &[]);
+
+        let prefixes: Vec<_> = [&download, &download, &unpack, &plain, &unpack, &unpack]
+            .into_iter()
+            .map(|entry| logger.status_line_prefix(entry))
+            .collect();
+        let erase = "\x1b[1A\x1b[2K";
+        assert_eq!(prefixes, ["", erase, "", "", "", erase]);
+
+        let logger = logger.with_record_delimiter("\0");
+        assert_eq!(logger.status_line_prefix(&download), "");
+        assert_eq!(logger.status_line_prefix(&download), "");
+    }
+
+    #[test]
+    /// Verifies that entries go to the configured writer, uncolored by default.
+    fn test_with_writer() {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let logger = Logger::new()
+            .with_timestamp(false)
+            .with_writer(output.clone());
+        logger.info("Ready", &[("port", "8080")]);
+        logger.clone().with("worker", "2").warn("Slow", &[]);
+
+        assert_eq!(
+            String::from_utf8(output.lock().unwrap().clone()).unwrap(),
+            "INFO Ready port=8080\nWARN Slow worker=2\n"
+        );
+
+        let colored = Logger::new().with_colors(true).with_writer(output);
+        assert!(colored.config.use_colors);
+    }
+
+    #[test]
+    /// Verifies that the latest filtered entries are written before an error.
+    fn test_error_context_buffer() {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let logger = Logger::new()
+            .with_timestamp(false)
+            .with_writer(output.clone())
+            .with_error_context_buffer(2);
+
+        logger.debug("one", &[]);
+        logger.trace("two", &[]);
+        logger.debug("three", &[]);
+        logger.info("Working", &[]);
+        logger.error("Failed", &[]);
+        logger.error("Failed again", &[]);
+
+        assert_eq!(
+            String::from_utf8(output.lock().unwrap().clone()).unwrap(),
+            "INFO Working\nTRCE two\nDEBG three\nERRO Failed\nERRO Failed again\n"
+        );
+    }
+
+    #[test]
+    /// Verifies parsing levels from names and codes in any case.
+    fn test_level_from_str() {
+        for level in [
+            Level::Trace,
+            Level::Debug,
+            Level::Info,
+            Level::Warn,
+            Level::Error,
+            Level::Off,
+        ] {
+            assert_eq!(level.name().parse::<Level>(), Ok(level));
+            assert_eq!(level.as_str().parse::<Level>(), Ok(level));
+            assert_eq!(level.name().to_uppercase().parse::<Level>(), Ok(level));
+        }
+        assert_eq!(" Warning ".parse::<Level>(), Ok(Level::Warn));
+        let err = "NOTE".parse::<Level>().unwrap_err();
+        assert_eq!(err.input(), "NOTE");
+        assert!(err.to_string().contains("`NOTE`"));
+
+        std::env::set_var("CCB_TEST_LEVEL_FROM_ENV", "trce");
+        assert_eq!(Level::from_env("CCB_TEST_LEVEL_FROM_ENV"), Level::Trace);
+        std::env::set_var("CCB_TEST_LEVEL_FROM_ENV", "bogus");
+        assert_eq!(Level::from_env("CCB_TEST_LEVEL_FROM_ENV"), Level::Info);
+        assert_eq!(Level::from_env("CCB_TEST_LEVEL_UNSET"), Level::Info);
+    }
+
+    #[test]
+    /// Verifies that the macros accept any `Display` value as a field value.
+    fn test_macros_accept_display_values() {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        set_thread_logger(
+            Logger::new()
+                .with_timestamp(false)
+                .with_colors(false)
+                .with_writer(output.clone()),
+        );
+        let name = String::from("alice");
+        info!(
+            "Batch done",
+            "count", 42, "ok", true, "user", name, "ratio", 0.5
+        );
+        info!("Borrowed", "user", &name, "literal", "str");
+        clear_thread_logger();
+
+        let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        for field in [
+            "count=42",
+            "ok=true",
+            "user=alice",
+            "ratio=0.5",
+            "literal=str",
+        ] {
+            assert!(output.contains(field), "missing {} in {}", field, output);
+        }
+    }
+
+    #[test]
+    /// Verifies the precedence of logger context, scoped fields, and call
+    /// fields.
+    fn test_scoped_context_precedence() {
+        let logger = Logger::new().with("user", "logger").with("service", "api");
+        let _outer = push_context(&[("user", "outer"), ("request_id", "req-1")]);
+        let _inner = push_context(&[("request_id", "req-2")]);
+
+        let entry = logger.build_entry(Level::Info, "msg", &[("service", "call")]);
+        assert_eq!(entry.fields["user"], "outer");
+        assert_eq!(entry.fields["request_id"], "req-2");
+        assert_eq!(entry.fields["service"], "call");
+    }
+
+    #[test]
+    /// Verifies that `with_fields` accumulates with other context.
+    fn test_with_fields() {
+        let logger = Logger::new()
+            .with("service", "auth")
+            .with_fields(&[("user", "alice"), ("region", "eu")])
+            .with_fields(&[("user", "bob")])
+            .with("version", "1.2.0");
+        assert_eq!(logger.context.len(), 4);
+        assert_eq!(logger.context["user"], "bob");
+        assert_eq!(logger.context["service"], "auth");
+    }
+
+    #[test]
+    /// Verifies custom timestamp formats and that invalid ones are rejected.
+    fn test_timestamp_format() {
+        let entry = fixed_entry(Level::Info, "Started", &[]);
+        let logger = Logger::new()
+            .with_colors(false)
+            .with_timestamp_format("%H:%M:%S");
+        let expected = format!("{} INFO Started\n", entry.timestamp.format("%H:%M:%S"));
+        assert_eq!(render_to_string(&logger, &entry, None), expected);
+
+        for invalid in ["", "%Q", "%H:%"] {
+            let logger = logger.clone().with_timestamp_format(invalid);
+            assert_eq!(logger.config.timestamp_format, "%H:%M:%S");
+        }
+
+        let mut broken = logger;
+        broken.config.timestamp_format = "%Q".to_string();
+        let fallback = entry.timestamp.format(format::TIMESTAMP_FORMAT).to_string();
+        assert!(render_to_string(&broken, &entry, None).starts_with(&fallback));
+    }
+
+    #[test]
+    /// Verifies that UTC timestamps are rendered in UTC, with a zero offset in JSON.
+    fn test_utc_timestamps() {
+        let entry = fixed_entry(Level::Info, "Tick", &[]);
+        let utc = entry.timestamp.with_timezone(&Utc);
+
+        let logger = Logger::new().with_colors(false).with_utc(true);
+        assert_eq!(
+            logger.format_entry(&entry),
+            format!("{} INFO Tick\n", utc.format(format::TIMESTAMP_FORMAT))
+        );
+
+        let json = logger.with_format(Format::Json).format_entry(&entry);
+        let expected = format!(
+            "\"timestamp\":\"{}\"",
+            utc.format("%Y-%m-%dT%H:%M:%S%.3f+00:00")
+        );
+        assert!(json.contains(&expected), "{}", json);
+    }
+
+    #[test]
+    /// Verifies that buffered entries are written at capacity, on flush, and
+    /// when the last clone is dropped.
+    fn test_buffering() {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let written = || output.lock().unwrap().len();
+        let logger = Logger::new()
+            .with_timestamp(false)
+            .with_buffering(30)
+            .with_writer(output.clone());
+
+        logger.info("first", &[]);
+        assert_eq!(written(), 0);
+        logger.info("second", &[]);
+        logger.info("third", &[]);
+        assert_eq!(written(), "INFO first\nINFO second\nINFO third\n".len());
+
+        logger.info("fourth", &[]);
+        logger.flush();
+        assert!(String::from_utf8_lossy(&output.lock().unwrap()).ends_with("INFO fourth\n"));
+
+        let clone = logger.clone();
+        clone.info("fifth", &[]);
+        drop(logger);
+        let before = written();
+        drop(clone);
+        assert_eq!(written(), before + "INFO fifth\n".len());
+    }
+
+    #[test]
+    /// Verifies the macros' fast-path level check for thread loggers.
+    fn test_level_enabled_fast_path() {
+        set_thread_logger(Logger::new().with_level(Level::Warn));
+        assert!(!__level_enabled(Level::Info));
+        assert!(__level_enabled(Level::Warn));
+        assert!(__level_enabled(Level::Custom(30, "ZZZZ", Color::White)));
+
+        set_thread_logger(
+            Logger::new()
+                .with_level(Level::Warn)
+                .with_error_context_buffer(4),
+        );
+        assert!(__level_enabled(Level::Trace));
+
+        set_thread_logger(Logger::new().with_level(Level::Off));
+        assert!(!__level_enabled(Level::Custom(255, "MAX!", Color::White)));
+        clear_thread_logger();
+    }
+
+    #[test]
+    /// Verifies that level labels use the theme's colors.
+    fn test_theme() {
+        let entry = fixed_entry(Level::Info, "Ready", &[]);
+        let logger = Logger::new()
+            .with_timestamp(false)
+            .with_colors(true)
+            .with_color_support(ColorSupport::TrueColor);
+
+        let blue = logger.clone().with_theme(Theme {
+            info: Some(Color::Blue),
+            ..Theme::default()
         });
-    };
-}
+        assert_eq!(
+            render_to_string(&blue, &entry, None),
+            "\x1b[0m\x1b[1m\x1b[34mINFO \x1b[0mReady\n"
+        );
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let mono = logger.with_theme(Theme::monochrome());
+        assert_eq!(
+            render_to_string(&mono, &entry, None),
+            "\x1b[0m\x1b[1mINFO \x1b[0mReady\n"
+        );
+        assert!(mono.describe().contains("theme: monochrome\n"));
+        assert_eq!(
+            Theme::monochrome().color(Level::Custom(25, "NOTE", Color::Magenta)),
+            None
+        );
+    }
 
     #[test]
-    /// Verifies that log levels are properly ordered by severity.
-    fn test_level_ordering() {
-        assert!(Level::Trace < Level::Debug);
-        assert!(Level::Debug < Level::Info);
-        assert!(Level::Info < Level::Warn);
-        assert!(Level::Warn < Level::Error);
+    /// Verifies that the style controls bold labels and key and message colors.
+    fn test_style() {
+        let entry = fixed_entry(Level::Info, "Ready", &[("port", "80")]);
+        let logger = Logger::new()
+            .with_timestamp(false)
+            .with_colors(true)
+            .with_color_support(ColorSupport::TrueColor)
+            .with_style(StyleConfig {
+                bold_level: false,
+                key_color: None,
+                message_color: Some(Color::Cyan),
+            });
+        assert_eq!(
+            render_to_string(&logger, &entry, None),
+            "\x1b[0m\x1b[32mINFO \x1b[0m\x1b[0m\x1b[36mReady\x1b[0m\x1b[0m port=\x1b[0m80\n"
+        );
+        assert!(logger.describe().contains("style: custom\n"));
     }
 
     #[test]
-    /// Tests that all log levels return the correct four-character string representation.
-    fn test_level_strings() {
-        assert_eq!(Level::Trace.as_str(), "TRCE");
-        assert_eq!(Level::Debug.as_str(), "DEBG");
-        assert_eq!(Level::Info.as_str(), "INFO");
-        assert_eq!(Level::Warn.as_str(), "WARN");
-        assert_eq!(Level::Error.as_str(), "ERRO");
+    /// Verifies that `log!` logs at a runtime level, evaluating it once.
+    fn test_log_macro() {
+        let (logger, capture) = Logger::capturing();
+        set_thread_logger(logger.with_timestamp(false).with_level(Level::Warn));
+
+        let mut evaluations = 0;
+        let mut next_level = |level| {
+            evaluations += 1;
+            level
+        };
+        crate::log!(next_level(Level::Error), "Retries exhausted", "attempts", 3);
+        crate::log!(next_level(Level::Info), "Retrying");
+        clear_thread_logger();
+
+        assert_eq!(evaluations, 2);
+        assert_eq!(capture.lines(), ["ERRO Retries exhausted attempts=3"]);
     }
 
     #[test]
-    /// Verifies logger creation with default and custom configurations.
-    fn test_logger_creation() {
-        let logger = Logger::new();
-        assert_eq!(logger.config.level, Level::Info);
+    /// Verifies that lazy fields are only computed for kept entries.
+    fn test_log_lazy() {
+        let (logger, capture) = Logger::capturing();
+        let logger = logger.with_timestamp(false);
+        let calls = Cell::new(0);
+        let fields = || {
+            calls.set(calls.get() + 1);
+            vec![("state", "[1, 2]".to_string())]
+        };
 
-        let logger = Logger::new().with_level(Level::Debug);
-        assert_eq!(logger.config.level, Level::Debug);
+        logger.log_lazy(Level::Debug, "Filtered", fields);
+        assert_eq!(calls.get(), 0);
+        logger.log_lazy(Level::Info, "Kept", fields);
+        assert_eq!(calls.get(), 1);
+        assert_eq!(capture.lines(), ["INFO Kept state=[1, 2]"]);
     }
 
     #[test]
-    /// Tests that context key-value pairs are properly stored and accessible.
-    fn test_logger_with_context() {
+    /// Verifies that the most specific module rule picks the level.
+    fn test_module_levels() {
         let logger = Logger::new()
-            .with("service", "test")
-            .with("version", "1.0.0");
+            .with_level(Level::Warn)
+            .with_module_level("app::db", Level::Debug)
+            .with_module_level("app::db::pool", Level::Error);
+        assert_eq!(logger.level_for(None), Level::Warn);
+        assert_eq!(logger.level_for(Some("app")), Level::Warn);
+        assert_eq!(logger.level_for(Some("app::db")), Level::Debug);
+        assert_eq!(logger.level_for(Some("app::db::query")), Level::Debug);
+        assert_eq!(logger.level_for(Some("app::db::pool")), Level::Error);
+        assert_eq!(logger.level_for(Some("app::dbx")), Level::Warn);
+        assert_eq!(logger.min_rank(), level_rank(Level::Debug));
 
-        assert_eq!(logger.context.get("service"), Some(&"test".to_string()));
-        assert_eq!(logger.context.get("version"), Some(&"1.0.0".to_string()));
+        let (capture_logger, capture) = Logger::capturing();
+        set_thread_logger(
+            capture_logger
+                .with_timestamp(false)
+                .with_level(Level::Error)
+                .with_module_level(module_path!(), Level::Debug),
+        );
+        debug!("From this module");
+        clear_thread_logger();
+        assert_eq!(capture.lines(), ["DEBG From this module"]);
     }
 
     #[test]
-    /// Verifies that custom configurations are properly applied to loggers.
-    fn test_logger_configuration() {
+    /// Verifies that sampling keeps one in every `n` entries of the sampled
+    /// level only, counted across clones.
+    fn test_sampling() {
+        let (logger, capture) = Logger::capturing();
+        let logger = logger
+            .with_timestamp(false)
+            .with_level(Level::Trace)
+            .with_sampling(Level::Trace, 0)
+            .with_sampling(Level::Debug, 3);
+        let clone = logger.clone();
+        for i in 0..4 {
+            logger.debug("Tick", &[("i", &i.to_string())]);
+            clone.debug("Tock", &[("i", &i.to_string())]);
+        }
+        logger.trace("Kept", &[]);
+        logger.info("Always", &[]);
+
+        assert_eq!(
+            capture.lines(),
+            [
+                "DEBG Tick i=0",
+                "DEBG Tock i=1",
+                "DEBG Tick i=3",
+                "TRCE Kept",
+                "INFO Always"
+            ]
+        );
+        assert!(logger
+            .config
+            .describe()
+            .contains("sampling: TRCE=1/0, DEBG=1/3\n"));
+    }
+
+    #[test]
+    /// Verifies that repeats beyond the limit are dropped per level and
+    /// summarized once their window has closed.
+    fn test_rate_limit() {
+        let (logger, capture) = Logger::capturing();
+        let logger = logger
+            .with_timestamp(false)
+            .with_rate_limit(1, Duration::from_millis(50));
+        for _ in 0..3 {
+            logger.error("Disk full", &[("try", "1")]);
+            logger.warn("Disk full", &[]);
+        }
+        std::thread::sleep(Duration::from_millis(60));
+        logger.info("Recovered", &[]);
+
+        assert_eq!(
+            capture.lines(),
+            [
+                "ERRO Disk full try=1",
+                "WARN Disk full",
+                "ERRO Disk full ... suppressed 2 messages suppressed=2",
+                "WARN Disk full ... suppressed 2 messages suppressed=2",
+                "INFO Recovered"
+            ]
+        );
+    }
+
+    #[test]
+    /// Verifies that tees get a plain copy in their own format while the
+    /// primary output keeps its colors, and that a bad path only warns.
+    fn test_tee() {
+        let dir = std::env::temp_dir().join(format!("ccb-tee-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let plain = dir.join("plain.log");
+        let json = dir.join("json.log");
+        let _ = std::fs::remove_file(&plain);
+        let _ = std::fs::remove_file(&json);
+
+        let output: Arc<Mutex<Vec<u8>>> = Arc::new(Mutex::new(Vec::new()));
+        let logger = Logger::new()
+            .with_timestamp(false)
+            .with_writer(output.clone())
+            .with_colors(true)
+            .with_color_support(ColorSupport::Basic)
+            .with_tee(dir.join("missing").join("dir.log"))
+            .with_tee(&plain)
+            .with_tee_format(&json, Format::Json);
+        logger.info("Ready", &[("port", "8080")]);
+
+        let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("Tee file unavailable"));
+        assert!(output.contains("\x1b[") && output.contains("Ready"));
+        assert_eq!(
+            std::fs::read_to_string(&plain).unwrap(),
+            "INFO Ready port=8080\n"
+        );
+        let json = std::fs::read_to_string(&json).unwrap();
+        assert!(json.contains(r#""message":"Ready""#) && !json.contains("\x1b["));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    /// Verifies that targets render before the message and as keys in JSON
+    /// and logfmt, and that macros tag entries with their target.
+    fn test_target() {
+        let mut entry = fixed_entry(Level::Info, "Connected", &[("target", "x")]);
+        entry.target = Some("db".to_string());
+        let logger = Logger::new().with_timestamp(false).with_colors(false);
+        assert_eq!(
+            render_to_string(&logger, &entry, None),
+            "INFO db: Connected target=x\n"
+        );
+
+        let json = render_to_string(&logger.clone().with_format(Format::Json), &entry, None);
+        assert!(json.contains(r#""level":"info","target":"db","message":"Connected""#));
+        assert!(json.contains(r#""fields.target":"x""#));
+        let logfmt = render_to_string(&logger.with_format(Format::Logfmt), &entry, None);
+        assert!(logfmt.starts_with("level=info target=db ts="));
+        assert!(logfmt.ends_with("fields.target=x\n"));
+
+        let (logger, capture) = Logger::capturing();
+        register_logger("test-target-http", logger.with_timestamp(false));
+        crate::warn!(target: "test-target-http", "Slow", "ms", 950);
+        let target = String::from("test-target-http");
+        crate::log!(target: &target, Level::Error, "Failed");
+        unregister_logger("test-target-http");
+        assert_eq!(
+            capture.lines(),
+            [
+                "WARN test-target-http: Slow ms=950",
+                "ERRO test-target-http: Failed"
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    /// Verifies that entries and configs round-trip through JSON, with levels
+    /// as lowercase names and theme colors as strings.
+    fn test_serde_round_trip() {
+        let mut entry = fixed_entry(Level::Warn, "Disk full", &[("disk", "sda")]);
+        entry.target = Some("storage".to_string());
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["level"], "warn");
+        assert_eq!(json["fields"]["disk"], "sda");
+        let back: LogEntry = serde_json::from_value(json).unwrap();
+        assert_eq!(back.level, Level::Warn);
+        assert_eq!(back.target.as_deref(), Some("storage"));
+        assert_eq!(back.timestamp, entry.timestamp);
+
         let config = Config {
             level: Level::Debug,
-            use_colors: false,
-            show_timestamp: false,
+            format: Format::Csv(CsvFormat::new(["disk"])),
+            theme: Theme {
+                info: Some(Color::Rgb(1, 2, 3)),
+                warn: Some(Color::Ansi256(208)),
+                error: None,
+                ..Theme::default()
+            },
+            sampling: vec![(Level::Trace, 10)],
+            ..Config::default()
         };
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains(r#""info":"1,2,3""#) && json.contains(r#""warn":"208""#));
+        let back: Config = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.describe(), config.describe());
 
-        let logger = Logger::with_config(config.clone());
-        assert_eq!(logger.config.level, Level::Debug);
-        assert!(!logger.config.use_colors);
-        assert!(!logger.config.show_timestamp);
+        let partial: Config = serde_json::from_str(r#"{"level": "error"}"#).unwrap();
+        assert_eq!(partial.level, Level::Error);
+        assert!(serde_json::from_str::<Level>(r#""verbose""#).is_err());
     }
 
     #[test]
-    /// Tests setting and retrieving the global logger instance.
-    fn test_global_logger() {
-        let custom_logger = Logger::new()
-            .with_level(Level::Trace)
-            .with("global", "test");
+    #[cfg(feature = "msgpack")]
+    /// Verifies that a MessagePack logger writes versioned, framed records to
+    /// its writer.
+    fn test_msgpack_logger() {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let logger = Logger::new()
+            .with_writer(output.clone())
+            .with_format(Format::MessagePack);
+        logger.info("Ready", &[("port", "8080")]);
+        logger.warn("Slow", &[]);
 
-        set_global_logger(custom_logger);
+        let output = output.lock().unwrap().clone();
+        let mut records = Vec::new();
+        let mut rest = &output[..];
+        while !rest.is_empty() {
+            assert_eq!(rest[0], format::MSGPACK_FRAME_VERSION);
+            let (prefix, tail) = rest[1..].split_at(4);
+            let length = u32::from_be_bytes(prefix.try_into().unwrap()) as usize;
+            let entry: LogEntry = rmp_serde::from_slice(&tail[..length]).unwrap();
+            records.push(entry);
+            rest = &tail[length..];
+        }
 
-        let retrieved = global_logger();
-        assert_eq!(retrieved.config.level, Level::Trace);
-        assert_eq!(retrieved.context.get("global"), Some(&"test".to_string()));
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].level, Level::Info);
+        assert_eq!(records[0].message, "Ready");
+        assert_eq!(
+            records[0].fields.get("port").map(String::as_str),
+            Some("8080")
+        );
+        assert_eq!(records[1].level, Level::Warn);
+        assert!(logger.describe().contains("format: messagepack\n"));
     }
 
     #[test]
-    /// Ensures that all logging macros compile and execute without errors.
-    /// In a real testing environment, stderr output would be captured for verification.
-    fn test_macros_compile() {
-        // Test that macros compile without panicking
-        // In a real test environment, you might want to capture stderr
-        trace!("Test trace message");
-        debug!("Test debug message");
-        info!("Test info message");
-        warn!("Test warn message");
-        error!("Test error message");
+    /// Verifies that async loggers write in order, drain on drop, and report
+    /// entries dropped on overflow.
+    fn test_async_channel() {
+        let (logger, capture) = Logger::capturing();
+        let logger = logger.with_timestamp(false).with_async_channel(2);
+        let clone = logger.clone();
+        for i in 0..20 {
+            clone.info("Tick", &[("i", &i.to_string())]);
+        }
+        drop(clone);
+        drop(logger);
+        let lines = capture.lines();
+        assert_eq!(lines.len(), 20);
+        assert_eq!(lines[19], "INFO Tick i=19");
 
-        trace!("Test with fields", "key1", "value1", "key2", "value2");
-        info!("User login", "user_id", "12345", "ip", "192.168.1.1");
+        // A writer that stalls while the gate is held, keeping the queue full
+        struct Gated(Arc<Mutex<()>>, Arc<Mutex<Vec<u8>>>);
+        impl Write for Gated {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                let _open = self.0.lock();
+                self.1.lock().unwrap().write(buf)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let gate = Arc::new(Mutex::new(()));
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let held = gate.lock().unwrap();
+        let logger = Logger::new()
+            .with_timestamp(false)
+            .with_writer(Arc::new(Mutex::new(Gated(gate.clone(), output.clone()))))
+            .with_async_channel_policy(1, OverflowPolicy::DropOldest);
+        logger.info("First", &[]);
+        for i in 0..5 {
+            logger.info("Burst", &[("i", &i.to_string())]);
+        }
+        drop(held);
+        logger.flush();
+        let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("Async log queue full, dropped entries dropped="));
+        assert!(output.ends_with("INFO Burst i=4\n"));
+        assert!(logger.describe().contains("async: 1 entries, DropOldest\n"));
     }
 
     #[test]
-    /// Verifies that LogEntry structures are created correctly with all required fields.
-    fn test_log_entry_creation() {
-        let _logger = Logger::new();
-        let now = Local::now();
+    /// Verifies each precision in the pretty and ISO timestamps.
+    fn test_timestamp_precision() {
+        use chrono::Timelike;
 
-        let entry = LogEntry {
-            level: Level::Info,
-            message: "test message".to_string(),
-            fields: HashMap::new(),
-            timestamp: now,
+        let mut entry = fixed_entry(Level::Info, "Tick", &[]);
+        entry.timestamp = Local
+            .with_ymd_and_hms(2024, 1, 15, 14, 30, 25)
+            .unwrap()
+            .with_nanosecond(123_456_789)
+            .unwrap();
+        let offset = entry.timestamp.format("%:z").to_string();
+        let cases = [
+            (TimestampPrecision::Seconds, "25"),
+            (TimestampPrecision::Millis, "25.123"),
+            (TimestampPrecision::Micros, "25.123456"),
+            (TimestampPrecision::Nanos, "25.123456789"),
+        ];
+        for (precision, seconds) in cases {
+            let logger = Logger::new()
+                .with_colors(false)
+                .with_timestamp_precision(precision);
+            assert_eq!(
+                render_to_string(&logger, &entry, None),
+                format!("2024-01-15 14:30:{} INFO Tick\n", seconds)
+            );
+            let logfmt = render_to_string(&logger.with_format(Format::Logfmt), &entry, None);
+            assert!(logfmt.contains(&format!("ts=2024-01-15T14:30:{}{} ", seconds, offset)));
+        }
+        assert!(Config::default()
+            .describe()
+            .contains("timestamp precision: Millis\n"));
+    }
+
+    #[test]
+    /// Verifies that a custom timestamp format survives the precision setter
+    /// in either order.
+    fn test_timestamp_precision_with_format() {
+        let entry = fixed_entry(Level::Info, "Tick", &[]);
+        let expected = format!("{} INFO Tick\n", entry.timestamp.format("%H:%M:%S"));
+        let loggers = [
+            Logger::new()
+                .with_timestamp_format("%H:%M:%S")
+                .with_timestamp_precision(TimestampPrecision::Micros),
+            Logger::new()
+                .with_timestamp_precision(TimestampPrecision::Micros)
+                .with_timestamp_format("%H:%M:%S"),
+        ];
+        for logger in loggers {
+            let logger = logger.with_colors(false);
+            assert_eq!(render_to_string(&logger, &entry, None), expected);
+            assert_eq!(
+                logger.config.timestamp_precision,
+                TimestampPrecision::Micros
+            );
+        }
+    }
+
+    #[test]
+    /// Verifies that compact timestamps only show the full stamp when the
+    /// second changes.
+    fn test_compact_timestamps() {
+        use chrono::Timelike;
+
+        let logger = Logger::new()
+            .with_colors(false)
+            .with_timestamp_mode(TimestampMode::Compact);
+        let at = |second: u32, millis: u32| {
+            let mut entry = fixed_entry(Level::Info, "Tick", &[]);
+            entry.timestamp = Local
+                .with_ymd_and_hms(2024, 1, 15, 14, 30, second)
+                .unwrap()
+                .with_nanosecond(millis * 1_000_000)
+                .unwrap();
+            let mut buffer = Buffer::no_color();
+            let annotations = logger.annotate(&entry);
+            logger.render(&entry, &annotations, &mut buffer).unwrap();
+            String::from_utf8(buffer.into_inner()).unwrap()
         };
 
-        assert_eq!(entry.level, Level::Info);
-        assert_eq!(entry.message, "test message");
-        assert!(entry.fields.is_empty());
+        assert_eq!(at(25, 100), "2024-01-15 14:30:25.100 INFO Tick\n");
+        assert_eq!(at(25, 125), "+.125 INFO Tick\n");
+        assert_eq!(at(25, 900), "+.900 INFO Tick\n");
+        assert_eq!(at(26, 5), "2024-01-15 14:30:26.005 INFO Tick\n");
     }
 
     #[test]
-    /// Tests that the logger properly sets the minimum log level for filtering.
-    fn test_level_filtering() {
-        let logger = Logger::new().with_level(Level::Warn);
+    /// Verifies that split streams send entries below `Warn` to stdout.
+    fn test_split_streams() {
+        let logger = Logger::new();
+        assert!(matches!(
+            logger.output_stream(Level::Debug),
+            atty::Stream::Stderr
+        ));
 
-        // This test would need a way to capture output to verify filtering
-        // For now, we just test that the configuration is set correctly
-        assert_eq!(logger.config.level, Level::Warn);
+        let logger = logger.with_split_streams(true);
+        for level in [Level::Trace, Level::Debug, Level::Info] {
+            assert!(matches!(logger.output_stream(level), atty::Stream::Stdout));
+        }
+        for level in [Level::Warn, Level::Error] {
+            assert!(matches!(logger.output_stream(level), atty::Stream::Stderr));
+        }
+        assert!(logger.config.describe().contains("split streams: on\n"));
     }
 
     #[test]
-    /// Verifies that color configuration can be enabled and disabled correctly.
-    fn test_colors_configuration() {
-        let logger_with_colors = Logger::new().with_colors(true);
-        let logger_without_colors = Logger::new().with_colors(false);
+    /// Verifies that the level list is sorted and round-trips through parsing.
+    fn test_level_iter() {
+        let levels: Vec<_> = Level::iter().collect();
+        assert_eq!(levels.len(), 5);
+        assert!(levels.windows(2).all(|pair| pair[0] < pair[1]));
+        for level in levels {
+            assert_eq!(level.as_str().parse::<Level>(), Ok(level));
+        }
+        assert!(!Level::all().contains(&Level::Off));
+    }
 
-        assert!(logger_with_colors.config.use_colors);
-        assert!(!logger_without_colors.config.use_colors);
+    #[test]
+    /// Verifies that hooks run in order on emitted entries only, and that a
+    /// panicking hook doesn't stop logging.
+    fn test_hooks() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook = |name: &'static str| -> Arc<dyn Fn(&LogEntry) + Send + Sync> {
+            let seen = seen.clone();
+            Arc::new(move |entry: &LogEntry| {
+                seen.lock()
+                    .unwrap()
+                    .push(format!("{}:{}", name, entry.message));
+            })
+        };
+        let (logger, capture) = Logger::capturing();
+        let logger = logger
+            .with_timestamp(false)
+            .with_hook(hook("first"))
+            .with_hook(Arc::new(|_: &LogEntry| panic!("broken hook")))
+            .with_hook(hook("second"));
+
+        logger.debug("Filtered", &[]);
+        logger.info("Kept", &[]);
+
+        assert_eq!(*seen.lock().unwrap(), ["first:Kept", "second:Kept"]);
+        assert_eq!(capture.lines(), ["INFO Kept"]);
     }
 
     #[test]
-    /// Tests that timestamp display can be configured independently.
-    fn test_timestamp_configuration() {
-        let logger_with_timestamp = Logger::new().with_timestamp(true);
-        let logger_without_timestamp = Logger::new().with_timestamp(false);
+    /// Verifies that buffered output is written by the flush interval without
+    /// an explicit flush, whichever of the two settings comes first.
+    fn test_flush_interval() {
+        for interval_first in [false, true] {
+            let output: Arc<Mutex<Vec<u8>>> = Arc::new(Mutex::new(Vec::new()));
+            let mut logger = Logger::new().with_timestamp(false);
+            if interval_first {
+                logger = logger.with_flush_interval(Duration::from_millis(10));
+            }
+            logger = logger.with_buffering(4096).with_writer(output.clone());
+            if !interval_first {
+                logger = logger.with_flush_interval(Duration::from_millis(10));
+            }
+            logger.info("Idle", &[]);
 
-        assert!(logger_with_timestamp.config.show_timestamp);
-        assert!(!logger_without_timestamp.config.show_timestamp);
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while output.lock().unwrap().is_empty() && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            assert_eq!(&*output.lock().unwrap(), b"INFO Idle\n");
+        }
     }
 
     #[test]
-    /// Validates that the Config::default() implementation provides sensible defaults.
-    fn test_default_config() {
-        let config = Config::default();
-        assert_eq!(config.level, Level::Info);
-        assert_eq!(config.show_timestamp, true);
-        // use_colors depends on terminal detection, so we don't assert its value
+    /// Verifies that field keys beyond the JSON key limit are collected into
+    /// a stringified `extra` key, across entries.
+    fn test_json_max_keys() {
+        let (logger, capture) = Logger::capturing();
+        let logger = logger.with_format(Format::Json).with_json_max_keys(2);
+        logger.info("First", &[("a", "1"), ("b", "2"), ("c", "3")]);
+        logger.info("Second", &[("b", "4"), ("d", "5"), ("extra", "6")]);
+        logger.info("Third", &[("a", "7")]);
+
+        let records: Vec<serde_json::Value> = capture
+            .lines()
+            .iter()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records[0]["a"], "1");
+        assert_eq!(records[0]["b"], "2");
+        assert_eq!(records[0]["extra"], r#"{"c":"3"}"#);
+        assert_eq!(records[1]["b"], "4");
+        assert_eq!(records[1]["extra"], r#"{"d":"5","extra":"6"}"#);
+        assert!(records[1].get("fields.extra").is_none());
+        assert_eq!(records[2]["a"], "7");
+        assert!(records[2].get("extra").is_none());
     }
 
     #[test]
-    /// Ensures that logger methods can be chained together fluently.
-    fn test_method_chaining() {
+    /// Verifies that buffered output goes to the moved files and later
+    /// entries to fresh files after `reopen_sinks`.
+    fn test_reopen_sinks() {
+        let dir = std::env::temp_dir().join(format!("ccb-reopen-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+        let tee = dir.join("tee.log");
+
+        let logger = Logger::new()
+            .with_timestamp(false)
+            .with_rotating_file(&path, 1024 * 1024, 1)
+            .with_buffering(4096)
+            .with_tee(&tee);
+        logger.info("Before", &[]);
+        std::fs::rename(&path, dir.join("app.log.old")).unwrap();
+        std::fs::rename(&tee, dir.join("tee.log.old")).unwrap();
+
+        logger.reopen_sinks().unwrap();
+        logger.info("After", &[]);
+        logger.flush();
+
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("app.log.old"), "INFO Before\n");
+        assert_eq!(read("app.log"), "INFO After\n");
+        assert_eq!(read("tee.log.old"), "INFO Before\n");
+        assert_eq!(read("tee.log"), "INFO After\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    /// Verifies that the JSON schema header is the first line of each JSON
+    /// output and is written only once.
+    fn test_json_schema_header() {
+        let dir = std::env::temp_dir().join(format!("ccb-schema-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let tee = dir.join("app.jsonl");
+        let _ = std::fs::remove_file(&tee);
+
+        let (logger, capture) = Logger::capturing();
+        let logger = logger
+            .with_timestamp(false)
+            .with_json_schema_header(true)
+            .with("service", "auth")
+            .with("level", "shadowed")
+            .with_tee_format(&tee, Format::Json);
+        logger.info("Started", &[]);
+        logger.info("Ready", &[]);
+        // The pretty primary output gets no header
+        assert_eq!(capture.lines().len(), 2);
+        assert!(capture.lines().iter().all(|line| !line.contains("_schema")));
+
+        let written = std::fs::read_to_string(&tee).unwrap();
+        let lines: Vec<_> = written.lines().collect();
+        assert_eq!(lines.len(), 3);
+        let schema: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(schema["_schema"]["version"], 1);
+        assert_eq!(schema["_schema"]["fields"]["message"], "string");
+        assert_eq!(schema["_schema"]["fields"]["service"], "string");
+        assert_eq!(schema["_schema"]["fields"]["fields.level"], "string");
+        assert!(lines[1].contains(r#""message":"Started""#));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    /// Verifies that events reach hooks and structured outputs regardless of
+    /// the level filter, but not pretty output.
+    fn test_events() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook_seen = seen.clone();
+        let (logger, capture) = Logger::capturing();
+        let logger = logger
+            .with_level(Level::Error)
+            .with("service", "auth")
+            .with_redacted_keys(["token"])
+            .with_hook(Arc::new(move |entry: &LogEntry| {
+                hook_seen.lock().unwrap().push(entry.clone());
+            }));
+
+        logger.event("login", &[("user", "alice"), ("token", "s3cr3t")]);
+        logger.info("Filtered", &[]);
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].level, Level::EVENT);
+        assert_eq!(seen[0].message, "login");
+        assert_eq!(seen[0].fields["service"], "auth");
+        assert_eq!(seen[0].fields["user"], "alice");
+        assert_ne!(seen[0].fields["token"], "s3cr3t");
+        assert!(capture.lines().is_empty());
+
+        let dir = std::env::temp_dir().join(format!("ccb-events-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let plain = dir.join("plain.log");
+        let json = dir.join("json.log");
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let logger = Logger::new()
+            .with_level(Level::Error)
+            .with_writer(output.clone())
+            .with_format(Format::Logfmt)
+            .with_tee_format(&plain, Format::Pretty)
+            .with_tee_format(&json, Format::Json);
+        logger.event("login", &[("user", "alice")]);
+
+        let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        assert!(output.contains(" msg=login user=alice\n"), "{}", output);
+        assert_eq!(std::fs::read_to_string(&plain).unwrap(), "");
+        let json = std::fs::read_to_string(&json).unwrap();
+        assert!(json.contains(r#""message":"login""#) && json.contains(r#""user":"alice""#));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    /// Verifies placeholder filling, escapes, and argument keys of `logf!`.
+    fn test_logf() {
+        let args = [
+            ("user", "alice".to_string()),
+            ("ip", "10.0.0.7".to_string()),
+        ];
+        assert_eq!(
+            interpolate("{} from {ip} as {}, {{x}} {} {missing} }", &args),
+            "alice from 10.0.0.7 as 10.0.0.7, {x} {} {missing} }"
+        );
+        assert_eq!(interpolate("open {", &args), "open {");
+
+        let (logger, capture) = Logger::capturing();
+        set_thread_logger(logger.with_timestamp(false).with_sorted_fields(true));
+        let user = "bob";
+        let retries = [1, 2];
+        logf!(Level::Info, "{} retried {} times", user, retries.len());
+        logf!(Level::Debug, "hidden {}", user);
+        clear_thread_logger();
+        assert_eq!(
+            capture.lines(),
+            ["INFO bob retried 2 times arg1=2 user=bob"]
+        );
+    }
+
+    #[test]
+    /// Verifies the level checks of `is_enabled` and `enabled!`.
+    fn test_is_enabled() {
+        let logger = Logger::new().with_level(Level::Warn);
+        assert!(!logger.is_enabled(Level::Info));
+        assert!(logger.is_enabled(Level::Warn) && logger.is_enabled(Level::Error));
+        assert!(!logger.is_enabled(Level::Off));
+        assert!(!logger.with_level(Level::Off).is_enabled(Level::Error));
+
+        set_thread_logger(
+            Logger::new()
+                .with_level(Level::Error)
+                .with_module_level(module_path!(), Level::Trace),
+        );
+        assert!(crate::enabled!(Level::Trace));
+        assert!(!Logger::new()
+            .with_level(Level::Error)
+            .with_module_level(module_path!(), Level::Trace)
+            .is_enabled(Level::Trace));
+        set_thread_logger(Logger::new().with_level(Level::Error));
+        assert!(!crate::enabled!(Level::Warn));
+        clear_thread_logger();
+    }
+
+    #[test]
+    /// Verifies exact, suffix, and prefix redaction in text and JSON output.
+    fn test_redacted_keys() {
+        let (logger, capture) = Logger::capturing();
+        let logger = logger
+            .with_timestamp(false)
+            .with_sorted_fields(true)
+            .with("api_token", "abc")
+            .with_redacted_keys(["password", "*_token"])
+            .with_redacted_keys(["internal_*".to_string()]);
+        logger.info(
+            "Login",
+            &[
+                ("PASSWORD", "hunter2"),
+                ("internal_id", "7"),
+                ("user", "ada"),
+            ],
+        );
+        logger
+            .clone()
+            .with_format(Format::Json)
+            .info("Login", &[("password", "x")]);
+
+        let lines = capture.lines();
+        assert_eq!(
+            lines[0],
+            "INFO Login PASSWORD=*** api_token=*** internal_id=*** user=ada"
+        );
+        let json: serde_json::Value = serde_json::from_str(&lines[1]).unwrap();
+        assert_eq!(json["password"], "***");
+        assert_eq!(json["api_token"], "***");
+        assert!(!logger.is_redacted("token"));
+    }
+
+    #[test]
+    /// Verifies that `error_with` numbers each error in the source chain.
+    fn test_error_with_chain() {
+        #[derive(Debug)]
+        struct Outer(std::io::Error);
+        impl fmt::Display for Outer {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "request failed")
+            }
+        }
+        impl std::error::Error for Outer {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                Some(&self.0)
+            }
+        }
+
+        let (logger, capture) = Logger::capturing();
+        let logger = logger.with_timestamp(false).with_sorted_fields(true);
+        let err = Outer(std::io::Error::other("connection reset"));
+        logger.error_with("Upload aborted", &err, &[("id", "7")]);
+
+        assert_eq!(
+            capture.lines(),
+            ["ERRO Upload aborted err.0=request failed err.1=connection reset id=7"]
+        );
+    }
+
+    #[test]
+    /// Verifies that `Off` silences every level and is never emitted itself.
+    fn test_level_off() {
+        const TOP: Level = Level::Custom(255, "TOP!", Color::White);
+        assert!(Level::Error < Level::Off && TOP < Level::Off);
+        assert_eq!(Level::Off.max(TOP), Level::Off);
+
+        let buffer = Arc::new(Mutex::new(Vec::new()));
         let logger = Logger::new()
+            .with_writer(buffer.clone())
+            .with_error_context_buffer(5)
+            .with_level(Level::Off);
+        logger.log(Level::Debug, "buffered?", &[]);
+        logger.log(Level::Error, "failure", &[]);
+        logger.log(TOP, "custom", &[]);
+        Logger::new()
+            .with_writer(buffer.clone())
             .with_level(Level::Trace)
-            .with_colors(false)
-            .with_timestamp(true)
-            .with("chain", "test")
-            .with("fluent", "api");
+            .log(Level::Off, "never", &[]);
+        assert!(buffer.lock().unwrap().is_empty());
+    }
 
-        assert_eq!(logger.config.level, Level::Trace);
-        assert!(!logger.config.use_colors);
-        assert!(logger.config.show_timestamp);
-        assert_eq!(logger.context.len(), 2);
+    #[test]
+    /// Verifies that strict context warns about an overwritten key and still
+    /// lets the later value win.
+    fn test_strict_context() {
+        let (logger, capture) = Logger::capturing();
+        let logger = logger
+            .with_level(Level::Warn)
+            .with_timestamp(false)
+            .with_sorted_fields(true)
+            .with_strict_context(true)
+            .with("svc", "x")
+            .with("svc", "y");
+        assert_eq!(logger.context.get("svc").map(String::as_str), Some("y"));
+        assert!(logger.describe().contains("strict context: on\n"));
+
+        // The logger's own context is attached to the warning as well
+        assert_eq!(
+            capture.lines(),
+            ["WARN Context key overwritten key=svc previous=x svc=x value=y"]
+        );
     }
 
     #[test]
-    /// Tests that level colors are assigned correctly for terminal output.
-    fn test_level_colors() {
-        assert_eq!(Level::Trace.color(), Color::Cyan);
-        assert_eq!(Level::Debug.color(), Color::Blue);
-        assert_eq!(Level::Info.color(), Color::Green);
-        assert_eq!(Level::Warn.color(), Color::Yellow);
-        assert_eq!(Level::Error.color(), Color::Red);
+    /// Verifies that entry field maps are pre-sized to the configured capacity.
+    fn test_field_capacity() {
+        let logger = Logger::new()
+            .with("service", "auth")
+            .with_field_capacity(32);
+        assert!(logger.context.capacity() >= 32);
+
+        let entry = logger.build_entry(Level::Info, "Request", &[("path", "/")]);
+        assert!(entry.fields.capacity() >= 32);
+        assert_eq!(entry.fields.len(), 2);
+    }
+
+    #[test]
+    /// Verifies that fieldless entries, built on the fast path, carry the
+    /// context and scoped fields, all marked as inherited.
+    fn test_fieldless_entry() {
+        let logger = Logger::new()
+            .with("service", "auth")
+            .with_distinguish_origin(true);
+        let scope = push_context(&[("request", "7")]);
+
+        let entry = logger.build_entry(Level::Info, "Ping", &[]);
+        assert_eq!(entry.fields.len(), 2);
+        assert_eq!(entry.fields["request"], "7");
+        assert_eq!(
+            entry.inherited_keys,
+            HashSet::from(["service".to_string(), "request".to_string()])
+        );
+        assert!(!logger.context.contains_key("request"));
+
+        drop(scope);
+        let bare = Logger::new().build_entry(Level::Info, "Ping", &[]);
+        assert_eq!(bare.fields.capacity(), 0);
+    }
+
+    #[test]
+    /// Verifies that failures reach the installed handler, but not
+    /// re-entrantly, and that a panicking handler still gets later failures.
+    fn test_error_handler() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static BROKEN_PIPES: AtomicUsize = AtomicUsize::new(0);
+
+        set_error_handler(|err: &CcbError| {
+            if let CcbError::Io(io) = err {
+                if io.kind() == std::io::ErrorKind::BrokenPipe {
+                    BROKEN_PIPES.fetch_add(1, Ordering::Relaxed);
+                    report_error(err);
+                }
+                if io.to_string() == "handler panic test" {
+                    panic!("error handler failed");
+                }
+            }
+        });
+
+        let err = CcbError::from(std::io::Error::from(std::io::ErrorKind::BrokenPipe));
+        report_error(&err);
+        report_error(&CcbError::Panicked);
+        assert_eq!(BROKEN_PIPES.load(Ordering::Relaxed), 1);
+
+        let fatal = CcbError::from(std::io::Error::other("handler panic test"));
+        assert!(std::panic::catch_unwind(AssertUnwindSafe(|| report_error(&fatal))).is_err());
+        assert!(!IN_ERROR_HANDLER.with(Cell::get));
+        report_error(&err);
+
+        assert_eq!(BROKEN_PIPES.load(Ordering::Relaxed), 2);
+        assert!(err.to_string().starts_with("failed to write log entry"));
     }
 
     #[test]
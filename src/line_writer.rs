@@ -0,0 +1,115 @@
+//! An `io::Write` adapter that turns written lines into log entries.
+
+use std::io::{self, Write};
+
+use crate::{Level, Logger};
+
+/// Adapts a [`Logger`] into an [`io::Write`] sink, logging one entry per line.
+///
+/// Bytes written to a `LogWriter` are buffered until a newline is seen; each
+/// complete line is then logged through the wrapped logger at the configured
+/// level. This makes it possible to capture the output of subsystems that only
+/// know how to write plain text to a `Write` implementation.
+///
+/// Trailing `\r` characters are stripped so CRLF input produces clean messages,
+/// and invalid UTF-8 is replaced rather than rejected. Any partial line still
+/// buffered when the writer is dropped is logged as a final entry.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::Write;
+/// use ccb::{Level, LogWriter, Logger};
+///
+/// let mut writer = LogWriter::new(Logger::new(), Level::Info);
+/// writeln!(writer, "subsystem ready").unwrap();
+/// write!(writer, "partial line").unwrap(); // logged when `writer` is dropped
+/// ```
+#[derive(Debug)]
+pub struct LogWriter {
+    /// The logger that receives each complete line.
+    logger: Logger,
+    /// The level every line is logged at.
+    level: Level,
+    /// Bytes received since the last newline.
+    buffer: Vec<u8>,
+}
+
+impl LogWriter {
+    /// Creates a writer that logs each line through `logger` at `level`.
+    ///
+    /// # Arguments
+    ///
+    /// * `logger` - The logger used to emit entries
+    /// * `level` - The level assigned to every logged line
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::{Level, LogWriter, Logger};
+    ///
+    /// let writer = LogWriter::new(Logger::new().with("source", "child"), Level::Debug);
+    /// ```
+    pub fn new(logger: Logger, level: Level) -> Self {
+        Self {
+            logger,
+            level,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Logs a single line, stripping a trailing carriage return.
+    fn emit(&self, line: &[u8]) {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        self.logger
+            .log(self.level, &String::from_utf8_lossy(line), &[]);
+    }
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+
+        if let Some(last_newline) = self.buffer.iter().rposition(|&b| b == b'\n') {
+            let remainder = self.buffer.split_off(last_newline + 1);
+            let complete = std::mem::replace(&mut self.buffer, remainder);
+            for line in complete[..last_newline].split(|&b| b == b'\n') {
+                self.emit(line);
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    /// Partial lines are kept buffered so that a flush never splits a line
+    /// into two entries.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        if !self.buffer.is_empty() {
+            let remainder = std::mem::take(&mut self.buffer);
+            self.emit(&remainder);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Verifies that complete lines are consumed and partial lines stay buffered.
+    fn test_partial_lines_are_buffered() {
+        let mut writer = LogWriter::new(Logger::new().with_level(Level::Error), Level::Info);
+
+        writer.write_all(b"first\nsecond\nthi").unwrap();
+        assert_eq!(writer.buffer, b"thi");
+
+        writer.write_all(b"rd\n").unwrap();
+        assert!(writer.buffer.is_empty());
+    }
+}
@@ -0,0 +1,109 @@
+//! Logging panics through the current logger.
+
+use std::cell::Cell;
+use std::panic;
+use std::sync::Once;
+
+use crate::{lock_global_logger, Level, Logger, HOLDS_GLOBAL_LOGGER, THREAD_LOGGER};
+
+/// Guards the hook installation so it happens at most once.
+static INSTALL: Once = Once::new();
+
+thread_local! {
+    /// Set while this thread logs a panic, so a panic raised by logging it
+    /// isn't logged again.
+    static IN_PANIC_HOOK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Installs a panic hook that logs every panic at `Error` level.
+///
+/// The entry goes to the current logger (see
+/// [`with_current_logger`](crate::with_current_logger)) with the panic message
+/// as `payload`, its source position as `location`, and the panicking thread
+/// as `thread`. The previously installed hook runs afterwards, so the default
+/// message and backtrace still appear on stderr. Calling this again has no
+/// effect.
+///
+/// If the panic happens while the current logger is in use on the same
+/// thread, for example inside [`with_global_logger`](crate::with_global_logger),
+/// the entry is skipped rather than risking a deadlock.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{set_global_logger, Logger};
+///
+/// let (logger, capture) = Logger::capturing();
+/// set_global_logger(logger.with_timestamp(false));
+/// ccb::capture_panics();
+///
+/// let _ = std::thread::spawn(|| panic!("disk on fire")).join();
+///
+/// let lines = capture.lines();
+/// assert!(lines[0].starts_with("ERRO Thread panicked"));
+/// assert!(lines[0].contains("payload=disk on fire"));
+/// ```
+pub fn capture_panics() {
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let payload = info
+                .payload()
+                .downcast_ref::<&str>()
+                .map(|payload| payload.to_string())
+                .or_else(|| info.payload().downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "Box<dyn Any>".to_string());
+            let location = info.location().map_or("unknown".to_string(), |location| {
+                format!(
+                    "{}:{}:{}",
+                    location.file(),
+                    location.line(),
+                    location.column()
+                )
+            });
+            let thread = std::thread::current();
+            let thread = thread.name().unwrap_or("unnamed");
+
+            let reentered = IN_PANIC_HOOK.with(|flag| flag.replace(true));
+            if !reentered {
+                with_available_logger(|logger| {
+                    logger.log(
+                        Level::Error,
+                        "Thread panicked",
+                        &[
+                            ("payload", &payload),
+                            ("location", &location),
+                            ("thread", thread),
+                        ],
+                    );
+                });
+                IN_PANIC_HOOK.with(|flag| flag.set(false));
+            }
+
+            previous(info);
+        }));
+    });
+}
+
+/// Runs `f` with the current logger unless it is already borrowed or locked
+/// by this thread.
+fn with_available_logger(f: impl FnOnce(&Logger)) {
+    let f = THREAD_LOGGER
+        .try_with(|slot| match slot.try_borrow().as_deref() {
+            Ok(Some(logger)) => {
+                f(logger);
+                None
+            }
+            Ok(None) => Some(f),
+            Err(_) => None,
+        })
+        .ok()
+        .flatten();
+    let Some(f) = f else {
+        return;
+    };
+    if HOLDS_GLOBAL_LOGGER.try_with(Cell::get).unwrap_or(true) {
+        return;
+    }
+    f(&lock_global_logger());
+}
@@ -0,0 +1,248 @@
+//! A log file that rotates once it reaches a size limit.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::{report_error, CcbError, Level, Logger};
+
+/// A file writer that moves `app.log` to `app.log.1`, shifting older files up,
+/// before a write would take it past `max_bytes`.
+#[derive(Debug)]
+pub(crate) struct RotatingFile {
+    /// The path of the active file.
+    path: PathBuf,
+    /// The size the active file may grow to before rotating.
+    max_bytes: u64,
+    /// How many rotated files are kept next to the active one.
+    max_files: usize,
+    /// The active file; `None` while it is closed for rotation.
+    file: Option<File>,
+    /// The size of the active file.
+    written: u64,
+}
+
+impl RotatingFile {
+    /// Opens `path` for appending, counting its existing contents.
+    pub(crate) fn open(path: &Path, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        let mut rotating = Self {
+            path: path.to_path_buf(),
+            max_bytes,
+            max_files,
+            file: None,
+            written: 0,
+        };
+        rotating.reopen()?;
+        Ok(rotating)
+    }
+
+    /// Returns the path of the `index`th rotated file, such as `app.log.1`.
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+
+    /// Opens the active file and picks up its current size.
+    pub(crate) fn reopen(&mut self) -> io::Result<()> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.written = file.metadata()?.len();
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// Shifts the rotated files up by one and starts a fresh active file.
+    ///
+    /// Renames are best-effort: a file that is missing or can't be moved, for
+    /// example because another process holds it open on Windows, is skipped
+    /// and writing continues in the current file. Its size keeps it over the
+    /// limit, so rotation is retried on the next write.
+    pub(crate) fn rotate(&mut self) -> io::Result<()> {
+        // Windows refuses to rename a file that is still open
+        self.file = None;
+        if self.max_files == 0 {
+            let _ = fs::remove_file(&self.path);
+        } else {
+            let _ = fs::remove_file(self.rotated_path(self.max_files));
+            for index in (1..self.max_files).rev() {
+                let _ = fs::rename(self.rotated_path(index), self.rotated_path(index + 1));
+            }
+            let _ = fs::rename(&self.path, self.rotated_path(1));
+        }
+        self.reopen()
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        if self.file.is_none() {
+            self.reopen()?;
+        }
+        let file = self.file.as_mut().expect("file was just opened");
+        let written = file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Logger {
+    /// Writes entries to a file that rotates once it reaches `max_bytes`.
+    ///
+    /// Before an entry would take the file past `max_bytes`, `app.log` is
+    /// renamed to `app.log.1`, `app.log.1` to `app.log.2`, and so on, dropping
+    /// the oldest beyond `max_files`, and a fresh `app.log` is started. An
+    /// entry larger than `max_bytes` still goes into a file of its own. With
+    /// `max_files` set to 0, the file is simply started over. Like
+    /// [`Logger::with_writer`], this disables detected colors.
+    ///
+    /// If the file can't be opened, a warning is logged and output stays
+    /// unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The active log file, created if needed and appended to
+    /// * `max_bytes` - The size at which the file is rotated
+    /// * `max_files` - How many rotated files to keep
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use ccb::Logger;
+    ///
+    /// // Keeps app.log plus app.log.1 to app.log.5, each up to 10 MiB
+    /// let logger = Logger::new().with_rotating_file("app.log", 10 * 1024 * 1024, 5);
+    /// logger.info("Started", &[]);
+    /// ```
+    #[track_caller]
+    pub fn with_rotating_file<P: AsRef<Path>>(
+        self,
+        path: P,
+        max_bytes: u64,
+        max_files: usize,
+    ) -> Self {
+        let path = path.as_ref();
+        match RotatingFile::open(path, max_bytes, max_files) {
+            Ok(file) => {
+                let file = Arc::new(Mutex::new(file));
+                let mut logger = self.with_writer(file.clone());
+                logger.rotating_file = Some(file);
+                logger
+            }
+            Err(err) => {
+                let path = path.display().to_string();
+                let error = err.to_string();
+                self.log_internal(
+                    Level::Warn,
+                    "Log file unavailable, keeping current output",
+                    &[("path", &path), ("error", &error)],
+                );
+                report_error(&CcbError::Io(err));
+                self
+            }
+        }
+    }
+
+    /// Rotates the file of [`Logger::with_rotating_file`] now, regardless of
+    /// its size.
+    ///
+    /// Pending output is flushed first, so it ends up in the file being
+    /// rotated. This suits a signal handler or a nightly job that rotates on
+    /// a schedule. Rotation happens under the file's lock, so it is safe while
+    /// other threads are logging; their entries go either to the old file or
+    /// to the fresh one. Without a rotating file, this only flushes.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from opening the fresh file. As with size-based
+    /// rotation, renames that fail are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new().with_rotating_file("app.log", 10 * 1024 * 1024, 5);
+    /// logger.info("End of day", &[]);
+    /// logger.rotate().expect("rotate app.log");
+    /// ```
+    pub fn rotate(&self) -> io::Result<()> {
+        self.flush();
+        let Some(file) = &self.rotating_file else {
+            return Ok(());
+        };
+        let mut file = file.lock().unwrap_or_else(PoisonError::into_inner);
+        file.flush()?;
+        file.rotate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Verifies that files are shifted up and the oldest dropped on rotation.
+    fn test_rotation() {
+        let dir = std::env::temp_dir().join(format!("ccb-rotate-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+
+        let logger = Logger::new()
+            .with_timestamp(false)
+            .with_rotating_file(&path, 20, 2);
+        for i in 0..4 {
+            // Each entry is "INFO Entry i=N\n", 15 bytes
+            logger.info("Entry", &[("i", &i.to_string())]);
+        }
+
+        let read = |path: &Path| fs::read_to_string(path).unwrap();
+        assert_eq!(read(&path), "INFO Entry i=3\n");
+        assert_eq!(read(&dir.join("app.log.1")), "INFO Entry i=2\n");
+        assert_eq!(read(&dir.join("app.log.2")), "INFO Entry i=1\n");
+        assert!(!dir.join("app.log.3").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    /// Verifies that `rotate` rotates below the size limit, after flushing.
+    fn test_manual_rotation() {
+        let dir = std::env::temp_dir().join(format!("ccb-rotate-now-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+
+        let logger = Logger::new()
+            .with_timestamp(false)
+            .with_rotating_file(&path, 1024 * 1024, 2)
+            .with_buffering(4096);
+        logger.info("Yesterday", &[]);
+        logger.rotate().unwrap();
+        logger.info("Today", &[]);
+        logger.flush();
+
+        let read = |path: &Path| fs::read_to_string(path).unwrap();
+        assert_eq!(read(&dir.join("app.log.1")), "INFO Yesterday\n");
+        assert_eq!(read(&path), "INFO Today\n");
+        assert!(Logger::new().rotate().is_ok());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,300 @@
+//! Explicit span start/end events sharing a generated span id.
+
+use std::fmt;
+use std::time::Instant;
+
+use crate::{Level, Logger};
+
+/// Identifies a span opened with [`Logger::span_start`].
+///
+/// The id comes from the logger's [`IdGenerator`](crate::IdGenerator) and is
+/// attached to both the start and end events as a `span_id` field, so a log
+/// processor can pair them up and reconstruct durations even when the span
+/// ends on another thread.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::Logger;
+///
+/// let logger = Logger::new();
+/// let span = logger.span_start("load_config", &[("path", "app.toml")]);
+/// println!("opened span {}", span);
+/// logger.span_end(span, &[("keys", "12")]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanId {
+    /// The generated span id.
+    id: String,
+    /// The span name, repeated on the end event.
+    name: String,
+    /// When the span was started, used to report its duration.
+    started: Instant,
+}
+
+impl SpanId {
+    /// Returns the id shared by the span's start and end events.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the name the span was started with.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for SpanId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
+
+/// A span that ends itself when dropped, created with [`Logger::span`].
+///
+/// Dropping the guard logs the span's end event, with its duration as
+/// `elapsed_ms`, just as [`Logger::span_end`] does. Fields given when the span
+/// was opened are included in both the start and end events.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{Level, Logger};
+///
+/// let (logger, capture) = Logger::capturing();
+/// let logger = logger
+///     .with_level(Level::Debug)
+///     .with_timestamp(false)
+///     .with_sorted_fields(true)
+///     .with_id_generator(|| "s1".to_string());
+///
+/// {
+///     let _span = logger.span_with_fields("import", &[("file", "users.csv")]);
+///     // ... do the work ...
+/// }
+///
+/// let lines = capture.lines();
+/// assert_eq!(lines[0], "DEBG import file=users.csv span_event=start span_id=s1");
+/// assert!(lines[1].starts_with("DEBG import elapsed_ms="));
+/// assert!(lines[1].ends_with("file=users.csv span_event=end span_id=s1"));
+/// ```
+#[derive(Debug)]
+#[must_use = "the span ends as soon as the guard is dropped"]
+pub struct Span<'a> {
+    /// The logger the end event is written to.
+    logger: &'a Logger,
+    /// The span's id, taken when the span ends.
+    id: Option<SpanId>,
+    /// Fields included in both events.
+    fields: Vec<(String, String)>,
+}
+
+impl Span<'_> {
+    /// Returns the id shared by the span's start and end events.
+    pub fn id(&self) -> &str {
+        self.id.as_ref().map_or("", SpanId::id)
+    }
+}
+
+impl Drop for Span<'_> {
+    fn drop(&mut self) {
+        if let Some(id) = self.id.take() {
+            let fields: Vec<_> = self
+                .fields
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str()))
+                .collect();
+            self.logger.span_end(id, &fields);
+        }
+    }
+}
+
+impl Logger {
+    /// Starts a span that logs its end event when the returned guard is dropped.
+    ///
+    /// The start event is logged right away at debug level, as with
+    /// [`Logger::span_start`]. Use [`Logger::span_with_fields`] to attach
+    /// fields to both events.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The span name, used as the message of both events
+    ///
+    /// # Returns
+    ///
+    /// A [`Span`] guard that ends the span when dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new();
+    /// let _span = logger.span("rebuild_index");
+    /// // DEBG rebuild_index span_event=start span_id=...
+    /// // ... and on drop:
+    /// // DEBG rebuild_index elapsed_ms=42 span_event=end span_id=...
+    /// ```
+    #[track_caller]
+    pub fn span(&self, name: &str) -> Span<'_> {
+        self.span_with_fields(name, &[])
+    }
+
+    /// Starts a span with fields that are logged on both its start and end
+    /// events, ending it when the returned guard is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The span name, used as the message of both events
+    /// * `fields` - Key-value pairs included in both events
+    ///
+    /// # Returns
+    ///
+    /// A [`Span`] guard that ends the span when dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new();
+    /// let _span = logger.span_with_fields("request", &[("path", "/users")]);
+    /// ```
+    #[track_caller]
+    pub fn span_with_fields(&self, name: &str, fields: &[(&str, &str)]) -> Span<'_> {
+        Span {
+            logger: self,
+            id: Some(self.span_start(name, fields)),
+            fields: fields
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Starts a span and logs its start event at debug level.
+    ///
+    /// The start event uses `name` as its message and carries `span_id` and
+    /// `span_event=start` fields in addition to `fields`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The span name
+    /// * `fields` - Additional key-value pairs for the start event
+    ///
+    /// # Returns
+    ///
+    /// The span's id, to be passed to [`Logger::span_end`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new();
+    /// let span = logger.span_start("request", &[("path", "/users")]);
+    /// ```
+    #[track_caller]
+    pub fn span_start(&self, name: &str, fields: &[(&str, &str)]) -> SpanId {
+        let span = SpanId {
+            id: self.config.id_generator.generate(),
+            name: name.to_string(),
+            started: Instant::now(),
+        };
+        self.log_span_event(&span, "start", fields);
+        span
+    }
+
+    /// Ends a span and logs its end event at debug level.
+    ///
+    /// The end event repeats the span's name and `span_id`, and carries
+    /// `span_event=end` and the span's duration as `elapsed_ms` in addition to
+    /// `fields`. The span may be ended through any logger on any thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `span` - The span returned by [`Logger::span_start`]
+    /// * `fields` - Additional key-value pairs for the end event
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let logger = Logger::new();
+    /// let span = logger.span_start("request", &[]);
+    /// logger.span_end(span, &[("status", "200")]);
+    /// ```
+    #[track_caller]
+    pub fn span_end(&self, span: SpanId, fields: &[(&str, &str)]) {
+        let elapsed_ms = span.started.elapsed().as_millis().to_string();
+        let mut fields = fields.to_vec();
+        fields.push(("elapsed_ms", &elapsed_ms));
+        self.log_span_event(&span, "end", &fields);
+    }
+
+    /// Logs a span event with the span's id and event kind attached.
+    #[track_caller]
+    fn log_span_event(&self, span: &SpanId, event: &str, fields: &[(&str, &str)]) {
+        let mut fields = fields.to_vec();
+        fields.push(("span_id", &span.id));
+        fields.push(("span_event", event));
+        self.log(Level::Debug, &span.name, &fields);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Verifies that span ids are unique and that spans keep their name.
+    fn test_span_ids_are_unique() {
+        let logger = Logger::new().with_level(Level::Error);
+
+        let first = logger.span_start("first", &[]);
+        let second = logger.span_start("second", &[]);
+
+        assert_ne!(first.id(), second.id());
+        assert_eq!(first.name(), "first");
+        assert_eq!(second.to_string(), second.id());
+
+        logger.span_end(first, &[]);
+        logger.span_end(second, &[]);
+    }
+
+    #[test]
+    /// Tests that span ids come from the logger's id generator.
+    fn test_span_ids_use_generator() {
+        let logger = Logger::new()
+            .with_level(Level::Error)
+            .with_id_generator(|| "fixed".to_string());
+
+        assert_eq!(logger.span_start("work", &[]).id(), "fixed");
+    }
+
+    #[test]
+    /// Verifies that a span guard logs its end event, with its fields, on drop.
+    fn test_span_guard() {
+        let (logger, capture) = Logger::capturing();
+        let logger = logger
+            .with_level(Level::Debug)
+            .with_timestamp(false)
+            .with_sorted_fields(true)
+            .with_id_generator(|| "g".to_string());
+
+        let span = logger.span_with_fields("sync", &[("peer", "b")]);
+        assert_eq!(span.id(), "g");
+        assert_eq!(capture.lines().len(), 1);
+        drop(span);
+        drop(logger.span("tick"));
+
+        let lines = capture.lines();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "DEBG sync peer=b span_event=start span_id=g");
+        assert!(lines[1].starts_with("DEBG sync elapsed_ms="));
+        assert!(lines[1].ends_with(" peer=b span_event=end span_id=g"));
+        assert_eq!(lines[2], "DEBG tick span_event=start span_id=g");
+        assert!(lines[3].ends_with(" span_event=end span_id=g"));
+    }
+}
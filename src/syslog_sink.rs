@@ -0,0 +1,131 @@
+//! Writing entries to the local syslog daemon.
+
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use syslog::{Facility, Formatter3164, LoggerBackend};
+
+use crate::format::escape_logfmt;
+use crate::{Level, LogEntry};
+
+/// A connection to the local syslog socket, shared between clones of a logger.
+#[derive(Clone)]
+pub(crate) struct SyslogSink(Arc<Mutex<syslog::Logger<LoggerBackend, Formatter3164>>>);
+
+impl fmt::Debug for SyslogSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SyslogSink")
+    }
+}
+
+/// The syslog severities CCB levels map to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    Err,
+    Warning,
+    Notice,
+    Info,
+    Debug,
+}
+
+impl SyslogSink {
+    /// Connects to the local syslog socket, tagging messages with the current
+    /// process name and id.
+    pub(crate) fn connect(facility: Facility) -> io::Result<Self> {
+        let process = std::env::current_exe()
+            .ok()
+            .and_then(|path| path.file_name()?.to_str().map(String::from))
+            .unwrap_or_else(|| "ccb".to_string());
+        let formatter = Formatter3164 {
+            facility,
+            hostname: None,
+            process,
+            pid: std::process::id(),
+        };
+        syslog::unix(formatter)
+            .map(|logger| Self(Arc::new(Mutex::new(logger))))
+            .map_err(|err| io::Error::other(err.to_string()))
+    }
+
+    /// Sends an entry at the syslog severity matching its level.
+    pub(crate) fn send(&self, entry: &LogEntry) -> io::Result<()> {
+        let body = message_body(entry);
+        let mut logger = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let result = match severity(entry.level) {
+            Severity::Err => logger.err(body),
+            Severity::Warning => logger.warning(body),
+            Severity::Notice => logger.notice(body),
+            Severity::Info => logger.info(body),
+            Severity::Debug => logger.debug(body),
+        };
+        result.map_err(|err| io::Error::other(err.to_string()))
+    }
+}
+
+/// Maps a level to a syslog severity by its numeric severity, so custom levels
+/// land next to the built-ins they sort between. Levels between `Info` and
+/// `Warn`, such as a custom `NOTE` level, become `notice`.
+fn severity(level: Level) -> Severity {
+    match level.severity() {
+        40.. => Severity::Err,
+        30.. => Severity::Warning,
+        21.. => Severity::Notice,
+        20 => Severity::Info,
+        _ => Severity::Debug,
+    }
+}
+
+/// Returns the message, prefixed with the target if any, followed by the
+/// fields as sorted `key=value` pairs, since classic syslog messages have no
+/// structured data.
+fn message_body(entry: &LogEntry) -> String {
+    let mut fields: Vec<_> = entry.fields.iter().collect();
+    fields.sort_unstable_by_key(|(key, _)| *key);
+    let mut body = match &entry.target {
+        Some(target) => format!("{}: {}", target, entry.message),
+        None => entry.message.clone(),
+    };
+    for (key, value) in fields {
+        body.push_str(&format!(" {}={}", key, escape_logfmt(value)));
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+    use chrono::Local;
+
+    #[test]
+    /// Verifies the level mapping and that fields are appended to the body.
+    fn test_syslog_message() {
+        assert_eq!(severity(Level::Trace), Severity::Debug);
+        assert_eq!(severity(Level::Info), Severity::Info);
+        assert_eq!(
+            severity(Level::Custom(25, "NOTE", Color::Magenta)),
+            Severity::Notice
+        );
+        assert_eq!(severity(Level::Warn), Severity::Warning);
+        assert_eq!(severity(Level::Error), Severity::Err);
+
+        let entry = LogEntry {
+            level: Level::Info,
+            message: "Request done".to_string(),
+            fields: [("status", "200"), ("path", "/my file")]
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            timestamp: Local::now(),
+            location: None,
+            target: None,
+            trace: None,
+            inherited_keys: Default::default(),
+        };
+        assert_eq!(
+            message_body(&entry),
+            r#"Request done path="/my file" status=200"#
+        );
+    }
+}
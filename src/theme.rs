@@ -0,0 +1,133 @@
+//! Level color themes and text styles for the pretty format.
+
+use termcolor::Color;
+
+use crate::{Level, MUTED};
+
+/// The colors the pretty format uses for level labels.
+///
+/// A level without a color is still shown in bold. Use
+/// [`Logger::with_theme`](crate::Logger::with_theme) to remap colors, for
+/// example for light terminals where the default green is hard to read.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{Color, Level, Logger, Theme};
+///
+/// let theme = Theme {
+///     info: Some(Color::Blue),
+///     ..Theme::default()
+/// };
+/// assert_eq!(theme.color(Level::Info), Some(Color::Blue));
+///
+/// let logger = Logger::new().with_theme(theme);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Theme {
+    /// Color of `TRCE` labels.
+    #[cfg_attr(feature = "serde", serde(with = "crate::color::serde_option_color"))]
+    pub trace: Option<Color>,
+    /// Color of `DEBG` labels.
+    #[cfg_attr(feature = "serde", serde(with = "crate::color::serde_option_color"))]
+    pub debug: Option<Color>,
+    /// Color of `INFO` labels.
+    #[cfg_attr(feature = "serde", serde(with = "crate::color::serde_option_color"))]
+    pub info: Option<Color>,
+    /// Color of `WARN` labels.
+    #[cfg_attr(feature = "serde", serde(with = "crate::color::serde_option_color"))]
+    pub warn: Option<Color>,
+    /// Color of `ERRO` labels.
+    #[cfg_attr(feature = "serde", serde(with = "crate::color::serde_option_color"))]
+    pub error: Option<Color>,
+    /// Whether custom levels are shown in their own color.
+    pub custom_colors: bool,
+}
+
+impl Theme {
+    /// Creates a theme without color differentiation: every level label is
+    /// shown in bold in the terminal's default color.
+    pub fn monochrome() -> Self {
+        Self {
+            trace: None,
+            debug: None,
+            info: None,
+            warn: None,
+            error: None,
+            custom_colors: false,
+        }
+    }
+
+    /// Returns the color for labels of `level`, if any.
+    pub fn color(&self, level: Level) -> Option<Color> {
+        match level {
+            Level::Trace => self.trace,
+            Level::Debug => self.debug,
+            Level::Info => self.info,
+            Level::Warn => self.warn,
+            Level::Error => self.error,
+            Level::Custom(_, _, color) => Some(color).filter(|_| self.custom_colors),
+            Level::Off => None,
+        }
+    }
+}
+
+impl Default for Theme {
+    /// Creates the default theme, which uses each level's [`Level::color`].
+    fn default() -> Self {
+        Self {
+            trace: Some(Level::Trace.color()),
+            debug: Some(Level::Debug.color()),
+            info: Some(Level::Info.color()),
+            warn: Some(Level::Warn.color()),
+            error: Some(Level::Error.color()),
+            custom_colors: true,
+        }
+    }
+}
+
+/// Text styling of the pretty format beyond level colors.
+///
+/// Use [`Logger::with_style`](crate::Logger::with_style) to, for example, turn
+/// off bold level labels on terminals that render bold oddly while keeping
+/// colors. Styles only take effect when colors are enabled.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{Color, Logger, StyleConfig};
+///
+/// let style = StyleConfig {
+///     bold_level: false,
+///     key_color: Some(Color::Cyan),
+///     ..StyleConfig::default()
+/// };
+/// let logger = Logger::new().with_style(style);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct StyleConfig {
+    /// Whether level labels are bold.
+    pub bold_level: bool,
+    /// Color of field keys, or `None` for the terminal's default color.
+    #[cfg_attr(feature = "serde", serde(with = "crate::color::serde_option_color"))]
+    pub key_color: Option<Color>,
+    /// Color of messages, or `None` for the terminal's default color.
+    #[cfg_attr(feature = "serde", serde(with = "crate::color::serde_option_color"))]
+    pub message_color: Option<Color>,
+}
+
+impl Default for StyleConfig {
+    /// Creates the default style: bold level labels, gray field keys, and
+    /// messages in the terminal's default color.
+    fn default() -> Self {
+        Self {
+            bold_level: true,
+            key_color: Some(MUTED),
+            message_color: None,
+        }
+    }
+}
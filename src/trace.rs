@@ -0,0 +1,224 @@
+//! Trace and span ids for correlating entries with distributed traces.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::Logger;
+
+thread_local! {
+    /// The trace context entered with [`enter_trace_context`], if any.
+    static CURRENT_TRACE: RefCell<Option<TraceContext>> = const { RefCell::new(None) };
+}
+
+/// The ids of the trace and span an entry was logged in, in the manner of
+/// OpenTelemetry.
+///
+/// Kept apart from ordinary context fields, a trace context is rendered in a
+/// dedicated position: after the level in the pretty format, and as top-level
+/// `trace_id` and `span_id` keys in the JSON and logfmt formats. Attach one to
+/// a logger with [`Logger::with_trace_context`], or to the current thread with
+/// [`enter_trace_context`].
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::TraceContext;
+///
+/// let trace = TraceContext::new("4bf92f3577b34da6a3ce929d0e0e4736", "00f067aa0ba902b7");
+/// assert_eq!(trace.to_string(), "4bf92f3577b34da6a3ce929d0e0e4736/00f067aa0ba902b7");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TraceContext {
+    /// The id of the trace, shared by every span in it.
+    pub trace_id: String,
+    /// The id of the active span within the trace.
+    pub span_id: String,
+}
+
+impl TraceContext {
+    /// Creates a trace context from a trace id and a span id.
+    ///
+    /// The ids are used as given; no particular length or encoding is
+    /// required.
+    ///
+    /// # Arguments
+    ///
+    /// * `trace_id` - The id of the trace
+    /// * `span_id` - The id of the active span
+    pub fn new<T: Into<String>, S: Into<String>>(trace_id: T, span_id: S) -> Self {
+        Self {
+            trace_id: trace_id.into(),
+            span_id: span_id.into(),
+        }
+    }
+}
+
+/// Writes the ids as `trace_id/span_id`, as shown in the pretty format.
+impl fmt::Display for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.trace_id, self.span_id)
+    }
+}
+
+/// Attaches a trace context to every entry logged on the current thread until
+/// the returned guard is dropped.
+///
+/// Use this when a span becomes active on a thread, for example when a request
+/// handler starts. It applies to all loggers, except those with their own
+/// [`Logger::with_trace_context`], which takes precedence. Entering a new
+/// context replaces the current one until its guard is dropped.
+///
+/// # Arguments
+///
+/// * `trace_id` - The id of the trace
+/// * `span_id` - The id of the span becoming active
+///
+/// # Returns
+///
+/// A guard that restores the previous trace context when dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{enter_trace_context, Format, Logger};
+///
+/// let (logger, capture) = Logger::capturing();
+/// let logger = logger.with_format(Format::Json);
+///
+/// {
+///     let _trace = enter_trace_context("4bf92f3577b34da6a3ce929d0e0e4736", "00f067aa0ba902b7");
+///     logger.info("Handling request", &[]);
+/// }
+/// logger.info("Idle", &[]);
+///
+/// let lines = capture.lines();
+/// assert!(lines[0].contains(r#""trace_id":"4bf92f3577b34da6a3ce929d0e0e4736""#));
+/// assert!(!lines[1].contains("trace_id"));
+/// ```
+#[must_use = "the trace context is removed as soon as the guard is dropped"]
+pub fn enter_trace_context<T: Into<String>, S: Into<String>>(
+    trace_id: T,
+    span_id: S,
+) -> TraceContextGuard {
+    let trace = TraceContext::new(trace_id, span_id);
+    let previous = CURRENT_TRACE.with(|current| current.borrow_mut().replace(trace));
+    TraceContextGuard {
+        previous,
+        _not_send: PhantomData,
+    }
+}
+
+/// Restores the previous trace context of an [`enter_trace_context`] scope
+/// when dropped.
+///
+/// The guard belongs to the thread that created it and can't be sent to
+/// another thread.
+#[derive(Debug)]
+pub struct TraceContextGuard {
+    /// The trace context that was current before this one was entered.
+    previous: Option<TraceContext>,
+    /// Keeps the guard on the thread whose trace context it restores.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for TraceContextGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        let _ = CURRENT_TRACE.try_with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+/// Returns the trace context entered on the current thread, if any.
+pub(crate) fn current_trace_context() -> Option<TraceContext> {
+    CURRENT_TRACE
+        .try_with(|current| current.borrow().clone())
+        .ok()
+        .flatten()
+}
+
+/// Replaces the trace context of the current thread, as when restoring a
+/// [`ContextSnapshot`](crate::ContextSnapshot).
+pub(crate) fn set_current_trace_context(trace: Option<TraceContext>) {
+    CURRENT_TRACE.with(|current| *current.borrow_mut() = trace);
+}
+
+impl Logger {
+    /// Attaches a trace context to every entry of this logger.
+    ///
+    /// The ids are stored apart from the [`with`](Logger::with) context and
+    /// rendered in a dedicated position: after the level in the pretty
+    /// format, and as top-level `trace_id` and `span_id` keys in the JSON and
+    /// logfmt formats. This takes precedence over a trace context entered on
+    /// the thread with [`enter_trace_context`].
+    ///
+    /// # Arguments
+    ///
+    /// * `trace_id` - The id of the trace
+    /// * `span_id` - The id of the active span
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ccb::Logger;
+    ///
+    /// let (logger, capture) = Logger::capturing();
+    /// let logger = logger
+    ///     .with_timestamp(false)
+    ///     .with_trace_context("4bf92f35", "00f067aa");
+    ///
+    /// logger.info("Charged card", &[("amount", "12")]);
+    /// assert_eq!(capture.lines(), ["INFO 4bf92f35/00f067aa Charged card amount=12"]);
+    /// ```
+    pub fn with_trace_context<T: Into<String>, S: Into<String>>(
+        mut self,
+        trace_id: T,
+        span_id: S,
+    ) -> Self {
+        self.trace_context = Some(TraceContext::new(trace_id, span_id));
+        self
+    }
+
+    /// Returns the trace context for a new entry: the logger's own, or else
+    /// the one entered on the current thread.
+    pub(crate) fn active_trace_context(&self) -> Option<TraceContext> {
+        self.trace_context.clone().or_else(current_trace_context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Level;
+
+    #[test]
+    /// Verifies nesting of thread trace contexts and the logger's precedence.
+    fn test_trace_context_precedence() {
+        let logger = Logger::new();
+        assert_eq!(logger.active_trace_context(), None);
+
+        let outer = enter_trace_context("t1", "s1");
+        {
+            let _inner = enter_trace_context("t1", "s2");
+            assert_eq!(
+                logger.active_trace_context(),
+                Some(TraceContext::new("t1", "s2"))
+            );
+        }
+        let entry = logger.build_entry(Level::Info, "msg", &[]);
+        assert_eq!(entry.trace, Some(TraceContext::new("t1", "s1")));
+
+        let own = logger.with_trace_context("t9", "s9");
+        assert_eq!(
+            own.active_trace_context(),
+            Some(TraceContext::new("t9", "s9"))
+        );
+        drop(outer);
+        assert_eq!(Logger::new().active_trace_context(), None);
+    }
+}
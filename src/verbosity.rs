@@ -0,0 +1,100 @@
+//! Standard `-v`/`-q` verbosity flags for clap-based CLIs.
+
+use crate::{Level, Logger};
+
+/// Verbosity flags that resolve to a log [`Level`].
+///
+/// Flatten this into a clap `Parser` to get the conventional `-v/--verbose`
+/// and `-q/--quiet` flags. Starting from `Info`, each `-v` lowers the level by
+/// one step (`-v` is `Debug`, `-vv` is `Trace`) and each `-q` raises it (`-q`
+/// is `Warn`, `-qq` is `Error`). The result is clamped to the available levels.
+///
+/// Requires the `clap` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// use ccb::{Level, Logger, Verbosity};
+/// use clap::Parser;
+///
+/// #[derive(Parser)]
+/// struct Cli {
+///     #[command(flatten)]
+///     verbosity: Verbosity,
+/// }
+///
+/// let cli = Cli::parse_from(["app", "-vv"]);
+/// assert_eq!(cli.verbosity.level(), Level::Trace);
+///
+/// let logger = Logger::new().with_level_from_verbosity_flag(&cli.verbosity);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::Args)]
+#[command(about = None, long_about = None)]
+pub struct Verbosity {
+    /// Increase logging verbosity (repeat for more detail)
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+    /// Decrease logging verbosity (repeat to show only errors)
+    #[arg(short, long, action = clap::ArgAction::Count, global = true, conflicts_with = "verbose")]
+    quiet: u8,
+}
+
+impl Verbosity {
+    /// Returns the log level selected by the flags.
+    pub fn level(&self) -> Level {
+        const LEVELS: [Level; 5] = [
+            Level::Trace,
+            Level::Debug,
+            Level::Info,
+            Level::Warn,
+            Level::Error,
+        ];
+        let index = 2 + i16::from(self.quiet) - i16::from(self.verbose);
+        LEVELS[index.clamp(0, 4) as usize]
+    }
+}
+
+impl Logger {
+    /// Sets the minimum log level from parsed verbosity flags.
+    ///
+    /// Equivalent to `logger.with_level(flags.level())`. Requires the `clap`
+    /// feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `flags` - The parsed verbosity flags
+    ///
+    /// # Returns
+    ///
+    /// Returns `self` for method chaining.
+    pub fn with_level_from_verbosity_flag(self, flags: &Verbosity) -> Self {
+        self.with_level(flags.level())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        verbosity: Verbosity,
+    }
+
+    fn level_for(args: &[&str]) -> Level {
+        let args = std::iter::once("app").chain(args.iter().copied());
+        Cli::parse_from(args).verbosity.level()
+    }
+
+    #[test]
+    /// Verifies the mapping from flag counts to levels, including clamping.
+    fn test_verbosity_levels() {
+        assert_eq!(level_for(&[]), Level::Info);
+        assert_eq!(level_for(&["-v"]), Level::Debug);
+        assert_eq!(level_for(&["-vvv"]), Level::Trace);
+        assert_eq!(level_for(&["--quiet"]), Level::Warn);
+        assert_eq!(level_for(&["-qqqq"]), Level::Error);
+    }
+}